@@ -3,4 +3,5 @@ export enum MessageKind {
     Stats = "stats",
     Error = "error",
     Watch = "watch",
+    Account = "account",
 }
\ No newline at end of file
@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::Local;
+use log::{debug, info, warn};
+use tokio::sync::{Mutex, RwLock};
+use crate::api::model::active_provider_manager::ActiveProviderManager;
+use crate::messaging::{send_message, MsgKind};
+use crate::model::config::{AccountMonitorConfig, Config, ConfigInput, InputType, ProcessTargets};
+use crate::model::provider_account::{ProviderAccountInfo, ProviderAccountStatus};
+use crate::processing::processor::playlist;
+use crate::tools::lru_cache::LRUResourceCache;
+use crate::utils::network::xtream;
+
+pub type ProviderAccounts = RwLock<BTreeMap<String, ProviderAccountStatus>>;
+
+struct MonitoredAccount {
+    name: String,
+    input_name: String,
+    input: ConfigInput,
+    is_alias: bool,
+}
+
+fn get_monitored_accounts(cfg: &Config) -> Vec<MonitoredAccount> {
+    let mut accounts = vec![];
+    for input in cfg.sources.iter().flat_map(|source| source.inputs.iter())
+        .filter(|input| input.enabled && matches!(input.input_type, InputType::Xtream | InputType::M3u)) {
+        if input.get_user_info().is_some() {
+            accounts.push(MonitoredAccount { name: input.name.clone(), input_name: input.name.clone(), input: input.clone(), is_alias: false });
+        }
+        for alias in input.aliases.iter().flatten() {
+            let alias_input = input.create_alias_input(alias);
+            if alias_input.get_user_info().is_some() {
+                accounts.push(MonitoredAccount { name: alias.name.clone(), input_name: input.name.clone(), input: alias_input, is_alias: true });
+            }
+        }
+    }
+    accounts
+}
+
+async fn apply_max_connections(active_provider: &ActiveProviderManager, account: &MonitoredAccount, info: &ProviderAccountInfo) {
+    if account.input.auto_max_connections {
+        if let Some(max_connections) = info.max_connections.filter(|&max_connections| max_connections > 0) {
+            if active_provider.update_max_connections(&account.name, max_connections).await {
+                debug!("Provider account {} limited to {max_connections} connections", account.name);
+            }
+        }
+    }
+}
+
+/// Applies the `max_connections` of the provider accounts to the inputs and aliases with `auto_max_connections`.
+pub async fn update_provider_max_connections(client: Arc<reqwest::Client>, cfg: Arc<Config>, active_provider: Arc<ActiveProviderManager>) {
+    for account in get_monitored_accounts(&cfg).iter().filter(|account| account.input.auto_max_connections) {
+        match xtream::get_xtream_account_info(Arc::clone(&client), &account.input).await {
+            Ok(info) => apply_max_connections(&active_provider, account, &info).await,
+            Err(err) => warn!("Failed to read max connections of provider account {}: {err}", account.name),
+        }
+    }
+}
+
+/// Runs the playlist update, afterwards the connection limits are read again from the provider accounts.
+pub async fn exec_processing_and_update_connections(client: Arc<reqwest::Client>, resource_cache: Arc<Option<Mutex<LRUResourceCache>>>,
+                                                    cfg: Arc<Config>, targets: Arc<ProcessTargets>, active_provider: Arc<ActiveProviderManager>) {
+    playlist::exec_processing(Arc::clone(&client), resource_cache, Arc::clone(&cfg), targets).await;
+    update_provider_max_connections(client, cfg, active_provider).await;
+}
+
+async fn check_account(client: &Arc<reqwest::Client>, cfg: &Config, monitor: &AccountMonitorConfig,
+                       active_provider: &ActiveProviderManager, accounts: &ProviderAccounts, account: &MonitoredAccount) {
+    let now = Local::now().timestamp();
+    let previous = accounts.read().await.get(&account.name).cloned();
+    let (info, error) = match xtream::get_xtream_account_info(Arc::clone(client), &account.input).await {
+        Ok(info) => {
+            apply_max_connections(active_provider, account, &info).await;
+            (Some(info), None)
+        }
+        Err(err) => {
+            warn!("Failed to check provider account {}: {err}", account.name);
+            (None, Some(err.to_string()))
+        }
+    };
+
+    // keep the last known state if the provider could not be queried
+    let (info, alert, disabled) = match info {
+        Some(account_info) => {
+            let alert = account_info.get_alert(monitor.expiry_warning_days, now);
+            let disabled = monitor.disable_expired && account.is_alias && (account_info.is_expired(now) || !account_info.is_active());
+            (Some(account_info), alert, disabled)
+        }
+        None => previous.as_ref().map_or((None, None, false), |status| (status.info.clone(), status.alert.clone(), status.disabled)),
+    };
+
+    if let Some(account_alert) = alert.as_ref() {
+        let exp_date = info.as_ref().and_then(|account_info| account_info.exp_date);
+        if !previous.as_ref().is_some_and(|status| status.has_alert(account_alert, exp_date)) {
+            let msg = format!("Provider account {} of input {} {account_alert}", account.name, account.input_name);
+            warn!("{msg}");
+            send_message(&MsgKind::Account, cfg.messaging.as_ref(), &msg);
+        }
+    }
+
+    if previous.as_ref().map_or(disabled, |status| status.disabled != disabled)
+        && active_provider.set_provider_disabled(&account.name, disabled).await {
+        info!("Provider account {} {}", account.name, if disabled { "disabled" } else { "enabled" });
+    }
+
+    accounts.write().await.insert(account.name.clone(), ProviderAccountStatus {
+        name: account.name.clone(),
+        input: account.input_name.clone(),
+        info,
+        alert,
+        error,
+        disabled,
+        checked_at: now,
+    });
+}
+
+/// Polls the provider accounts in the configured interval, alerts are only sent when they change.
+pub async fn start_account_monitor(client: Arc<reqwest::Client>, cfg: Arc<Config>,
+                                   active_provider: Arc<ActiveProviderManager>, accounts: Arc<ProviderAccounts>) {
+    let Some(monitor) = cfg.account_monitor.as_ref().filter(|monitor| monitor.enabled) else {
+        return;
+    };
+    let monitored_accounts = get_monitored_accounts(&cfg);
+    if monitored_accounts.is_empty() {
+        debug!("No provider accounts to monitor");
+        return;
+    }
+    let interval = Duration::from_secs(u64::from(monitor.interval_mins) * 60);
+    loop {
+        for account in &monitored_accounts {
+            check_account(&client, &cfg, monitor, &active_provider, &accounts, account).await;
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
@@ -14,7 +14,7 @@ use crate::api::model::streams::shared_stream_manager::SharedStreamManager;
 use crate::api::model::streams::throttled_stream::ThrottledStream;
 use crate::auth::authenticator::Claims;
 use crate::model::api_proxy::{ProxyUserCredentials, UserConnectionPermission};
-use crate::model::config::{ConfigInput, ConfigTarget, InputFetchMethod, TargetType};
+use crate::model::config::{ConfigInput, ConfigTarget, InputFetchMethod, InputType, TargetType};
 use crate::model::playlist::{PlaylistEntry, PlaylistItemType, XtreamCluster};
 use crate::tools::atomic_once_flag::AtomicOnceFlag;
 use crate::tools::lru_cache::LRUResourceCache;
@@ -22,7 +22,8 @@ use crate::utils::constants::{DASH_EXT, HLS_EXT};
 use crate::utils::crypto_utils::{deobfuscate_text, obfuscate_text};
 use crate::utils::default_utils::default_grace_period_millis;
 use crate::utils::file::file_utils::create_new_file_for_write;
-use crate::utils::network::request;
+use crate::utils::network::{input_auth, request};
+use crate::utils::network::stalker::resolve_stalker_stream_url;
 use crate::utils::network::request::{extract_extension_from_url, replace_url_extension, sanitize_sensitive_info};
 use crate::utils::size_utils::human_readable_byte_size;
 use crate::utils::{debug_if_enabled, sys_utils, trace_if_enabled};
@@ -37,9 +38,11 @@ use log::{debug, error, log_enabled, trace};
 use reqwest::StatusCode;
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::hash::BuildHasher;
 use std::io::BufWriter;
 use std::path::Path;
 use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
 use tokio::sync::Mutex;
 use url::Url;
 
@@ -225,6 +228,8 @@ pub struct StreamDetails {
     pub grace_period_millis: u64,
     pub reconnect_flag: Option<Arc<AtomicOnceFlag>>,
     pub provider_connection_guard: Option<ProviderConnectionGuard>,
+    // the response status if the stream could not be opened, otherwise bad request is returned
+    pub error_status: Option<StatusCode>,
 }
 
 impl StreamDetails {
@@ -236,6 +241,19 @@ impl StreamDetails {
             grace_period_millis: default_grace_period_millis(),
             reconnect_flag: None,
             provider_connection_guard: None,
+            error_status: None,
+        }
+    }
+
+    fn from_error(error_status: StatusCode) -> Self {
+        Self {
+            stream: None,
+            stream_info: None,
+            input_name: None,
+            grace_period_millis: 0,
+            reconnect_flag: None,
+            provider_connection_guard: None,
+            error_status: Some(error_status),
         }
     }
     #[inline]
@@ -252,7 +270,7 @@ impl StreamDetails {
 /**
 * If successfully a provider connection is used, do not forget to release if unsuccessfully
 */
-async fn get_streaming_options(app_state: &AppState, stream_url: &str, input: &ConfigInput, force_provider: Option<&str>)
+async fn get_streaming_options<S: BuildHasher>(app_state: &AppState, stream_url: &str, input: &ConfigInput, stream_headers: &HashMap<String, String, S>, force_provider: Option<&str>)
                                -> (Option<ProviderConnectionGuard>, StreamingOption, Option<HashMap<String, String>>) {
     let provider_connection_guard = match force_provider {
         Some(provider) => app_state.active_provider.force_exact_acquire_connection(provider).await,
@@ -280,7 +298,20 @@ async fn get_streaming_options(app_state: &AppState, stream_url: &str, input: &C
             }
         }
     };
-    (Some(provider_connection_guard), stream_response_params, Some(input.headers.clone()))
+    (Some(provider_connection_guard), stream_response_params, Some(get_input_request_headers(app_state, input, stream_headers).await))
+}
+
+// the stream headers of the playlist item replace the input headers with the same name
+async fn get_input_request_headers<S: BuildHasher>(app_state: &AppState, input: &ConfigInput, stream_headers: &HashMap<String, String, S>) -> HashMap<String, String> {
+    let mut headers = input_auth::get_input_headers(&app_state.http_client, input).await.unwrap_or_else(|err| {
+        error!("{err}");
+        input.headers.clone()
+    });
+    if !stream_headers.is_empty() {
+        headers.retain(|name, _| !stream_headers.keys().any(|header| header.eq_ignore_ascii_case(name)));
+        headers.extend(stream_headers.iter().map(|(name, value)| (name.clone(), value.clone())));
+    }
+    headers
 }
 
 
@@ -292,17 +323,18 @@ fn get_grace_period_millis(connection_permission: &UserConnectionPermission, str
 }
 
 #[allow(clippy::too_many_arguments)]
-async fn create_stream_response_details(app_state: &AppState,
+async fn create_stream_response_details<S: BuildHasher>(app_state: &AppState,
                                         stream_options: &StreamOptions,
                                         stream_url: &str,
                                         req_headers: &HeaderMap,
                                         input: &ConfigInput,
+                                        stream_headers: &HashMap<String, String, S>,
                                         item_type: PlaylistItemType,
                                         share_stream: bool,
                                         connection_permission: UserConnectionPermission,
                                         force_provider: Option<&str>) -> StreamDetails {
     let (mut provider_connection_guard, stream_response_params, input_headers) =
-        get_streaming_options(app_state, stream_url, input, force_provider).await;
+        get_streaming_options(app_state, stream_url, input, stream_headers, force_provider).await;
     let config_grace_period_millis = app_state.config.reverse_proxy.as_ref()
         .and_then(|r| r.stream.as_ref()).map_or_else(default_grace_period_millis, |s| s.grace_period_millis);
     let grace_period_millis = get_grace_period_millis(&connection_permission, &stream_response_params, config_grace_period_millis);
@@ -316,19 +348,50 @@ async fn create_stream_response_details(app_state: &AppState,
                 grace_period_millis,
                 reconnect_flag: None,
                 provider_connection_guard,
+                error_status: None,
             }
         }
         StreamingOption::Available(provider_name, request_url) |
         StreamingOption::GracePeriod(provider_name, request_url) => {
+            // portal stream links are created on play
+            let request_url = if input.input_type == InputType::Stalker {
+                match resolve_stalker_stream_url(Arc::clone(&app_state.http_client), input, provider_name.as_deref(), &request_url).await {
+                    Ok(stream_url) => stream_url,
+                    Err(err) => {
+                        // the connection guard is dropped, the provider connection is released
+                        error!("{err}");
+                        return StreamDetails::from_error(StatusCode::BAD_GATEWAY);
+                    }
+                }
+            } else {
+                request_url
+            };
             let parsed_url = Url::parse(&request_url);
             let ((stream, stream_info), reconnect_flag) = if let Ok(url) = parsed_url {
-                if stream_options.pipe_provider_stream {
-                    (provider_stream::get_provider_pipe_stream(app_state, &url, req_headers, input_headers.as_ref(), item_type).await, None)
+                // a forced provider is reported with the input name, its client is selected by the forced name
+                let http_client = input.get_http_client_by_name(&app_state.http_client, force_provider.or(provider_name.as_deref()));
+                let open_stream = |headers: Option<HashMap<String, String>>| {
+                    let http_client = Arc::clone(&http_client);
+                    let url = url.clone();
+                    async move {
+                        if stream_options.pipe_provider_stream {
+                            (provider_stream::get_provider_pipe_stream(app_state, &http_client, &url, req_headers, headers.as_ref(), item_type).await, None)
+                        } else {
+                            let input_token = input_auth::InputTokenHeader::new(&app_state.http_client, input);
+                            let buffer_stream_options = BufferStreamOptions::new(item_type, share_stream, stream_options, input_token);
+                            let reconnect_flag = buffer_stream_options.get_reconnect_flag_clone();
+                            (provider_stream::get_provider_reconnect_buffered_stream(app_state, http_client, &url, req_headers, headers.as_ref(), buffer_stream_options).await,
+                             Some(reconnect_flag))
+                        }
+                    }
+                };
+                let opened_stream = open_stream(input_headers).await;
+                if input.auth.is_some() && is_rejected_stream(&opened_stream.0) {
+                    // the provider could have revoked the token, login again and retry once
+                    input_auth::invalidate_input_token(input).await;
+                    open_stream(Some(get_input_request_headers(app_state, input, stream_headers).await)).await
                 } else {
-                    let buffer_stream_options = BufferStreamOptions::new(item_type, share_stream, stream_options);
-                    let reconnect_flag = buffer_stream_options.get_reconnect_flag_clone();
-                    (provider_stream::get_provider_reconnect_buffered_stream(app_state, &url, req_headers, input_headers.as_ref(), buffer_stream_options).await,
-                     Some(reconnect_flag))
+                    opened_stream
                 }
             } else {
                 ((None, None), None)
@@ -358,11 +421,16 @@ async fn create_stream_response_details(app_state: &AppState,
                 grace_period_millis,
                 reconnect_flag,
                 provider_connection_guard,
+                error_status: None,
             }
         }
     }
 }
 
+fn is_rejected_stream(provider_stream: &ProviderStreamResponse) -> bool {
+    matches!(&provider_stream.1, Some((_, status)) if input_auth::is_token_rejected(*status))
+}
+
 pub struct RedirectParams<'a, P>
 where
     P: PlaylistEntry,
@@ -401,6 +469,10 @@ pub async fn redirect_response<'a, P>(app_state: &AppState, params: &'a Redirect
 where
     P: PlaylistEntry,
 {
+    // local files and portal links can't be redirected, they are always served through the reverse proxy
+    if matches!(params.input.input_type, InputType::Local | InputType::Stalker) {
+        return None;
+    }
     let item_type = params.item.get_item_type();
     let provider_url = &params.item.get_provider_url();
 
@@ -555,12 +627,14 @@ pub fn bad_response_with_delete_cookie() -> impl IntoResponse + Send {
 }
 
 /// # Panics
-pub async fn force_provider_stream_response(app_state: &AppState,
+#[allow(clippy::too_many_arguments)]
+pub async fn force_provider_stream_response<S: BuildHasher>(app_state: &AppState,
                                             cookie: &str,
                                             virtual_id: u32,
                                             item_type: PlaylistItemType,
                                             req_headers: &HeaderMap,
                                             input: &ConfigInput,
+                                            stream_headers: &HashMap<String, String, S>,
                                             user: &ProxyUserCredentials) -> impl axum::response::IntoResponse + Send {
     if let Some((stream_virtual_id, provider_name, stream_url)) = get_stream_info_from_crypted_cookie(&app_state.config.t_encrypt_secret, cookie) {
         if stream_virtual_id == virtual_id {
@@ -569,7 +643,7 @@ pub async fn force_provider_stream_response(app_state: &AppState,
             let connection_permission = UserConnectionPermission::Allowed;
 
             let mut stream_details =
-                create_stream_response_details(app_state, &stream_options, &stream_url, req_headers, input, item_type, share_stream, connection_permission.clone(), Some(&provider_name)).await;
+                create_stream_response_details(app_state, &stream_options, &stream_url, req_headers, input, stream_headers, item_type, share_stream, connection_permission.clone(), Some(&provider_name)).await;
 
             if stream_details.has_stream() {
                 let provider_response = stream_details.stream_info.as_ref().map(|(h, sc)| (h.clone(), *sc));
@@ -589,19 +663,96 @@ pub async fn force_provider_stream_response(app_state: &AppState,
                 return response.body(body_stream).unwrap().into_response();
             }
             drop(stream_details.provider_connection_guard.take());
+            if let Some(error_status) = stream_details.error_status {
+                return error_status.into_response();
+            }
         }
     }
     bad_response_with_delete_cookie().into_response()
 }
 
+fn get_local_file_content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()).map(str::to_lowercase).as_deref() {
+        Some("mp4") => "video/mp4",
+        Some("mkv") => "video/x-matroska",
+        Some("avi" | "divx") => "video/x-msvideo",
+        Some("mov") => "video/quicktime",
+        Some("mpeg" | "mpg") => "video/mpeg",
+        Some("ts") => "video/mp2t",
+        _ => "application/octet-stream",
+    }
+}
+
+// parses a single `bytes=start-end` range, multipart ranges are not supported
+fn parse_range_header(req_headers: &HeaderMap, file_size: u64) -> Option<(u64, u64)> {
+    let range = req_headers.get(axum::http::header::RANGE)?.to_str().ok()?.strip_prefix("bytes=")?;
+    let (start, end) = range.split_once('-')?;
+    let last = file_size.checked_sub(1)?;
+    let (start, end) = if start.trim().is_empty() {
+        let suffix: u64 = end.trim().parse().ok()?;
+        (file_size.saturating_sub(suffix), last)
+    } else {
+        let start: u64 = start.trim().parse().ok()?;
+        let end = if end.trim().is_empty() { last } else { end.trim().parse::<u64>().ok()?.min(last) };
+        (start, end)
+    };
+    (start <= end).then_some((start, end))
+}
+
+/// Streams a file from a local input. Range requests are supported to allow seeking.
+async fn local_stream_response(stream_url: &str, req_headers: &HeaderMap) -> axum::response::Response {
+    let Some(file_path) = Url::parse(stream_url).ok().and_then(|url| url.to_file_path().ok()) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let Ok(mut file) = tokio::fs::File::open(&file_path).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let Ok(file_size) = file.metadata().await.map(|metadata| metadata.len()) else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let response = axum::response::Response::builder()
+        .header(axum::http::header::CONTENT_TYPE, get_local_file_content_type(&file_path))
+        .header(axum::http::header::ACCEPT_RANGES, "bytes");
+    if req_headers.contains_key(axum::http::header::RANGE) {
+        let Some((start, end)) = parse_range_header(req_headers, file_size) else {
+            return axum::response::Response::builder()
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(axum::http::header::CONTENT_RANGE, format!("bytes */{file_size}"))
+                .body(Body::empty())
+                .unwrap()
+                .into_response();
+        };
+        if file.seek(std::io::SeekFrom::Start(start)).await.is_err() {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+        let length = end - start + 1;
+        let stream = tokio_util::io::ReaderStream::new(tokio::io::BufReader::new(file.take(length)));
+        return response
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(axum::http::header::CONTENT_RANGE, format!("bytes {start}-{end}/{file_size}"))
+            .header(axum::http::header::CONTENT_LENGTH, length)
+            .body(Body::from_stream(stream))
+            .unwrap()
+            .into_response();
+    }
+    let stream = tokio_util::io::ReaderStream::new(tokio::io::BufReader::new(file));
+    response
+        .status(StatusCode::OK)
+        .header(axum::http::header::CONTENT_LENGTH, file_size)
+        .body(Body::from_stream(stream))
+        .unwrap()
+        .into_response()
+}
+
 /// # Panics
 #[allow(clippy::too_many_arguments)]
-pub async fn stream_response(app_state: &AppState,
+pub async fn stream_response<S: BuildHasher>(app_state: &AppState,
                              virtual_id: u32,
                              item_type: PlaylistItemType,
                              stream_url: &str,
                              req_headers: &HeaderMap,
                              input: &ConfigInput,
+                             stream_headers: &HashMap<String, String, S>,
                              target: &ConfigTarget,
                              user: &ProxyUserCredentials,
                              connection_permission: UserConnectionPermission) -> impl axum::response::IntoResponse + Send {
@@ -611,6 +762,10 @@ pub async fn stream_response(app_state: &AppState,
         return create_custom_video_stream_response(&app_state.config, &CustomVideoStreamType::UserConnectionsExhausted).into_response();
     }
 
+    if input.input_type == InputType::Local {
+        return local_stream_response(stream_url, req_headers).await.into_response();
+    }
+
     let share_stream = is_stream_share_enabled(item_type, target);
     if share_stream {
         if let Some(value) = shared_stream_response(app_state, stream_url, user, connection_permission.clone()).await {
@@ -620,7 +775,7 @@ pub async fn stream_response(app_state: &AppState,
 
     let stream_options = get_stream_options(app_state);
     let mut stream_details =
-        create_stream_response_details(app_state, &stream_options, stream_url, req_headers, input, item_type, share_stream, connection_permission.clone(), None).await;
+        create_stream_response_details(app_state, &stream_options, stream_url, req_headers, input, stream_headers, item_type, share_stream, connection_permission.clone(), None).await;
     if stream_details.has_stream() {
         // let content_length = get_stream_content_length(provider_response.as_ref());
         let provider_response = stream_details.stream_info.as_ref().map(|(h, sc)| (h.clone(), *sc));
@@ -665,7 +820,7 @@ pub async fn stream_response(app_state: &AppState,
         return stream_resp.into_response();
     }
     drop(stream_details.provider_connection_guard.take());
-    axum::http::StatusCode::BAD_REQUEST.into_response()
+    stream_details.error_status.unwrap_or(StatusCode::BAD_REQUEST).into_response()
 }
 
 fn get_stream_throttle(app_state: &AppState) -> u64 {
@@ -741,7 +896,12 @@ pub async fn resource_response(app_state: &AppState, resource_url: &str, req_hea
     }
     trace_if_enabled!("Try to fetch resource {}", sanitize_sensitive_info(resource_url));
     if let Ok(url) = Url::parse(resource_url) {
-        let client = request::get_client_request(&app_state.http_client, input.map_or(InputFetchMethod::GET, |i| i.method), input.map(|i| &i.headers), &url, Some(&req_headers));
+        let http_client = input.map_or_else(|| Arc::clone(&app_state.http_client), |i| i.get_http_client(&app_state.http_client, resource_url));
+        let input_headers = match input {
+            Some(i) => Some(get_input_request_headers(app_state, i, &HashMap::new()).await),
+            None => None,
+        };
+        let client = request::get_client_request(&http_client, input.map_or(InputFetchMethod::GET, |i| i.method), input_headers.as_ref(), &url, Some(&req_headers));
         match client.send().await {
             Ok(response) => {
                 let status = response.status();
@@ -2,7 +2,8 @@ use crate::model::config::{Config, ConfigInput, ConfigTarget, InputType, TargetT
 use crate::model::playlist::{M3uPlaylistItem, PlaylistGroup, PlaylistItemType, XtreamCluster};
 use crate::repository::{m3u_repository, xtream_repository};
 use crate::utils::file::file_lock_manager::FileReadGuard;
-use crate::utils::network::{m3u, xtream};
+use crate::processing::parser::local;
+use crate::utils::network::{hdhomerun, m3u, mapped, stalker, xtream};
 use axum::response::IntoResponse;
 use serde::Serialize;
 use serde_json::{json, Value};
@@ -172,6 +173,10 @@ pub(in crate::api::endpoints) async fn get_playlist(client: Arc<reqwest::Client>
                 match input.input_type {
                     InputType::M3u | InputType::M3uBatch => m3u::get_m3u_playlist(client, cfg, input, &cfg.working_dir).await,
                     InputType::Xtream | InputType::XtreamBatch => xtream::get_xtream_playlist(client, input, &cfg.working_dir).await,
+                    InputType::Local => local::get_local_playlist(cfg, input),
+                    InputType::Stalker => stalker::get_stalker_playlist(client, input).await,
+                    InputType::HdHomeRun => hdhomerun::get_hdhomerun_playlist(client, input).await,
+                    InputType::Json | InputType::Csv => mapped::get_mapped_playlist(client, input, &cfg.working_dir).await,
                 };
             if result.is_empty() {
                 let error_strings: Vec<String> = errors.iter().map(std::string::ToString::to_string).collect();
@@ -0,0 +1,64 @@
+use std::sync::Arc;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use log::error;
+use crate::api::model::app_state::AppState;
+use crate::model::config::TargetType;
+use crate::repository::enigma2_repository::{enigma2_bouquet_filename, enigma2_bouquet_prefix, enigma2_collect_bouquets, enigma2_create_bouquet, enigma2_create_bouquets_index, Enigma2Service, ENIGMA2_BOUQUETS_FILE};
+use crate::repository::m3u_playlist_iterator::M3uPlaylistIterator;
+use crate::utils::network::request::sanitize_sensitive_info;
+
+/// Serves the `bouquets.tv` index and the `userbouquet.*.tv` files of a user.
+/// The bouquets are created from the m3u output of the target with the stream urls of the user.
+async fn enigma2_bouquet(
+    axum::extract::Path((username, password, file_name)): axum::extract::Path<(String, String, String)>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl IntoResponse + Send {
+    let Some((user, target)) = app_state.config.get_target_for_user(&username, &password).await else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    if user.permission_denied(&app_state) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let Some(target_output) = target.get_enigma2_output() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if !target.has_output(&TargetType::M3u) {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let iterator = match M3uPlaylistIterator::new(&app_state.config, target, &user).await {
+        Ok(iterator) => iterator,
+        Err(err) => {
+            error!("{}", sanitize_sensitive_info(err.to_string().as_str()));
+            return StatusCode::NO_CONTENT.into_response();
+        }
+    };
+    let bouquets = enigma2_collect_bouquets(iterator.map(|(item, _has_next)| {
+        let url = if item.t_stream_url.is_empty() { item.url } else { item.t_stream_url };
+        (item.group, Enigma2Service { id: item.virtual_id, name: item.title, url })
+    }));
+
+    let prefix = enigma2_bouquet_prefix(target, target_output);
+    let content = if file_name == ENIGMA2_BOUQUETS_FILE {
+        Some(enigma2_create_bouquets_index(&prefix, bouquets.len()))
+    } else {
+        bouquets.iter().enumerate()
+            .find(|(index, _)| enigma2_bouquet_filename(&prefix, *index) == file_name)
+            .map(|(_, bouquet)| enigma2_create_bouquet(bouquet))
+    };
+    match content {
+        Some(text) => axum::response::Response::builder()
+            .status(StatusCode::OK)
+            .header(axum::http::header::CONTENT_TYPE, mime::TEXT_PLAIN_UTF_8.to_string())
+            .body(axum::body::Body::from(text))
+            .unwrap()
+            .into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+pub fn enigma2_api_register() -> axum::Router<Arc<AppState>> {
+    axum::Router::new()
+        .route("/enigma2/{username}/{password}/{file_name}", axum::routing::get(enigma2_bouquet))
+}
@@ -4,15 +4,18 @@ use crate::api::model::active_provider_manager::ProviderConfig;
 use crate::api::model::app_state::AppState;
 use crate::api::model::streams::provider_stream::{create_custom_video_stream_response, CustomVideoStreamType};
 use crate::model::api_proxy::{ProxyUserCredentials, UserConnectionPermission};
-use crate::model::config::ConfigInput;
+use crate::model::config::{ConfigInput, ConfigTarget, TargetType};
 use crate::model::playlist::{PlaylistItemType, XtreamCluster};
 use crate::processing::parser::hls::{rewrite_hls, RewriteHlsProps};
+use crate::repository::m3u_repository::m3u_get_item_for_stream_id;
+use crate::repository::xtream_repository::xtream_get_item_for_stream_id;
 use crate::utils::constants::HLS_EXT;
 use crate::utils::network::request;
 use crate::utils::network::request::{is_hls_url, replace_url_extension, sanitize_sensitive_info};
 use axum::response::IntoResponse;
 use log::{debug, error};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 #[derive(Debug, Deserialize)]
@@ -41,7 +44,8 @@ pub(in crate::api) async fn handle_hls_stream_request(app_state: &Arc<AppState>,
                                                       provider_name: Option<String>,
                                                       hls_url: &str,
                                                       virtual_id: u32,
-                                                      input: &ConfigInput) -> impl IntoResponse + Send {
+                                                      input: &ConfigInput,
+                                                      stream_headers: &HashMap<String, String>) -> impl IntoResponse + Send {
     let url = replace_url_extension(hls_url, HLS_EXT);
     let server_info = app_state.config.get_user_server_info(user).await;
 
@@ -67,7 +71,7 @@ pub(in crate::api) async fn handle_hls_stream_request(app_state: &Arc<AppState>,
         },
     };
 
-    match request::download_text_content(Arc::clone(&app_state.http_client), input, &request_url, None).await {
+    match request::download_text_content_with_headers(Arc::clone(&app_state.http_client), input, &request_url, stream_headers).await {
         Ok((content, response_url)) => {
             let rewrite_hls_props = RewriteHlsProps {
                 secret: &app_state.config.t_encrypt_secret,
@@ -88,6 +92,21 @@ pub(in crate::api) async fn handle_hls_stream_request(app_state: &Arc<AppState>,
     }
 }
 
+// the segment requests have no playlist item, the stream headers are read from the stored playlist of the target
+async fn get_stream_headers_for_virtual_id(app_state: &AppState, target: &ConfigTarget, virtual_id: u32) -> HashMap<String, String> {
+    if target.has_output(&TargetType::M3u) {
+        if let Ok(pli) = m3u_get_item_for_stream_id(virtual_id, &app_state.config, target).await {
+            return pli.get_stream_headers();
+        }
+    }
+    if target.has_output(&TargetType::Xtream) {
+        if let Ok((pli, _)) = xtream_get_item_for_stream_id(virtual_id, &app_state.config, target, None) {
+            return pli.get_stream_headers();
+        }
+    }
+    HashMap::new()
+}
+
 async fn hls_api_stream(
     req_headers: axum::http::HeaderMap,
     axum::extract::Path(params): axum::extract::Path<HlsApiPathParams>,
@@ -119,14 +138,15 @@ async fn hls_api_stream(
     }
 
     let provider_name = Some(stream_provider_name);
+    let stream_headers = get_stream_headers_for_virtual_id(&app_state, target, virtual_id).await;
 
     if is_hls_url(&hls_url) {
-        return handle_hls_stream_request(&app_state, &user, provider_name, &hls_url, virtual_id, input).await.into_response();
+        return handle_hls_stream_request(&app_state, &user, provider_name, &hls_url, virtual_id, input, &stream_headers).await.into_response();
     }
 
     // if provider_name.is_some() {
     // TODO we decode twice the cookie, one time to check for connection permission and one time in force_provider_stream_response
-    force_provider_stream_response(&app_state, &params.token, virtual_id, PlaylistItemType::LiveHls, &req_headers, input, &user).await.into_response()
+    force_provider_stream_response(&app_state, &params.token, virtual_id, PlaylistItemType::LiveHls, &req_headers, input, &stream_headers, &user).await.into_response()
     // } else {
     //     stream_response(&app_state, virtual_id, PlaylistItemType::LiveHls, &hls_url, &req_headers, input, target, &user, connection_permission).await.into_response()
     // }
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use log::error;
+use serde::Deserialize;
+use url::Url;
+use crate::api::model::app_state::AppState;
+use crate::model::config::TargetType;
+use crate::repository::json_repository::json_load_playlist;
+use crate::repository::storage_const;
+use crate::utils::network::request::sanitize_sensitive_info;
+
+#[derive(Debug, Deserialize)]
+struct JsonPlaylistRequest {
+    page: Option<usize>,
+    page_size: Option<usize>,
+}
+
+fn json_get_m3u_stream_url(base_url: &str, username: &str, password: &str, virtual_id: u32) -> String {
+    let virtual_id = virtual_id.to_string();
+    match Url::parse(base_url) {
+        Ok(mut url) => {
+            // the segments are percent encoded
+            if let Ok(mut segments) = url.path_segments_mut() {
+                segments.pop_if_empty().extend([storage_const::M3U_STREAM_PATH, username, password, &virtual_id]);
+            }
+            url.to_string()
+        }
+        Err(_) => format!("{base_url}/{}/{username}/{password}/{virtual_id}", storage_const::M3U_STREAM_PATH),
+    }
+}
+
+/// Serves the json export of the target with the stream urls of the user.
+/// The channels are paginated if `page_size` is given, `page` starts with 1.
+async fn json_playlist(
+    axum::extract::Path((username, password)): axum::extract::Path<(String, String)>,
+    axum::extract::Query(request): axum::extract::Query<JsonPlaylistRequest>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl IntoResponse + Send {
+    let Some((user, target)) = app_state.config.get_target_for_user(&username, &password).await else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    if user.permission_denied(&app_state) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    if target.get_json_output().is_none() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    if !target.has_output(&TargetType::Xtream) && !target.has_output(&TargetType::M3u) {
+        // the streams can only be served with a xtream or m3u output
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let mut document = match json_load_playlist(&app_state.config, target).await {
+        Ok(document) => document,
+        Err(err) => {
+            error!("{}", sanitize_sensitive_info(err.to_string().as_str()));
+            return StatusCode::NO_CONTENT.into_response();
+        }
+    };
+    if let Some(page_size) = request.page_size.filter(|&size| size > 0) {
+        document.paginate(request.page.unwrap_or(1).max(1), page_size);
+    }
+    let server_info = app_state.config.get_user_server_info(&user).await;
+    if target.has_output(&TargetType::Xtream) {
+        document.rewrite_xtream_urls(target.options.as_ref().and_then(|o| o.force_redirect.as_ref()), Some(&(user, server_info)));
+    } else {
+        // without xtream output the streams are served through the m3u api
+        let base_url = server_info.get_base_url();
+        document.rewrite_urls(|channel| if user.proxy.is_redirect(channel.item_type) || target.is_force_redirect(channel.item_type) {
+            channel.url.clone()
+        } else {
+            json_get_m3u_stream_url(&base_url, &user.username, &user.password, channel.virtual_id)
+        });
+    }
+    axum::Json(document).into_response()
+}
+
+pub fn json_api_register() -> axum::Router<Arc<AppState>> {
+    axum::Router::new()
+        .route("/json/{username}/{password}", axum::routing::get(json_playlist))
+}
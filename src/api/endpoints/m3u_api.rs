@@ -2,22 +2,42 @@ use crate::api::api_utils::{get_user_target, get_user_target_by_credentials, is_
 use crate::api::endpoints::hls_api::handle_hls_stream_request;
 use crate::api::model::app_state::AppState;
 use crate::api::model::request::UserApiRequest;
-use crate::model::api_proxy::{UserConnectionPermission};
-use crate::model::config::{TargetType};
+use crate::model::api_proxy::UserConnectionPermission;
+use crate::model::config::TargetType;
 use crate::model::playlist::{FieldGetAccessor, PlaylistEntry, PlaylistItemType, XtreamCluster};
-use crate::repository::m3u_repository::{m3u_get_item_for_stream_id, m3u_load_rewrite_playlist};
+use crate::repository::m3u_repository::{m3u_create_split_index, m3u_get_item_for_stream_id, m3u_load_rewrite_playlist, m3u_get_split_names, m3u_load_rewrite_split_playlist};
+use crate::repository::user_repository::user_get_bouquet_filter;
 use crate::utils::network::request::{extract_extension_from_url, sanitize_sensitive_info};
 use axum::response::IntoResponse;
 use bytes::Bytes;
 use futures::stream;
 use log::{debug, error};
+use std::collections::HashMap;
 use std::sync::Arc;
 use axum::http::StatusCode;
+use url::Url;
 use crate::api::endpoints::xtream_api::XtreamApiStreamContext;
 use crate::api::model::streams::provider_stream::{create_custom_video_stream_response, CustomVideoStreamType};
 use crate::repository::storage_const;
+use crate::utils::catchup_utils::fill_catchup_template;
 use crate::utils::constants::{HLS_EXT};
 
+fn m3u_text_response<I>(m3u_iter: I, attachment: bool) -> axum::response::Response
+where
+    I: Iterator<Item=String> + Send + 'static,
+{
+    // Convert the iterator into a stream of `Bytes`
+    let content_stream = stream::iter(m3u_iter.map(|line| Ok::<Bytes, String>(Bytes::from([line.as_bytes(), b"\n"].concat()))));
+
+    let mut builder = axum::response::Response::builder()
+        .status(axum::http::StatusCode::OK)
+        .header(axum::http::header::CONTENT_TYPE, mime::TEXT_PLAIN_UTF_8.to_string());
+    if attachment {
+        builder = builder.header("Content-Disposition", "attachment; filename=\"playlist.m3u\"");
+    }
+    builder.body(axum::body::Body::from_stream(content_stream)).unwrap().into_response()
+}
+
 async fn m3u_api(
     api_req: &UserApiRequest,
     app_state: &AppState,
@@ -25,18 +45,7 @@ async fn m3u_api(
     match get_user_target(api_req, app_state).await {
         Some((user, target)) => {
             match m3u_load_rewrite_playlist(&app_state.config, target, &user).await {
-                Ok(m3u_iter) => {
-                    // Convert the iterator into a stream of `Bytes`
-                    let content_stream = stream::iter(m3u_iter.map(|line| Ok::<Bytes, String>(Bytes::from([line.to_string().as_bytes(), b"\n"].concat()))));
-
-                    let mut builder = axum::response::Response::builder()
-                        .status(axum::http::StatusCode::OK)
-                        .header(axum::http::header::CONTENT_TYPE, mime::TEXT_PLAIN_UTF_8.to_string());
-                    if api_req.content_type == "m3u_plus" {
-                        builder = builder.header("Content-Disposition", "attachment; filename=\"playlist.m3u\"");
-                    }
-                    builder.body(axum::body::Body::from_stream(content_stream)).unwrap().into_response()
-                }
+                Ok(m3u_iter) => m3u_text_response(m3u_iter, api_req.content_type == "m3u_plus"),
                 Err(err) => {
                     error!("{}", sanitize_sensitive_info(err.to_string().as_str()));
                     axum::http::StatusCode::NO_CONTENT.into_response()
@@ -82,12 +91,14 @@ async fn m3u_api_stream(
     let virtual_id: u32 = try_result_bad_request!(action_stream_id.trim().parse());
     let pli = try_result_bad_request!(m3u_get_item_for_stream_id(virtual_id, &app_state.config, target).await, true, format!("Failed to read m3u item for stream id {}", virtual_id));
     let input = try_option_bad_request!(app_state.config.get_input_by_name(pli.input_name.as_str()), true, format!("Cant find input for target {target_name}, stream_id {virtual_id}"));
+    // the user agent and referrer of the #EXTVLCOPT options are sent to the provider
+    let stream_headers = pli.get_stream_headers();
 
     let cluster = XtreamCluster::try_from(pli.item_type).unwrap_or(XtreamCluster::Live);
 
     if let Some(cookie) = is_seek_response(cluster, pli.virtual_id, &app_state.config.t_encrypt_secret, &req_headers) {
         // partial request means we are in reverse proxy mode, seek happened
-        return force_provider_stream_response(&app_state, &cookie, pli.virtual_id, pli.item_type, &req_headers, input, &user).await.into_response()
+        return force_provider_stream_response(&app_state, &cookie, pli.virtual_id, pli.item_type, &req_headers, input, &stream_headers, &user).await.into_response()
     }
 
     let (provider_name, connection_permission) = check_force_provider(&app_state, virtual_id, &req_headers, &user).await;
@@ -120,10 +131,10 @@ async fn m3u_api_stream(
     let is_hls_request = pli.item_type == PlaylistItemType::LiveHls || pli.item_type == PlaylistItemType::LiveDash || extension == HLS_EXT;
     // Reverse proxy mode
     if is_hls_request {
-        return handle_hls_stream_request(&app_state, &user, provider_name, &pli.url, pli.virtual_id, input).await.into_response();
+        return handle_hls_stream_request(&app_state, &user, provider_name, &pli.url, pli.virtual_id, input, &stream_headers).await.into_response();
     }
 
-    stream_response(&app_state, pli.virtual_id, pli.item_type, pli.url.as_str(), &req_headers, input, target, &user, connection_permission).await.into_response()
+    stream_response(&app_state, pli.virtual_id, pli.item_type, pli.url.as_str(), &req_headers, input, &stream_headers, target, &user, connection_permission).await.into_response()
 }
 
 async fn m3u_api_resource(
@@ -164,6 +175,107 @@ async fn m3u_api_resource(
     }
 }
 
+async fn m3u_api_catchup(
+    req_headers: axum::http::HeaderMap,
+    axum::extract::Query(api_req): axum::extract::Query<UserApiRequest>,
+    axum::extract::Query(catchup_params): axum::extract::Query<HashMap<String, String>>,
+    axum::extract::Path((username, password, stream_id)): axum::extract::Path<(String, String, String)>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    let (user, target) = try_option_bad_request!(get_user_target_by_credentials(&username, &password, &api_req, &app_state).await, false, format!("Could not find any user {username}"));
+    if user.permission_denied(&app_state) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    if !target.has_output(&TargetType::M3u) {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    let virtual_id: u32 = try_result_bad_request!(stream_id.trim().parse());
+    let pli = try_result_bad_request!(m3u_get_item_for_stream_id(virtual_id, &app_state.config, target).await, true, format!("Failed to read m3u item for stream id {}", virtual_id));
+    let template = try_option_bad_request!(pli.get_catchup_template(), true, format!("No catchup for stream id {virtual_id}"));
+    let input = try_option_bad_request!(app_state.config.get_input_by_name(pli.input_name.as_str()), true, format!("Cant find input for target {}, stream_id {virtual_id}", target.name));
+    let stream_headers = pli.get_stream_headers();
+    let catchup_url = fill_catchup_template(&template, &catchup_params);
+
+    if user.proxy.is_redirect(pli.item_type) || target.is_force_redirect(pli.item_type) {
+        debug!("Redirecting catchup request to {}", sanitize_sensitive_info(&catchup_url));
+        return redirect(&catchup_url).into_response();
+    }
+
+    let (_provider_name, connection_permission) = check_force_provider(&app_state, virtual_id, &req_headers, &user).await;
+    stream_response(&app_state, pli.virtual_id, PlaylistItemType::Catchup, &catchup_url, &req_headers, input, &stream_headers, target, &user, connection_permission).await.into_response()
+}
+
+fn m3u_get_split_playlist_url(base_url: &str, username: &str, password: &str, name: &str) -> String {
+    match Url::parse(base_url) {
+        Ok(mut url) => {
+            // the segments are percent encoded
+            if let Ok(mut segments) = url.path_segments_mut() {
+                segments.pop_if_empty().extend(["m3u", username, password, "group", name]);
+            }
+            url.to_string()
+        }
+        Err(_) => format!("{base_url}/m3u/{username}/{password}/group/{name}"),
+    }
+}
+
+/// Serves the index of the split playlists, each entry points to the playlist of a group or cluster.
+async fn m3u_api_split_index(
+    axum::extract::Query(api_req): axum::extract::Query<UserApiRequest>,
+    axum::extract::Path((username, password)): axum::extract::Path<(String, String)>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    let (user, target) = try_option_bad_request!(get_user_target_by_credentials(&username, &password, &api_req, &app_state).await, false, format!("Could not find any user {username}"));
+    if user.permission_denied(&app_state) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let Some(split) = target.get_m3u_output().and_then(|output| output.split) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let Some(names) = m3u_get_split_names(&app_state.config, target, split, &app_state.m3u_split_names).await else {
+        return StatusCode::NO_CONTENT.into_response();
+    };
+    // bouquet filtered groups are not listed
+    let filter = user_get_bouquet_filter(&app_state.config, &user.username, None, TargetType::M3u, XtreamCluster::Live).await;
+    let base_url = app_state.config.get_user_server_info(&user).await.get_base_url();
+    let index = m3u_create_split_index(names.iter_filtered(filter.as_ref()).map(|(name, title)| (title, m3u_get_split_playlist_url(&base_url, &user.username, &user.password, name))));
+    axum::response::Response::builder()
+        .status(StatusCode::OK)
+        .header(axum::http::header::CONTENT_TYPE, mime::TEXT_PLAIN_UTF_8.to_string())
+        .body(axum::body::Body::from(index))
+        .unwrap()
+        .into_response()
+}
+
+/// Serves the playlist of a group or cluster, the name is the one used in the index.
+async fn m3u_api_split_playlist(
+    axum::extract::Query(api_req): axum::extract::Query<UserApiRequest>,
+    axum::extract::Path((username, password, name)): axum::extract::Path<(String, String, String)>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    let (user, target) = try_option_bad_request!(get_user_target_by_credentials(&username, &password, &api_req, &app_state).await, false, format!("Could not find any user {username}"));
+    if user.permission_denied(&app_state) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let Some(split) = target.get_m3u_output().and_then(|output| output.split) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let Some(names) = m3u_get_split_names(&app_state.config, target, split, &app_state.m3u_split_names).await else {
+        return StatusCode::NO_CONTENT.into_response();
+    };
+    let filter = user_get_bouquet_filter(&app_state.config, &user.username, None, TargetType::M3u, XtreamCluster::Live).await;
+    let Some(title) = names.get_title(&name, filter.as_ref()) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    match m3u_load_rewrite_split_playlist(&app_state.config, target, &user, split, title).await {
+        Ok(m3u_iter) => m3u_text_response(m3u_iter, false),
+        Err(err) => {
+            error!("{}", sanitize_sensitive_info(err.to_string().as_str()));
+            StatusCode::NO_CONTENT.into_response()
+        }
+    }
+}
+
 macro_rules! register_m3u_stream_routes {
     ($router:expr, [$($path:expr),*]) => {{
         $router
@@ -191,4 +303,20 @@ pub fn m3u_api_register() -> axum::Router<Arc<AppState>> {
     register_m3u_stream_routes!(router, ["live", "movie", "series"])
     .route(&format!("/{}/{{username}}/{{password}}/{{stream_id}}", storage_const::M3U_STREAM_PATH), axum::routing::get(m3u_api_stream))
     .route(&format!("/{}/{{username}}/{{password}}/{{stream_id}}/{{resource}}", storage_const::M3U_RESOURCE_PATH), axum::routing::get(m3u_api_resource))
-}
\ No newline at end of file
+    .route(&format!("/{}/{{username}}/{{password}}/{{stream_id}}", storage_const::M3U_CATCHUP_PATH), axum::routing::get(m3u_api_catchup))
+    .route("/m3u/{username}/{password}/group", axum::routing::get(m3u_api_split_index))
+    .route("/m3u/{username}/{password}/group/{name}", axum::routing::get(m3u_api_split_playlist))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::endpoints::m3u_api::m3u_get_split_playlist_url;
+
+    #[test]
+    fn test_split_playlist_url() {
+        assert_eq!(m3u_get_split_playlist_url("http://localhost:8901", "user 1", "p/ss#", "nachrichten_hd"),
+                   "http://localhost:8901/m3u/user%201/p%2Fss%23/group/nachrichten_hd");
+        assert_eq!(m3u_get_split_playlist_url("http://localhost:8901/", "user 1", "p/ss#", "été"),
+                   "http://localhost:8901/m3u/user%201/p%2Fss%23/group/%C3%A9t%C3%A9");
+    }
+}
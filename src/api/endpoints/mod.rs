@@ -7,4 +7,6 @@ pub(in crate::api) mod web_index;
 pub(in crate::api) mod hls_api;
 mod user_api;
 pub(in crate::api) mod hdhomerun_api;
+pub(in crate::api) mod enigma2_api;
+pub(in crate::api) mod json_api;
 mod api_playlist_utils;
\ No newline at end of file
@@ -1,3 +1,4 @@
+use crate::api::account_monitor::exec_processing_and_update_connections;
 use crate::api::endpoints::download_api;
 use crate::api::endpoints::user_api::user_api_register;
 use crate::api::model::app_state::AppState;
@@ -9,7 +10,9 @@ use crate::m3u_filter_error::M3uFilterError;
 use crate::model::api_proxy::{ApiProxyConfig, ApiProxyServerInfo, ProxyUserCredentials, TargetUser};
 use crate::model::config::{validate_targets, Config, ConfigDto, ConfigInput, ConfigInputOptions, ConfigSource, ConfigTarget, InputType};
 use crate::model::playlist::{XtreamPlaylistItem};
-use crate::processing::processor::playlist;
+use crate::repository::override_repository;
+use crate::repository::override_repository::PlaylistItemOverride;
+use crate::repository::stats_repository::load_processing_stats;
 use crate::repository::user_repository::store_api_user;
 use crate::utils::file::config_reader;
 use crate::utils::network::request::sanitize_sensitive_info;
@@ -131,7 +134,8 @@ async fn playlist_update(
     let process_targets = validate_targets(user_targets.as_ref(), &app_state.config.sources);
     match process_targets {
         Ok(valid_targets) => {
-            tokio::spawn(playlist::exec_processing(Arc::clone(&app_state.http_client), Arc::clone(&app_state.config), Arc::new(valid_targets)));
+            tokio::spawn(exec_processing_and_update_connections(Arc::clone(&app_state.http_client), Arc::clone(&app_state.cache), Arc::clone(&app_state.config),
+                                                                Arc::new(valid_targets), Arc::clone(&app_state.active_provider)));
             axum::http::StatusCode::OK.into_response()
         }
         Err(err) => {
@@ -141,6 +145,67 @@ async fn playlist_update(
     }
 }
 
+fn has_target(cfg: &Config, target_name: &str) -> bool {
+    cfg.sources.iter().flat_map(|source| &source.targets).any(|target| target.name == target_name)
+}
+
+/// Returns the not found response for override requests of unknown targets.
+fn get_unknown_target_response(cfg: &Config, target_name: &str) -> Option<axum::response::Response> {
+    if has_target(cfg, target_name) {
+        None
+    } else {
+        Some((axum::http::StatusCode::NOT_FOUND, axum::Json(json!({"error": format!("Target not found {target_name}")}))).into_response())
+    }
+}
+
+async fn playlist_overrides(
+    axum::extract::Path(target_name): axum::extract::Path<String>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    if let Some(response) = get_unknown_target_response(&app_state.config, &target_name) {
+        return response;
+    }
+    match override_repository::load_overrides(&app_state.config, &target_name).await {
+        Ok(overrides) => axum::Json(overrides).into_response(),
+        Err(err) => (axum::http::StatusCode::INTERNAL_SERVER_ERROR, axum::Json(json!({"error": err.to_string()}))).into_response(),
+    }
+}
+
+async fn save_playlist_override(
+    axum::extract::Path(target_name): axum::extract::Path<String>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+    axum::extract::Json(mut item_override): axum::extract::Json<PlaylistItemOverride>,
+) -> impl axum::response::IntoResponse + Send {
+    if let Some(response) = get_unknown_target_response(&app_state.config, &target_name) {
+        return response;
+    }
+    if let Err(err) = item_override.prepare() {
+        return (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": err.to_string()}))).into_response();
+    }
+    match override_repository::save_override(&app_state.config, &target_name, item_override).await {
+        Ok(()) => axum::http::StatusCode::OK.into_response(),
+        Err(err) => (axum::http::StatusCode::INTERNAL_SERVER_ERROR, axum::Json(json!({"error": err.to_string()}))).into_response(),
+    }
+}
+
+async fn delete_playlist_override(
+    axum::extract::Path((target_name, uuid)): axum::extract::Path<(String, String)>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    if let Some(response) = get_unknown_target_response(&app_state.config, &target_name) {
+        return response;
+    }
+    let uuid = match override_repository::validate_override_uuid(&uuid) {
+        Ok(uuid) => uuid,
+        Err(err) => return (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": err.to_string()}))).into_response(),
+    };
+    match override_repository::delete_override(&app_state.config, &target_name, &uuid).await {
+        Ok(true) => axum::http::StatusCode::OK.into_response(),
+        Ok(false) => axum::http::StatusCode::NOT_FOUND.into_response(),
+        Err(err) => (axum::http::StatusCode::INTERNAL_SERVER_ERROR, axum::Json(json!({"error": err.to_string()}))).into_response(),
+    }
+}
+
 fn create_config_input_for_m3u(url: &str) -> ConfigInput {
     ConfigInput {
         id: 0,
@@ -312,6 +377,10 @@ async fn create_status_check(app_state: &Arc<AppState>) -> StatusCheck {
     };
 
     let active_provider_connections = app_state.active_provider.active_connections().await.map(|c| c.into_iter().collect::<BTreeMap<_, _>>());
+    let provider_accounts = {
+        let accounts = app_state.provider_accounts.read().await;
+        if accounts.is_empty() { None } else { Some(accounts.values().cloned().collect()) }
+    };
 
     StatusCheck {
         status: "ok".to_string(),
@@ -323,6 +392,8 @@ async fn create_status_check(app_state: &Arc<AppState>) -> StatusCheck {
         active_user_connections,
         active_provider_connections,
         cache,
+        last_update: load_processing_stats(&app_state.config),
+        provider_accounts,
     }
 }
 #[axum::debug_handler]
@@ -346,6 +417,8 @@ pub fn v1_api_register(web_auth_enabled: bool, app_state: Arc<AppState>, web_ui_
         .route("/playlist/webplayer/{target_id}", axum::routing::post(playlist_webplayer))
         .route("/playlist/update", axum::routing::post(playlist_update))
         .route("/playlist", axum::routing::post(playlist_content))
+        .route("/playlist/overrides/{target_name}", axum::routing::get(playlist_overrides).post(save_playlist_override))
+        .route("/playlist/overrides/{target_name}/{uuid}", axum::routing::delete(delete_playlist_override))
         .route("/file/download", axum::routing::post(download_api::queue_download_file))
         .route("/file/download/info", axum::routing::get(download_api::download_file_info));
     if web_auth_enabled {
@@ -358,3 +431,20 @@ pub fn v1_api_register(web_auth_enabled: bool, app_state: Arc<AppState>, web_ui_
     }
     base_router.nest(&format!("{web_ui_path}/api/v1"), router)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::api::endpoints::v1_api::get_unknown_target_response;
+    use crate::model::config::{Config, ConfigSource, ConfigTarget};
+
+    #[test]
+    fn test_unknown_override_target() {
+        let cfg = Config {
+            sources: vec![ConfigSource { inputs: vec![], targets: vec![ConfigTarget { name: String::from("tv"), ..ConfigTarget::default() }] }],
+            ..Config::default()
+        };
+        assert!(get_unknown_target_response(&cfg, "tv").is_none());
+        let response = get_unknown_target_response(&cfg, "unknown").unwrap();
+        assert_eq!(response.status(), axum::http::StatusCode::NOT_FOUND);
+    }
+}
@@ -70,7 +70,7 @@ fn get_epg_path_for_target(config: &Config, target: &ConfigTarget) -> Option<Pat
                     return get_epg_path_for_target_of_type(&target.name, m3u_get_epg_file_path(&target_path));
                 }
             }
-            TargetOutput::Strm(_) | TargetOutput::HdHomeRun(_) => {}
+            TargetOutput::Strm(_) | TargetOutput::HdHomeRun(_) | TargetOutput::Xspf(_) | TargetOutput::Pls(_) | TargetOutput::Enigma2(_) | TargetOutput::Json(_) => {}
         }
     }
     None
@@ -196,10 +196,12 @@ async fn xtream_player_api_stream(
     let (pli, mapping) = try_result_bad_request!(xtream_repository::xtream_get_item_for_stream_id(virtual_id, &app_state.config, target, None), true, format!("Failed to read xtream item for stream id {}", virtual_id));
     let input = try_option_bad_request!(app_state.config.get_input_by_name(pli.input_name.as_str()), true, format!("Cant find input for target {target_name}, context {}, stream_id {virtual_id}", stream_req.context));
     let cluster = pli.xtream_cluster;
+    // the user agent and referrer of the #EXTVLCOPT options are sent to the provider
+    let stream_headers = pli.get_stream_headers();
 
     if let Some(cookie) = is_seek_response(cluster, pli.virtual_id, &app_state.config.t_encrypt_secret, req_headers) {
         // partial request means we are in reverse proxy mode, seek happened
-        return force_provider_stream_response(app_state, &cookie, pli.virtual_id, pli.item_type, req_headers, input, &user).await.into_response()
+        return force_provider_stream_response(app_state, &cookie, pli.virtual_id, pli.item_type, req_headers, input, &stream_headers, &user).await.into_response()
     }
 
     let (provider_name, connection_permission) = check_force_provider(app_state, virtual_id, req_headers, &user).await;
@@ -240,10 +242,10 @@ async fn xtream_player_api_stream(
     let is_hls_request = pli.item_type == PlaylistItemType::LiveHls || pli.item_type == PlaylistItemType::LiveDash || extension == HLS_EXT;
     // Reverse proxy mode
     if is_hls_request {
-        return handle_hls_stream_request(app_state, &user, provider_name, &stream_url, pli.virtual_id, input).await.into_response();
+        return handle_hls_stream_request(app_state, &user, provider_name, &stream_url, pli.virtual_id, input, &stream_headers).await.into_response();
     }
 
-    stream_response(app_state, pli.virtual_id, pli.item_type, &stream_url, req_headers, input, target, &user, connection_permission).await.into_response()
+    stream_response(app_state, pli.virtual_id, pli.item_type, &stream_url, req_headers, input, &stream_headers, target, &user, connection_permission).await.into_response()
 }
 
 async fn xtream_player_api_stream_with_token(
@@ -264,6 +266,7 @@ async fn xtream_player_api_stream_with_token(
         let input = try_option_bad_request!(app_state.config.get_input_by_name(pli.input_name.as_str()), true, format!("Cant find input for target {target_name}, context {}, stream_id {virtual_id}", stream_req.context));
 
         let is_hls_request = pli.item_type == PlaylistItemType::LiveHls || stream_ext.as_deref() == Some(HLS_EXT);
+        let stream_headers = pli.get_stream_headers();
 
         let server = app_state.config.web_ui.as_ref().and_then(|web_ui| web_ui.player_server.as_ref()).map_or("default", |server_name| server_name.as_str());
 
@@ -285,7 +288,7 @@ async fn xtream_player_api_stream_with_token(
 
         // Reverse proxy mode
         if is_hls_request {
-            return handle_hls_stream_request(app_state, &user, None, &pli.url, pli.virtual_id, input).await.into_response();
+            return handle_hls_stream_request(app_state, &user, None, &pli.url, pli.virtual_id, input, &stream_headers).await.into_response();
         }
 
         let extension = stream_ext.unwrap_or_else(
@@ -303,7 +306,7 @@ async fn xtream_player_api_stream_with_token(
         stream_req.context));
 
         trace_if_enabled!("Streaming stream request from {}", sanitize_sensitive_info(&stream_url));
-        stream_response(app_state, pli.virtual_id, pli.item_type, &stream_url, req_headers, input, target, &user, UserConnectionPermission::Allowed).await.into_response()
+        stream_response(app_state, pli.virtual_id, pli.item_type, &stream_url, req_headers, input, &stream_headers, target, &user, UserConnectionPermission::Allowed).await.into_response()
     } else {
         axum::http::StatusCode::BAD_REQUEST.into_response()
     }
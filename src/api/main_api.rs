@@ -1,3 +1,5 @@
+use crate::api::endpoints::enigma2_api::enigma2_api_register;
+use crate::api::endpoints::json_api::json_api_register;
 use crate::api::endpoints::hdhomerun_api::hdhr_api_register;
 use crate::api::endpoints::hls_api::hls_api_register;
 use crate::api::endpoints::m3u_api::m3u_api_register;
@@ -11,9 +13,11 @@ use crate::api::model::app_state::{AppState, HdHomerunAppState};
 use crate::api::model::download::DownloadQueue;
 use crate::api::model::streams::shared_stream_manager::SharedStreamManager;
 use crate::api::scheduler::start_scheduler;
-use crate::model::config::{validate_targets, Config, ProcessTargets, RateLimitConfig, ScheduleConfig};
+use crate::api::account_monitor::{exec_processing_and_update_connections, start_account_monitor, update_provider_max_connections, ProviderAccounts};
+use crate::model::config::{validate_targets, Config, InputType, ProcessTargets, RateLimitConfig, ScheduleConfig};
 use crate::model::healthcheck::{Healthcheck};
-use crate::processing::processor::playlist;
+use crate::repository::m3u_repository::M3uSplitNamesCache;
+use crate::utils::network::{hdhomerun, request};
 use crate::tools::lru_cache::LRUResourceCache;
 use log::{error, info};
 use reqwest::Client;
@@ -22,7 +26,6 @@ use std::io::ErrorKind;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
 use axum::Router;
 use tokio::sync::Mutex;
 use tower_governor::key_extractor::SmartIpKeyExtractor;
@@ -53,11 +56,17 @@ async fn healthcheck() -> impl axum::response::IntoResponse {
     axum::Json(create_healthcheck())
 }
 
-async fn create_shared_data(cfg: &Arc<Config>) -> AppState {
-    let lru_cache = cfg.reverse_proxy.as_ref().and_then(|r| r.cache.as_ref()).and_then(|c| if c.enabled {
+/// The resource cache of the reverse proxy, the cached files are not scanned yet.
+/// # Panics
+/// The cache directory is set when the config is prepared.
+pub fn create_resource_cache(cfg: &Config) -> Arc<Option<Mutex<LRUResourceCache>>> {
+    Arc::new(cfg.reverse_proxy.as_ref().and_then(|r| r.cache.as_ref()).and_then(|c| if c.enabled {
         Some(Mutex::new(LRUResourceCache::new(c.t_size, &PathBuf::from(c.dir.as_ref().unwrap()))))
-    } else { None });
-    let cache = Arc::new(lru_cache);
+    } else { None }))
+}
+
+async fn create_shared_data(cfg: &Arc<Config>) -> AppState {
+    let cache = create_resource_cache(cfg);
     let cache_scanner = Arc::clone(&cache);
     tokio::spawn(async move {
         if let Some(m) = cache_scanner.as_ref() {
@@ -71,31 +80,60 @@ async fn create_shared_data(cfg: &Arc<Config>) -> AppState {
     let active_users = Arc::new(ActiveUserManager::new(cfg.log.as_ref().is_some_and(|l| l.log_active_user)));
     let active_provider = Arc::new(ActiveProviderManager::new(cfg).await);
 
-    let mut builder = Client::builder().http1_only();
-    if cfg.connect_timeout_secs > 0 {
-        builder = builder.connect_timeout(Duration::from_secs(u64::from(cfg.connect_timeout_secs)));
-    }
-
-    let client = builder.build().unwrap_or_else(|_| Client::new());
+    let client = Arc::new(request::create_http_client(cfg.connect_timeout_secs, None).unwrap_or_else(|_| Client::new()));
+    exec_update_tuner_connections(&client, cfg, &active_provider);
 
     AppState {
         config: Arc::clone(cfg),
-        http_client: Arc::new(client),
+        http_client: client,
         downloads: Arc::new(DownloadQueue::new()),
         cache,
         shared_stream_manager: Arc::new(SharedStreamManager::new()),
         active_users,
         active_provider,
+        provider_accounts: Arc::new(ProviderAccounts::default()),
+        m3u_split_names: Arc::new(M3uSplitNamesCache::default()),
     }
 }
 
-fn exec_update_on_boot(client: Arc<reqwest::Client>, cfg: &Arc<Config>, targets: &Arc<ProcessTargets>) {
-    if cfg.update_on_boot {
-        let cfg_clone = Arc::clone(cfg);
-        let targets_clone = Arc::clone(targets);
-        tokio::spawn(
-            async move { playlist::exec_processing(client, cfg_clone, targets_clone).await }
-        );
+// HDHomeRun inputs without max_connections are limited to the tuner count of the device
+fn exec_update_tuner_connections(client: &Arc<reqwest::Client>, cfg: &Arc<Config>, active_provider: &Arc<ActiveProviderManager>) {
+    for input in cfg.sources.iter().flat_map(|source| source.inputs.iter()).filter(|input| input.input_type == InputType::HdHomeRun) {
+        let devices = std::iter::once((input.name.clone(), input.url.clone(), input.max_connections))
+            .chain(input.aliases.iter().flatten().map(|alias| (alias.name.clone(), alias.url.clone(), alias.max_connections)));
+        for (name, url, max_connections) in devices {
+            if max_connections > 0 {
+                continue;
+            }
+            let client = Arc::clone(client);
+            let input = input.clone();
+            let active_provider = Arc::clone(active_provider);
+            tokio::spawn(async move {
+                if let Some(tuner_count) = hdhomerun::get_hdhomerun_tuner_count(client, &input, &url).await {
+                    if active_provider.update_max_connections(&name, tuner_count).await {
+                        info!("Input {name} limited to {tuner_count} tuner connections");
+                    }
+                }
+            });
+        }
+    }
+}
+
+fn exec_account_monitor(app_state: &Arc<AppState>) {
+    if app_state.config.account_monitor.as_ref().is_some_and(|monitor| monitor.enabled) {
+        tokio::spawn(start_account_monitor(Arc::clone(&app_state.http_client), Arc::clone(&app_state.config),
+                                           Arc::clone(&app_state.active_provider), Arc::clone(&app_state.provider_accounts)));
+    } else if app_state.config.sources.iter().flat_map(|source| source.inputs.iter()).any(|input| input.auto_max_connections) {
+        // the account monitor applies the limits on each check
+        tokio::spawn(update_provider_max_connections(Arc::clone(&app_state.http_client), Arc::clone(&app_state.config),
+                                                     Arc::clone(&app_state.active_provider)));
+    }
+}
+
+fn exec_update_on_boot(app_state: &Arc<AppState>, targets: &Arc<ProcessTargets>) {
+    if app_state.config.update_on_boot {
+        tokio::spawn(exec_processing_and_update_connections(Arc::clone(&app_state.http_client), Arc::clone(&app_state.cache), Arc::clone(&app_state.config),
+                                                            Arc::clone(targets), Arc::clone(&app_state.active_provider)));
     }
 }
 
@@ -125,7 +163,8 @@ fn get_process_targets(cfg: &Arc<Config>, process_targets: &Arc<ProcessTargets>,
     Arc::clone(process_targets)
 }
 
-fn exec_scheduler(client: &Arc<reqwest::Client>, cfg: &Arc<Config>, targets: &Arc<ProcessTargets>) {
+fn exec_scheduler(client: &Arc<reqwest::Client>, resource_cache: &Arc<Option<Mutex<LRUResourceCache>>>, cfg: &Arc<Config>,
+                  targets: &Arc<ProcessTargets>, active_provider: &Arc<ActiveProviderManager>) {
     let schedules: Vec<ScheduleConfig> = if let Some(schedules) = &cfg.schedules {
         schedules.clone()
     } else {
@@ -136,8 +175,10 @@ fn exec_scheduler(client: &Arc<reqwest::Client>, cfg: &Arc<Config>, targets: &Ar
         let exec_targets = get_process_targets(cfg, targets, schedule.targets.as_ref());
         let cfg_clone = Arc::clone(cfg);
         let http_client = Arc::clone(client);
+        let cache = Arc::clone(resource_cache);
+        let provider_manager = Arc::clone(active_provider);
         tokio::spawn(async move {
-            start_scheduler(http_client, expression.as_str(), cfg_clone, exec_targets).await;
+            start_scheduler(http_client, cache, expression.as_str(), cfg_clone, exec_targets, provider_manager).await;
         });
     }
 }
@@ -227,8 +268,9 @@ pub async fn start_server(cfg: Arc<Config>, targets: Arc<ProcessTargets>) -> fut
     let app_state = Arc::new(app_shared_data);
     let shared_data = Arc::clone(&app_state);
 
-    exec_scheduler(&Arc::clone(&shared_data.http_client), &cfg, &targets);
-    exec_update_on_boot(Arc::clone(&shared_data.http_client), &cfg, &targets);
+    exec_scheduler(&Arc::clone(&shared_data.http_client), &shared_data.cache, &cfg, &targets, &shared_data.active_provider);
+    exec_update_on_boot(&shared_data, &targets);
+    exec_account_monitor(&shared_data);
     let web_auth_enabled = is_web_auth_enabled(&cfg, web_ui_enabled);
 
     if cfg.t_api_proxy.read().await.is_some() {
@@ -258,6 +300,8 @@ pub async fn start_server(cfg: Arc<Config>, targets: Arc<ProcessTargets>) -> fut
     let mut api_router = axum::Router::new()
         .merge(xtream_api_register())
         .merge(m3u_api_register())
+        .merge(enigma2_api_register())
+        .merge(json_api_register())
         .merge(xmltv_api_register())
         .merge(hls_api_register());
     let mut rate_limiting = false;
@@ -1,5 +1,6 @@
 pub mod model;
 pub mod api_utils;
 mod scheduler;
+mod account_monitor;
 mod endpoints;
 pub mod main_api;
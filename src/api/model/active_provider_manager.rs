@@ -2,7 +2,7 @@ use crate::model::config::{Config, ConfigInput, ConfigInputAlias, InputType, Inp
 use log::{debug, log_enabled};
 use std::collections::HashMap;
 use std::ops::Deref;
-use std::sync::atomic::{AtomicU16, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -69,6 +69,7 @@ pub enum ProviderAllocation {
 /// `max_connections`: Maximum allowed concurrent connections.
 /// `priority`: Priority level for selecting providers.
 /// `current_connections`: A `RwLock` to safely track the number of active connections.
+/// `disabled`: Disabled providers are not allocated, e.g. when the account is expired.
 #[derive(Debug)]
 pub struct ProviderConfig {
     pub id: u16,
@@ -77,9 +78,10 @@ pub struct ProviderConfig {
     pub username: Option<String>,
     pub password: Option<String>,
     pub input_type: InputType,
-    max_connections: u16,
+    max_connections: AtomicU16,
     priority: i16,
     current_connections: AtomicU16,
+    disabled: AtomicBool,
 }
 
 impl ProviderConfig {
@@ -91,9 +93,10 @@ impl ProviderConfig {
             username: cfg.username.clone(),
             password: cfg.password.clone(),
             input_type: cfg.input_type,
-            max_connections: cfg.max_connections,
+            max_connections: AtomicU16::new(cfg.max_connections),
             priority: cfg.priority,
             current_connections: AtomicU16::new(0),
+            disabled: AtomicBool::new(false),
         }
     }
 
@@ -105,9 +108,10 @@ impl ProviderConfig {
             username: alias.username.clone(),
             password: alias.password.clone(),
             input_type: cfg.input_type,
-            max_connections: alias.max_connections,
+            max_connections: AtomicU16::new(alias.max_connections),
             priority: alias.priority,
             current_connections: AtomicU16::new(0),
+            disabled: AtomicBool::new(false),
         }
     }
 
@@ -115,14 +119,38 @@ impl ProviderConfig {
         InputUserInfo::new(self.input_type, self.username.as_deref(), self.password.as_deref(), &self.url)
     }
 
+    #[inline]
+    pub fn get_max_connections(&self) -> u16 {
+        self.max_connections.load(Ordering::SeqCst)
+    }
+
+    /// The limit can change at runtime, e.g. when it is read from the device or provider.
+    pub fn set_max_connections(&self, max_connections: u16) {
+        self.max_connections.store(max_connections, Ordering::SeqCst);
+    }
+
+    #[inline]
+    pub fn is_disabled(&self) -> bool {
+        self.disabled.load(Ordering::SeqCst)
+    }
+
+    pub fn set_disabled(&self, disabled: bool) {
+        self.disabled.store(disabled, Ordering::SeqCst);
+    }
+
     #[inline]
     pub fn is_exhausted(&self) -> bool {
-        self.max_connections > 0 && self.current_connections.load(Ordering::SeqCst) >= self.max_connections
+        if self.is_disabled() {
+            return true;
+        }
+        let max_connections = self.get_max_connections();
+        max_connections > 0 && self.current_connections.load(Ordering::SeqCst) >= max_connections
     }
 
     #[inline]
     pub fn is_over_limit(&self) -> bool {
-        self.max_connections > 0 && self.current_connections.load(Ordering::SeqCst) > self.max_connections
+        let max_connections = self.get_max_connections();
+        max_connections > 0 && self.current_connections.load(Ordering::SeqCst) > max_connections
     }
 
     //
@@ -132,14 +160,18 @@ impl ProviderConfig {
     // }
 
     fn try_allocate(&self, grace: bool) -> u8 {
+        if self.is_disabled() {
+            return 3;
+        }
         let connections = self.current_connections.load(Ordering::SeqCst);
-        if self.max_connections == 0 {
+        let max_connections = self.get_max_connections();
+        if max_connections == 0 {
             self.current_connections.fetch_add(1, Ordering::SeqCst);
             return 1;
         }
-        if (!grace && connections < self.max_connections) || (grace && connections <= self.max_connections) {
+        if (!grace && connections < max_connections) || (grace && connections <= max_connections) {
             self.current_connections.fetch_add(1, Ordering::SeqCst);
-            return if connections < self.max_connections { 1 } else { 2 };
+            return if connections < max_connections { 1 } else { 2 };
         }
         3
     }
@@ -150,11 +182,15 @@ impl ProviderConfig {
 
     // is intended to use with redirects, to cycle through provider
     fn get_next(&self, grace: bool) -> bool {
+        if self.is_disabled() {
+            return false;
+        }
         let connections = self.current_connections.load(Ordering::SeqCst);
-        if self.max_connections == 0 {
+        let max_connections = self.get_max_connections();
+        if max_connections == 0 {
             return true;
         }
-        if (!grace && connections < self.max_connections) || (grace && connections <= self.max_connections) {
+        if (!grace && connections < max_connections) || (grace && connections <= max_connections) {
             return true;
         }
         false
@@ -648,6 +684,30 @@ impl ActiveProviderManager {
         }
     }
 
+    /// Updates the connection limit of a provider, returns false if there is no provider with this name.
+    pub async fn update_max_connections(&self, provider_name: &str, max_connections: u16) -> bool {
+        let providers = self.providers.read().await;
+        match Self::get_provider_config(provider_name, &providers) {
+            Some((_lineup, config)) => {
+                config.set_max_connections(max_connections);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Disabled providers are skipped when connections are acquired, e.g. expired accounts.
+    pub async fn set_provider_disabled(&self, provider_name: &str, disabled: bool) -> bool {
+        let providers = self.providers.read().await;
+        match Self::get_provider_config(provider_name, &providers) {
+            Some((_lineup, config)) => {
+                config.set_disabled(disabled);
+                true
+            }
+            None => false,
+        }
+    }
+
     // we need the provider_name to exactly release this provider
     pub async fn release_connection(&self, provider_name: &str) {
         let providers = self.providers.read().await;
@@ -756,12 +816,21 @@ mod tests {
             enabled: true,
             input_type: InputType::Xtream, // You can use a default value here
             max_connections,
+            auto_max_connections: false,
             priority,
             aliases: None,
             headers: HashMap::default(),
             options: None,
             method: InputFetchMethod::default(),
+            mapping: None,
+            retry: None,
+            proxy: None,
+            auth: None,
+            filter: None,
             t_base_url: String::default(),
+            t_filter: None,
+            t_client: None,
+            t_portal_token: Arc::default(),
         }
     }
 
@@ -775,7 +844,10 @@ mod tests {
             password: Some("alias_pass".to_string()),
             priority,
             max_connections,
+            proxy: None,
             t_base_url: String::default(),
+            t_client: None,
+            t_portal_token: Arc::default(),
         }
     }
 
@@ -801,6 +873,24 @@ mod tests {
         should_exhausted!(lineup);
     }
 
+    // Test runtime changes of the connection limit and disabled aliases
+    #[test]
+    fn test_provider_disabled_and_max_connections() {
+        let mut input = create_config_input(1, "provider3_1", 1, 1);
+        input.aliases = Some(vec![create_config_input_alias(2, "http://alias3", 1, 1)]);
+        let providers = vec![ProviderLineup::Multi(MultiProviderLineup::new(&input))];
+        let (lineup, alias) = ActiveProviderManager::get_provider_config("alias_2", &providers).unwrap();
+        alias.set_disabled(true);
+        let (_, provider) = ActiveProviderManager::get_provider_config("provider3_1", &providers).unwrap();
+        provider.set_max_connections(2);
+        should_available!(lineup, 1);
+        should_available!(lineup, 1);
+        should_grace_period!(lineup, 1);
+        should_exhausted!(lineup);
+        alias.set_disabled(false);
+        should_available!(lineup, 2);
+    }
+
     // // Test acquiring from a MultiProviderLineup where the alias has a different priority
     #[test]
     fn test_provider_with_priority_alias() {
@@ -1,5 +1,6 @@
 use tokio::sync::{Mutex};
 use std::sync::Arc;
+use crate::api::account_monitor::ProviderAccounts;
 use crate::api::model::active_provider_manager::ActiveProviderManager;
 use crate::api::model::active_user_manager::ActiveUserManager;
 use crate::api::model::download::DownloadQueue;
@@ -7,6 +8,7 @@ use crate::api::model::streams::shared_stream_manager::SharedStreamManager;
 use crate::model::api_proxy::UserConnectionPermission;
 use crate::model::config::{Config};
 use crate::model::hdhomerun_config::HdHomeRunDeviceConfig;
+use crate::repository::m3u_repository::M3uSplitNamesCache;
 use crate::tools::lru_cache::LRUResourceCache;
 use crate::utils::default_utils::{default_grace_period_millis, default_grace_period_timeout_secs};
 
@@ -19,6 +21,8 @@ pub struct AppState {
     pub shared_stream_manager: Arc<SharedStreamManager>,
     pub active_users: Arc<ActiveUserManager>,
     pub active_provider: Arc<ActiveProviderManager>,
+    pub provider_accounts: Arc<ProviderAccounts>,
+    pub m3u_split_names: Arc<M3uSplitNamesCache>,
 }
 
 impl AppState {
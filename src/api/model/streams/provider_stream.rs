@@ -74,6 +74,7 @@ pub fn get_header_filter_for_item_type(item_type: PlaylistItemType) -> HeaderFil
 }
 
 pub async fn get_provider_pipe_stream(app_state: &AppState,
+                                      http_client: &Arc<reqwest::Client>,
                                       stream_url: &Url,
                                       req_headers: &HeaderMap,
                                       input_headers: Option<&HashMap<String, String>>,
@@ -85,7 +86,7 @@ pub async fn get_provider_pipe_stream(app_state: &AppState,
     // The stream url, we need to clone it because of move to async block.
     // We merge configured input headers with the headers from the request.
     let headers = get_request_headers(input_headers, Some(&req_headers));
-    let client = app_state.http_client.get(stream_url.clone()).headers(headers.clone());
+    let client = http_client.get(stream_url.clone()).headers(headers.clone());
     match client.send().await {
         Ok(response) => {
             let response_headers = get_response_headers(response.headers());
@@ -112,13 +113,14 @@ pub async fn get_provider_pipe_stream(app_state: &AppState,
 }
 
 pub async fn get_provider_reconnect_buffered_stream(app_state: &AppState,
+                                                    http_client: Arc<reqwest::Client>,
                                                     stream_url: &Url,
                                                     req_headers: &HeaderMap,
                                                     input_headers: Option<&HashMap<String, String>>,
                                                     options: BufferStreamOptions) -> ProviderStreamResponse {
-    match create_provider_stream(&app_state.config, Arc::clone(&app_state.http_client), stream_url, req_headers, input_headers, options).await {
-        None => (None, None),
-        Some((stream, info)) => {
+    match create_provider_stream(&app_state.config, http_client, stream_url, req_headers, input_headers, options).await {
+        Err(status) => (None, Some((Vec::new(), status))),
+        Ok((stream, info)) => {
             (Some(stream), info)
         }
     }
@@ -9,10 +9,11 @@ use crate::model::config::{Config};
 use crate::model::playlist::PlaylistItemType;
 use crate::tools::atomic_once_flag::AtomicOnceFlag;
 use crate::utils::debug_if_enabled;
+use crate::utils::network::input_auth::{is_token_rejected, InputTokenHeader};
 use crate::utils::network::request::{classify_content_type, get_request_headers, sanitize_sensitive_info, MimeCategory};
 use futures::stream::{self};
 use futures::{StreamExt, TryStreamExt};
-use log::{debug, warn};
+use log::{debug, error, warn};
 use reqwest::header::{HeaderMap, RANGE};
 use reqwest::StatusCode;
 use std::collections::HashMap;
@@ -32,14 +33,16 @@ pub struct BufferStreamOptions {
     buffer_enabled: bool,
     buffer_size: usize,
     share_stream: bool,
-    reconnect_flag: Arc<AtomicOnceFlag>
+    reconnect_flag: Arc<AtomicOnceFlag>,
+    input_token: Option<InputTokenHeader>,
 }
 
 impl BufferStreamOptions {
     pub(crate) fn new(
         item_type: PlaylistItemType,
         share_stream: bool,
-        stream_options: &StreamOptions
+        stream_options: &StreamOptions,
+        input_token: Option<InputTokenHeader>,
     ) -> Self {
         Self {
             item_type,
@@ -48,7 +51,8 @@ impl BufferStreamOptions {
             buffer_enabled: stream_options.buffer_enabled,
             buffer_size: stream_options.buffer_size,
             share_stream,
-            reconnect_flag: Arc::new(AtomicOnceFlag::new())
+            reconnect_flag: Arc::new(AtomicOnceFlag::new()),
+            input_token,
         }
     }
 
@@ -94,6 +98,7 @@ struct ProviderStreamOptions {
     reconnect_force_secs: u32,
     headers: HeaderMap,
     range_bytes: Arc<Option<AtomicUsize>>,
+    input_token: Option<InputTokenHeader>,
 }
 
 impl ProviderStreamOptions {
@@ -135,6 +140,16 @@ impl ProviderStreamOptions {
         &self.headers
     }
 
+    // the token of the input could be refreshed since the last request
+    async fn refresh_token_header(&mut self) {
+        if let Some(input_token) = self.input_token.as_ref() {
+            match input_token.get_header().await {
+                Ok((name, value)) => { self.headers.insert(name, value); }
+                Err(err) => error!("{err}"),
+            }
+        }
+    }
+
     #[inline]
     pub fn get_total_bytes_send(&self) -> Option<usize> {
         self.range_bytes.as_ref().as_ref().map(|atomic| atomic.load(Ordering::SeqCst))
@@ -250,11 +265,13 @@ async fn provider_initial_request(cfg: &Config, request_client: Arc<reqwest::Cli
     }
 }
 
-async fn stream_provider(client: Arc<reqwest::Client>, stream_options: ProviderStreamOptions) -> Option<BoxedProviderStream> {
-    let url = stream_options.get_url();
+async fn stream_provider(client: Arc<reqwest::Client>, mut stream_options: ProviderStreamOptions) -> Option<BoxedProviderStream> {
+    let url = stream_options.get_url().clone();
     debug_if_enabled!("stream provider {}", sanitize_sensitive_info(url.as_str()));
+    let mut token_rejected = false;
     while stream_options.should_continue() {
         debug_if_enabled!("Reconnecting stream {}", sanitize_sensitive_info(url.as_str()));
+        stream_options.refresh_token_header().await;
         let (client, _) = prepare_client(&client, &stream_options);
         match client.send().await {
             Ok(response) => {
@@ -270,6 +287,12 @@ async fn stream_provider(client: Arc<reqwest::Client>, stream_options: ProviderS
                         Some(provider_stream)
                     };
                 }
+                if let Some(input_token) = stream_options.input_token.as_ref().filter(|_| !token_rejected && is_token_rejected(status)) {
+                    // the token was revoked while streaming, login again and retry once
+                    input_token.invalidate().await;
+                    token_rejected = true;
+                    continue;
+                }
                 if status.is_client_error() {
                     debug!("Client error status response : {status}");
                     return None;
@@ -302,18 +325,20 @@ async fn stream_provider(client: Arc<reqwest::Client>, stream_options: ProviderS
 
 const RETRY_SECONDS: u64 = 5;
 const ERR_MAX_RETRY_COUNT: u32 = 5;
-async fn get_initial_stream(cfg: &Config, client: Arc<reqwest::Client>, stream_options: &ProviderStreamOptions) -> Option<ProviderStreamFactoryResponse> {
+async fn get_initial_stream(cfg: &Config, client: Arc<reqwest::Client>, stream_options: &ProviderStreamOptions) -> Result<ProviderStreamFactoryResponse, StatusCode> {
     let start = Instant::now();
     let mut connect_err: u32 = 1;
+    let mut last_status = StatusCode::SERVICE_UNAVAILABLE;
     while stream_options.should_continue() {
         match provider_initial_request(cfg, Arc::clone(&client), stream_options).await {
-            Ok(Some(value)) => return Some(value),
+            Ok(Some(value)) => return Ok(value),
             Ok(None) => {
                 if connect_err > ERR_MAX_RETRY_COUNT {
                     warn!("The stream could be unavailable. {}", sanitize_sensitive_info(stream_options.get_url().as_str()));
                 }
             }
             Err(status) => {
+                last_status = status;
                 if status == StatusCode::FORBIDDEN || status == StatusCode::SERVICE_UNAVAILABLE || status == StatusCode::UNAUTHORIZED {
                     warn!("The stream could be unavailable. ({status}) {}", sanitize_sensitive_info(stream_options.get_url().as_str()));
                     break;
@@ -334,7 +359,7 @@ async fn get_initial_stream(cfg: &Config, client: Arc<reqwest::Client>, stream_o
         tokio::time::sleep(Duration::from_millis(100)).await;
     }
     stream_options.cancel_reconnect();
-    None
+    Err(last_status)
 }
 
 fn create_provider_stream_options(stream_url: &Url,
@@ -354,6 +379,7 @@ fn create_provider_stream_options(stream_url: &Url,
         reconnect_force_secs,
         headers,
         range_bytes,
+        input_token: options.input_token.clone(),
     }
 }
 
@@ -362,7 +388,7 @@ pub async fn create_provider_stream(cfg: &Config,
                                     stream_url: &Url,
                                     req_headers: &HeaderMap,
                                     input_headers: Option<&HashMap<String, String>>,
-                                    options: BufferStreamOptions) -> Option<ProviderStreamFactoryResponse> {
+                                    options: BufferStreamOptions) -> Result<ProviderStreamFactoryResponse, StatusCode> {
     let stream_options = create_provider_stream_options(stream_url, req_headers, input_headers, &options);
 
     let client_stream_factory = |stream, reconnect_flag, range_cnt| {
@@ -375,7 +401,7 @@ pub async fn create_provider_stream(cfg: &Config,
     };
 
     match get_initial_stream(cfg, Arc::clone(&client), &stream_options).await {
-        Some((init_stream, info)) => {
+        Ok((init_stream, info)) => {
             let is_media_stream = if let Some((headers, _)) = &info {
                 classify_content_type(headers) == MimeCategory::Video
             } else {
@@ -400,12 +426,12 @@ pub async fn create_provider_stream(cfg: &Config,
                         }
                     }
                 }).flatten().boxed();
-                Some((client_stream_factory(init_stream.chain(unfold).boxed(), Arc::clone(&continue_client_signal), stream_options.get_range_bytes_clone()).boxed(), info))
+                Ok((client_stream_factory(init_stream.chain(unfold).boxed(), Arc::clone(&continue_client_signal), stream_options.get_range_bytes_clone()).boxed(), info))
             } else {
-                Some((client_stream_factory(init_stream.boxed(), Arc::clone(&continue_signal), stream_options.get_range_bytes_clone()).boxed(), info))
+                Ok((client_stream_factory(init_stream.boxed(), Arc::clone(&continue_signal), stream_options.get_range_bytes_clone()).boxed(), info))
             }
         }
-        None => None
+        Err(status) => Err(status)
     }
 }
 
@@ -5,8 +5,11 @@ use chrono::{DateTime, FixedOffset, Local};
 use cron::Schedule;
 use log::error;
 use crate::utils::sys_utils::exit;
+use crate::api::account_monitor::exec_processing_and_update_connections;
+use crate::api::model::active_provider_manager::ActiveProviderManager;
 use crate::model::config::{Config, ProcessTargets};
-use crate::processing::processor::playlist::exec_processing;
+use crate::tools::lru_cache::LRUResourceCache;
+use tokio::sync::Mutex;
 
 pub fn datetime_to_instant(datetime: DateTime<FixedOffset>) -> Instant {
     // Convert DateTime<FixedOffset> to SystemTime
@@ -24,7 +27,8 @@ pub fn datetime_to_instant(datetime: DateTime<FixedOffset>) -> Instant {
     Instant::now() + duration_until
 }
 
-pub async fn start_scheduler(client: Arc<reqwest::Client>, expression: &str, config: Arc<Config>, targets: Arc<ProcessTargets>) -> ! {
+pub async fn start_scheduler(client: Arc<reqwest::Client>, resource_cache: Arc<Option<Mutex<LRUResourceCache>>>, expression: &str, config: Arc<Config>, targets: Arc<ProcessTargets>,
+                             active_provider: Arc<ActiveProviderManager>) -> ! {
     match Schedule::from_str(expression) {
         Ok(schedule) => {
             let offset = *Local::now().offset();
@@ -32,7 +36,7 @@ pub async fn start_scheduler(client: Arc<reqwest::Client>, expression: &str, con
                 let mut upcoming = schedule.upcoming(offset).take(1);
                 if let Some(datetime) = upcoming.next() {
                     tokio::time::sleep_until(tokio::time::Instant::from(datetime_to_instant(datetime))).await;
-                    exec_processing(Arc::clone(&client), Arc::clone(&config), Arc::clone(&targets)).await;
+                    exec_processing_and_update_connections(Arc::clone(&client), Arc::clone(&resource_cache), Arc::clone(&config), Arc::clone(&targets), Arc::clone(&active_provider)).await;
                  }
             }
         }
@@ -24,6 +24,7 @@ pub fn get_field_value(pli: &PlaylistItem, field: &ItemField) -> String {
         ItemField::Input => header.input_name.to_string(),
         ItemField::Type => header.item_type.to_string(),
         ItemField::Caption => if header.title.is_empty() { header.name.to_string() } else { header.title.to_string() },
+        ItemField::Chno => header.chno.clone(),
     };
     value.to_string()
 }
@@ -36,6 +37,7 @@ pub fn set_field_value(pli: &mut PlaylistItem, field: &ItemField, value: String)
         ItemField::Title => header.title = value,
         ItemField::Url => header.url = value,
         ItemField::Input => header.input_name = value,
+        ItemField::Chno => header.chno = value,
         ItemField::Caption => {
             header.title.clone_from(&value);
             header.name = value;
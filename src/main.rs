@@ -198,7 +198,13 @@ fn create_directories(cfg: &Config, temp_path: &Path) {
 
 async fn start_in_cli_mode(cfg: Arc<Config>, targets: Arc<ProcessTargets>) {
     let client = Arc::new(reqwest::Client::new());
-    playlist::exec_processing(client, cfg, targets).await;
+    let resource_cache = api::main_api::create_resource_cache(&cfg);
+    if let Some(cache) = resource_cache.as_ref() {
+        if let Err(err) = cache.lock().await.scan() {
+            error!("Failed to scan cache {err}");
+        }
+    }
+    playlist::exec_processing(client, resource_cache, cfg, targets).await;
 }
 
 async fn start_in_server_mode(cfg: Arc<Config>, targets: Arc<ProcessTargets>) {
@@ -12,6 +12,8 @@ pub enum MsgKind {
     Error,
     #[serde(rename = "watch")]
     Watch,
+    #[serde(rename = "account")]
+    Account,
 }
 
 fn is_enabled(kind: &MsgKind, cfg: &MessagingConfig) -> bool {
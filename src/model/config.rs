@@ -28,7 +28,7 @@ use crate::messaging::MsgKind;
 use crate::model::api_proxy::{ApiProxyConfig, ApiProxyServerInfo, ProxyUserCredentials};
 use crate::model::mapping::Mapping;
 use crate::model::mapping::Mappings;
-use crate::utils::default_utils::{default_as_default, default_as_true, default_resolve_delay_secs, default_grace_period_millis, default_grace_period_timeout_secs, default_connect_timeout_secs};
+use crate::utils::default_utils::{default_as_default, default_as_true, default_resolve_delay_secs, default_grace_period_millis, default_grace_period_timeout_secs, default_connect_timeout_secs, default_retry_delay_secs, default_retry_max_delay_secs, default_account_monitor_interval_mins, default_account_expiry_warning_days, default_auth_header, default_auth_prefix};
 use crate::utils::file::file_lock_manager::FileLockManager;
 use crate::utils::file::file_utils;
 use crate::utils::file::file_utils::file_reader;
@@ -40,7 +40,7 @@ const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (AppleTV; U; CPU OS 14_2 like Mac
 pub const MAPPER_ATTRIBUTE_FIELDS: &[&str] = &[
     "name", "title", "caption", "group", "id", "chno", "logo",
     "logo_small", "parent_code", "audio_track",
-    "time_shift", "rec", "url", "epg_channel_id", "epg_id"
+    "time_shift", "rec", "catchup", "catchup_days", "catchup_source", "url", "epg_channel_id", "epg_id"
 ];
 
 pub const AFFIX_FIELDS: &[&str] = &["name", "title", "caption", "group"];
@@ -73,7 +73,8 @@ use crate::model::hdhomerun_config::HdHomeRunConfig;
 use crate::model::playlist::{PlaylistItemType, XtreamCluster};
 use crate::utils::constants::CONSTANTS;
 use crate::utils::file::config_reader::csv_read_inputs;
-use crate::utils::network::request::{get_base_url_from_str, get_credentials_from_url, get_credentials_from_url_str};
+use crate::utils::network::input_auth::InputAuthToken;
+use crate::utils::network::request::{create_http_client, get_base_url_from_str, get_credentials_from_url, get_credentials_from_url_str, sanitize_sensitive_info};
 use crate::utils::string_utils::get_trimmed_string;
 
 #[derive(Debug, Copy, Clone, serde::Serialize, serde::Deserialize, Sequence, PartialEq, Eq, Hash)]
@@ -86,6 +87,14 @@ pub enum TargetType {
     Strm,
     #[serde(rename = "hdhomerun")]
     HdHomeRun,
+    #[serde(rename = "xspf")]
+    Xspf,
+    #[serde(rename = "pls")]
+    Pls,
+    #[serde(rename = "enigma2")]
+    Enigma2,
+    #[serde(rename = "json")]
+    Json,
 }
 
 impl TargetType {
@@ -93,6 +102,10 @@ impl TargetType {
     const XTREAM: &'static str = "Xtream";
     const STRM: &'static str = "Strm";
     const HDHOMERUN: &'static str = "HdHomeRun";
+    const XSPF: &'static str = "Xspf";
+    const PLS: &'static str = "Pls";
+    const ENIGMA2: &'static str = "Enigma2";
+    const JSON: &'static str = "Json";
 }
 
 impl Display for TargetType {
@@ -102,6 +115,10 @@ impl Display for TargetType {
             Self::Xtream => Self::XTREAM,
             Self::Strm => Self::STRM,
             Self::HdHomeRun => Self::HDHOMERUN,
+            Self::Xspf => Self::XSPF,
+            Self::Pls => Self::PLS,
+            Self::Enigma2 => Self::ENIGMA2,
+            Self::Json => Self::JSON,
         })
     }
 }
@@ -182,6 +199,8 @@ pub enum ItemField {
     Type,
     #[serde(rename = "caption")]
     Caption,
+    #[serde(rename = "chno")]
+    Chno,
 }
 
 impl ItemField {
@@ -192,6 +211,7 @@ impl ItemField {
     const INPUT: &'static str = "Input";
     const TYPE: &'static str = "Type";
     const CAPTION: &'static str = "Caption";
+    const CHNO: &'static str = "Chno";
 }
 
 impl Display for ItemField {
@@ -204,6 +224,7 @@ impl Display for ItemField {
             Self::Input => Self::INPUT,
             Self::Type => Self::TYPE,
             Self::Caption => Self::CAPTION,
+            Self::Chno => Self::CHNO,
         })
     }
 }
@@ -216,9 +237,10 @@ pub enum FilterMode {
     Include,
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 pub enum SortOrder {
     #[serde(rename = "asc")]
+    #[default]
     Asc,
     #[serde(rename = "desc")]
     Desc,
@@ -241,28 +263,96 @@ impl ProcessTargets {
     }
 }
 
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+pub enum SortCompare {
+    #[serde(rename = "text")]
+    #[default]
+    Text,
+    #[serde(rename = "natural")]
+    Natural,
+    #[serde(rename = "numeric")]
+    Numeric,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ConfigSortGroup {
+    #[serde(default)]
+    pub order: SortOrder,
+    #[serde(default)]
+    pub compare: SortCompare,
+    // list of regular expressions matched against group title
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<Vec<String>>,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub t_re_sequence: Option<Vec<regex::Regex>>,
+}
+
+impl ConfigSortGroup {
+    pub fn prepare(&mut self) -> Result<(), M3uFilterError> {
+        if let Some(sequence) = self.sequence.as_ref() {
+            let regexps: Result<Vec<regex::Regex>, _> = sequence.iter().map(|s| regex::Regex::new(s)).collect();
+            match regexps {
+                Ok(re_sequence) => self.t_re_sequence = Some(re_sequence),
+                Err(err) => return create_m3u_filter_error_result!(M3uFilterErrorKind::Info, "cant parse group sort sequence regex: {err}"),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigSortKey {
+    pub field: ItemField,
+    #[serde(default)]
     pub order: SortOrder,
+    #[serde(default)]
+    pub compare: SortCompare,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ConfigSortChannel {
-    pub field: ItemField,
     // channel field
-    pub group_pattern: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<ItemField>,
     // match against group title
+    pub group_pattern: String,
+    #[serde(default)]
     pub order: SortOrder,
+    #[serde(default)]
+    pub compare: SortCompare,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub sequence: Option<Vec<String>>,
+    // additional sort keys, applied in the given order when the previous keys are equal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keys: Option<Vec<ConfigSortKey>>,
     #[serde(skip_serializing, skip_deserializing)]
     pub re: Option<regex::Regex>,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub t_keys: Vec<ConfigSortKey>,
 }
 
 impl ConfigSortChannel {
     pub fn prepare(&mut self) -> Result<(), M3uFilterError> {
+        self.t_keys = Vec::new();
+        if let Some(field) = self.field.as_ref() {
+            self.t_keys.push(ConfigSortKey {
+                field: field.clone(),
+                order: self.order.clone(),
+                compare: self.compare.clone(),
+                sequence: self.sequence.clone(),
+            });
+        }
+        if let Some(keys) = self.keys.as_ref() {
+            self.t_keys.extend(keys.iter().cloned());
+        }
+        if self.t_keys.is_empty() {
+            return create_m3u_filter_error_result!(M3uFilterErrorKind::Info, "channel sort needs a field or keys: {}", &self.group_pattern);
+        }
         match regex::Regex::new(&self.group_pattern) {
             Ok(pattern) => {
                 self.re = Some(pattern);
@@ -286,6 +376,9 @@ pub struct ConfigSort {
 
 impl ConfigSort {
     pub fn prepare(&mut self) -> Result<(), M3uFilterError> {
+        if let Some(groups) = self.groups.as_mut() {
+            handle_m3u_filter_error_result!(M3uFilterErrorKind::Info, groups.prepare());
+        }
         if let Some(channels) = self.channels.as_mut() {
             handle_m3u_filter_error_result_list!(M3uFilterErrorKind::Info, channels.iter_mut().map(ConfigSortChannel::prepare));
         }
@@ -315,6 +408,175 @@ impl ConfigRename {
     }
 }
 
+fn compile_group_patterns(patterns: &[String]) -> Result<Vec<regex::Regex>, M3uFilterError> {
+    patterns.iter().map(|pattern| regex::Regex::new(pattern)
+        .map_err(|err| info_err!(format!("cant parse regex: {pattern} {err}")))).collect()
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigGroupMerge {
+    // group title patterns, all matching groups are merged into `name`
+    pub groups: Vec<String>,
+    pub name: String,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub t_re: Vec<regex::Regex>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Sequence, PartialEq, Eq, Default)]
+pub enum GroupSplitBy {
+    #[serde(rename = "alphabet")]
+    #[default]
+    Alphabet,
+    #[serde(rename = "field")]
+    Field,
+}
+
+fn default_group_split_template() -> String { String::from("{group} {key}") }
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigGroupSplit {
+    pub group_pattern: String,
+    #[serde(default)]
+    pub by: GroupSplitBy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<ItemField>,
+    // only groups with more channels are split, alphabet buckets are filled up to this size
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size: Option<usize>,
+    #[serde(default = "default_group_split_template")]
+    pub template: String,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub re: Option<regex::Regex>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigGroupParent {
+    pub groups: Vec<String>,
+    pub parent: String,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub t_re: Vec<regex::Regex>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ConfigGroupRule {
+    Merge(ConfigGroupMerge),
+    Split(ConfigGroupSplit),
+    Parent(ConfigGroupParent),
+}
+
+impl ConfigGroupRule {
+    pub fn prepare(&mut self) -> Result<(), M3uFilterError> {
+        match self {
+            ConfigGroupRule::Merge(merge) => {
+                merge.name = merge.name.trim().to_string();
+                if merge.name.is_empty() {
+                    return create_m3u_filter_error_result!(M3uFilterErrorKind::Info, "group merge rule needs a name");
+                }
+                merge.t_re = compile_group_patterns(&merge.groups)?;
+            }
+            ConfigGroupRule::Split(split) => {
+                if split.by == GroupSplitBy::Field && split.field.is_none() {
+                    return create_m3u_filter_error_result!(M3uFilterErrorKind::Info, "group split by field needs a field: {}", &split.group_pattern);
+                }
+                if split.max_size == Some(0) {
+                    return create_m3u_filter_error_result!(M3uFilterErrorKind::Info, "group split max_size must be greater than 0: {}", &split.group_pattern);
+                }
+                split.re = Some(compile_group_patterns(std::slice::from_ref(&split.group_pattern))?.remove(0));
+            }
+            ConfigGroupRule::Parent(parent) => {
+                parent.parent = parent.parent.trim().to_string();
+                if parent.parent.is_empty() {
+                    return create_m3u_filter_error_result!(M3uFilterErrorKind::Info, "group parent rule needs a parent name");
+                }
+                parent.t_re = compile_group_patterns(&parent.groups)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Sequence, PartialEq, Eq, Default)]
+pub enum ChannelNumberingStrategy {
+    #[serde(rename = "sequential")]
+    #[default]
+    Sequential,
+    #[serde(rename = "persistent")]
+    Persistent,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigChannelNumberRange {
+    // match against group title
+    pub group_pattern: String,
+    pub start: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<u32>,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub re: Option<regex::Regex>,
+}
+
+impl ConfigChannelNumberRange {
+    pub fn prepare(&mut self) -> Result<(), M3uFilterError> {
+        if self.start == 0 || self.end.is_some_and(|end| end < self.start) {
+            return create_m3u_filter_error_result!(M3uFilterErrorKind::Info, "invalid channel number range {}-{} for {}", self.start, self.end.unwrap_or(u32::MAX), &self.group_pattern);
+        }
+        match regex::Regex::new(&self.group_pattern) {
+            Ok(pattern) => {
+                self.re = Some(pattern);
+                Ok(())
+            }
+            Err(err) => create_m3u_filter_error_result!(M3uFilterErrorKind::Info, "cant parse regex: {} {err}", &self.group_pattern),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigChannelNumbering {
+    #[serde(default)]
+    pub strategy: ChannelNumberingStrategy,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ranges: Vec<ConfigChannelNumberRange>,
+}
+
+impl ConfigChannelNumbering {
+    pub fn prepare(&mut self) -> Result<(), M3uFilterError> {
+        handle_m3u_filter_error_result_list!(M3uFilterErrorKind::Info, self.ranges.iter_mut().map(ConfigChannelNumberRange::prepare));
+        let sorted_ranges = self.get_sorted_ranges();
+        if let Some(((start, end), (next_start, next_end))) = sorted_ranges.iter().zip(sorted_ranges.iter().skip(1)).find(|((_, end), (next_start, _))| next_start <= end) {
+            return create_m3u_filter_error_result!(M3uFilterErrorKind::Info, "channel number ranges {start}-{end} and {next_start}-{next_end} overlap");
+        }
+        Ok(())
+    }
+
+    fn get_sorted_ranges(&self) -> Vec<(u32, u32)> {
+        let mut ranges: Vec<(u32, u32)> = self.ranges.iter().map(|range| (range.start, range.end.unwrap_or(u32::MAX))).collect();
+        ranges.sort_unstable();
+        ranges
+    }
+
+    /// Returns the number ranges outside the configured ranges, for channels of groups without a range.
+    pub fn get_unranged_segments(&self) -> Vec<(u32, u32)> {
+        let mut segments = vec![];
+        let mut next = Some(1u32);
+        for (start, end) in self.get_sorted_ranges() {
+            if let Some(segment_start) = next.filter(|&segment_start| segment_start < start) {
+                segments.push((segment_start, start - 1));
+            }
+            next = end.checked_add(1);
+        }
+        if let Some(segment_start) = next {
+            segments.push((segment_start, u32::MAX));
+        }
+        segments
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 #[serde(deny_unknown_fields)]
 pub struct ConfigTargetOptions {
@@ -326,6 +588,8 @@ pub struct ConfigTargetOptions {
     pub remove_duplicates: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub force_redirect: Option<ClusterFlags>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel_numbering: Option<ConfigChannelNumbering>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -348,6 +612,14 @@ pub struct XtreamTargetOutput {
 }
 
 
+#[derive(Debug, Copy, Clone, serde::Serialize, serde::Deserialize, Sequence, PartialEq, Eq)]
+pub enum M3uSplitMode {
+    #[serde(rename = "group")]
+    Group,
+    #[serde(rename = "cluster")]
+    Cluster,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct M3uTargetOutput {
@@ -357,6 +629,9 @@ pub struct M3uTargetOutput {
     pub include_type_in_url: bool,
     #[serde(default)]
     pub mask_redirect_url: bool,
+    // additionally writes one playlist for each group or cluster
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split: Option<M3uSplitMode>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -373,6 +648,48 @@ pub struct StrmTargetOutput {
     pub kodi_style: bool,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub strm_props: Option<Vec<String>>,
+    #[serde(default)]
+    pub nfo: bool,
+    #[serde(default)]
+    pub artwork: bool,
+}
+
+/// Output for plain playlist files like `xspf` and `pls`, the filename is relative to the working dir.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlaylistFileTargetOutput {
+    pub filename: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Enigma2TargetOutput {
+    pub directory: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bouquet_prefix: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JsonTargetOutput {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+}
+
+impl JsonTargetOutput {
+    // The urls of the user are xtream urls, they need a xtream output.
+    fn prepare(&mut self, target_name: &str, has_xtream_output: bool) -> Result<(), M3uFilterError> {
+        self.filename = get_trimmed_string(&self.filename);
+        self.username = get_trimmed_string(&self.username);
+        if self.username.is_some() && !has_xtream_output {
+            return Err(info_err!(format!("json output with a username is only permitted when used in combination with xtream output: {target_name}")));
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -391,6 +708,10 @@ pub enum TargetOutput {
     M3u(M3uTargetOutput),
     Strm(StrmTargetOutput),
     HdHomeRun(HdHomeRunTargetOutput),
+    Xspf(PlaylistFileTargetOutput),
+    Pls(PlaylistFileTargetOutput),
+    Enigma2(Enigma2TargetOutput),
+    Json(JsonTargetOutput),
 }
 
 bitflags! {
@@ -536,6 +857,8 @@ pub struct ConfigTarget {
     pub rename: Option<Vec<ConfigRename>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mapping: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_rules: Option<Vec<ConfigGroupRule>>,
     #[serde(default)]
     pub processing_order: ProcessingOrder,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -549,8 +872,7 @@ pub struct ConfigTarget {
 }
 
 impl ConfigTarget {
-    pub fn prepare(&mut self, id: u16, templates: Option<&Vec<PatternTemplate>>) -> Result<(), M3uFilterError> {
-        self.id = id;
+    fn prepare_output(&mut self) -> Result<(), M3uFilterError> {
         if self.output.is_empty() {
             return Err(info_err!(format!("Missing output format for {}", self.name)));
         }
@@ -559,10 +881,17 @@ impl ConfigTarget {
         let mut xtream_cnt = 0;
         let mut strm_needs_xtream = false;
         let mut hdhr_cnt = 0;
+        let mut xspf_cnt = 0;
+        let mut pls_cnt = 0;
+        let mut enigma2_cnt = 0;
+        let mut json_cnt = 0;
+        let has_xtream_output = self.output.iter().any(|target_output| matches!(target_output, TargetOutput::Xtream(_)));
         let mut hdhomerun_needs_m3u = false;
         let mut hdhomerun_needs_xtream = false;
 
         for target_output in &mut self.output {
+            if matches!(target_output, TargetOutput::Xspf(_)) { xspf_cnt += 1; }
+            if matches!(target_output, TargetOutput::Pls(_)) { pls_cnt += 1; }
             match target_output {
                 TargetOutput::Xtream(_) => {
                     xtream_cnt += 1;
@@ -605,13 +934,37 @@ impl ConfigTarget {
                         }
                     }
                 }
+                TargetOutput::Xspf(file_output) | TargetOutput::Pls(file_output) => {
+                    file_output.filename = file_output.filename.trim().to_string();
+                    if file_output.filename.is_empty() {
+                        return create_m3u_filter_error_result!(M3uFilterErrorKind::Info, "filename is required for xspf and pls type: {}", self.name);
+                    }
+                }
+                TargetOutput::Enigma2(enigma2_output) => {
+                    enigma2_cnt += 1;
+                    enigma2_output.directory = enigma2_output.directory.trim().to_string();
+                    if enigma2_output.directory.is_empty() {
+                        return create_m3u_filter_error_result!(M3uFilterErrorKind::Info, "directory is required for enigma2 type: {}", self.name);
+                    }
+                    enigma2_output.username = get_trimmed_string(&enigma2_output.username);
+                    enigma2_output.bouquet_prefix = get_trimmed_string(&enigma2_output.bouquet_prefix);
+                }
+                TargetOutput::Json(json_output) => {
+                    json_cnt += 1;
+                    json_output.prepare(&self.name, has_xtream_output)?;
+                }
             }
         }
 
-        if m3u_cnt > 1 || strm_cnt > 1 || xtream_cnt > 1 || hdhr_cnt > 1 {
+        if m3u_cnt > 1 || strm_cnt > 1 || xtream_cnt > 1 || hdhr_cnt > 1 || xspf_cnt > 1 || pls_cnt > 1 || enigma2_cnt > 1 || json_cnt > 1 {
             return create_m3u_filter_error_result!(M3uFilterErrorKind::Info, "Multiple output formats with same type : {}", self.name);
         }
 
+        // the bouquets are created from the m3u playlist and use its stream urls
+        if enigma2_cnt > 0 && m3u_cnt == 0 {
+            return create_m3u_filter_error_result!(M3uFilterErrorKind::Info, "enigma2 output is only permitted when used in combination with m3u output: {}", self.name);
+        }
+
         if strm_cnt > 0 && strm_needs_xtream && xtream_cnt == 0 {
             return create_m3u_filter_error_result!(M3uFilterErrorKind::Info, "strm output with a username is only permitted when used in combination with xtream output: {}", self.name);
         }
@@ -627,6 +980,12 @@ impl ConfigTarget {
                 return create_m3u_filter_error_result!(M3uFilterErrorKind::Info, "HdHomeRun output has `use_output=xtream` but no `xtream` output defined: {}", self.name);
             }
         }
+        Ok(())
+    }
+
+    pub fn prepare(&mut self, id: u16, templates: Option<&Vec<PatternTemplate>>) -> Result<(), M3uFilterError> {
+        self.id = id;
+        self.prepare_output()?;
 
         if let Some(watch) = &self.watch {
             let regexps: Result<Vec<regex::Regex>, _> = watch.iter().map(|s| regex::Regex::new(s)).collect();
@@ -648,6 +1007,12 @@ impl ConfigTarget {
                 if let Some(sort) = self.sort.as_mut() {
                     handle_m3u_filter_error_result!(M3uFilterErrorKind::Info, sort.prepare());
                 }
+                if let Some(group_rules) = self.group_rules.as_mut() {
+                    handle_m3u_filter_error_result_list!(M3uFilterErrorKind::Info, group_rules.iter_mut().map(ConfigGroupRule::prepare));
+                }
+                if let Some(channel_numbering) = self.options.as_mut().and_then(|o| o.channel_numbering.as_mut()) {
+                    handle_m3u_filter_error_result!(M3uFilterErrorKind::Info, channel_numbering.prepare());
+                }
                 Ok(())
             }
             Err(err) => Err(err),
@@ -662,6 +1027,14 @@ impl ConfigTarget {
         true
     }
 
+    pub(crate) fn get_group_parent(&self, group_title: &str) -> Option<&str> {
+        self.group_rules.as_ref()?.iter().find_map(|rule| match rule {
+            ConfigGroupRule::Parent(parent) if parent.parent != group_title
+                && parent.t_re.iter().any(|re| re.is_match(group_title)) => Some(parent.parent.as_str()),
+            _ => None,
+        })
+    }
+
     pub(crate) fn get_xtream_output(&self) -> Option<&XtreamTargetOutput> {
         if let Some(TargetOutput::Xtream(output)) = self.output.iter().find(|o| matches!(o, TargetOutput::Xtream(_))) {
             Some(output)
@@ -686,6 +1059,22 @@ impl ConfigTarget {
     //     }
     // }
 
+    pub(crate) fn get_enigma2_output(&self) -> Option<&Enigma2TargetOutput> {
+        if let Some(TargetOutput::Enigma2(output)) = self.output.iter().find(|o| matches!(o, TargetOutput::Enigma2(_))) {
+            Some(output)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn get_json_output(&self) -> Option<&JsonTargetOutput> {
+        if let Some(TargetOutput::Json(output)) = self.output.iter().find(|o| matches!(o, TargetOutput::Json(_))) {
+            Some(output)
+        } else {
+            None
+        }
+    }
+
     pub(crate) fn get_hdhomerun_output(&self) -> Option<&HdHomeRunTargetOutput> {
         if let Some(TargetOutput::HdHomeRun(output)) = self.output.iter().find(|o| matches!(o, TargetOutput::HdHomeRun(_))) {
             Some(output)
@@ -701,6 +1090,10 @@ impl ConfigTarget {
                 TargetOutput::M3u(_) => { if tt == &TargetType::M3u { return true; } }
                 TargetOutput::Strm(_) => { if tt == &TargetType::Strm { return true; } }
                 TargetOutput::HdHomeRun(_) => { if tt == &TargetType::HdHomeRun { return true; } }
+                TargetOutput::Xspf(_) => { if tt == &TargetType::Xspf { return true; } }
+                TargetOutput::Pls(_) => { if tt == &TargetType::Pls { return true; } }
+                TargetOutput::Enigma2(_) => { if tt == &TargetType::Enigma2 { return true; } }
+                TargetOutput::Json(_) => { if tt == &TargetType::Json { return true; } }
             }
         }
         false
@@ -723,8 +1116,8 @@ pub struct ConfigSource {
 
 impl ConfigSource {
     #[allow(clippy::cast_possible_truncation)]
-    pub fn prepare(&mut self, index: u16, include_computed: bool) -> Result<u16, M3uFilterError> {
-        handle_m3u_filter_error_result_list!(M3uFilterErrorKind::Info, self.inputs.iter_mut().enumerate().map(|(idx, i)| i.prepare(index+(idx as u16), include_computed)));
+    pub fn prepare(&mut self, index: u16, include_computed: bool, templates: Option<&Vec<PatternTemplate>>) -> Result<u16, M3uFilterError> {
+        handle_m3u_filter_error_result_list!(M3uFilterErrorKind::Info, self.inputs.iter_mut().enumerate().map(|(idx, i)| i.prepare(index+(idx as u16), include_computed, templates)));
         Ok(index + (self.inputs.len() as u16))
     }
 
@@ -769,6 +1162,16 @@ pub enum InputType {
     M3uBatch,
     #[serde(rename = "xtream_batch")]
     XtreamBatch,
+    #[serde(rename = "local")]
+    Local,
+    #[serde(rename = "stalker")]
+    Stalker,
+    #[serde(rename = "hdhomerun")]
+    HdHomeRun,
+    #[serde(rename = "json")]
+    Json,
+    #[serde(rename = "csv")]
+    Csv,
 }
 
 impl InputType {
@@ -776,6 +1179,11 @@ impl InputType {
     const XTREAM: &'static str = "xtream";
     const M3U_BATCH: &'static str = "m3u_batch";
     const XTREAM_BATCH: &'static str = "xtream_batch";
+    const LOCAL: &'static str = "local";
+    const STALKER: &'static str = "stalker";
+    const HDHOMERUN: &'static str = "hdhomerun";
+    const JSON: &'static str = "json";
+    const CSV: &'static str = "csv";
 }
 
 impl Display for InputType {
@@ -785,6 +1193,11 @@ impl Display for InputType {
             Self::Xtream => Self::XTREAM,
             Self::M3uBatch => Self::M3U_BATCH,
             Self::XtreamBatch => Self::XTREAM_BATCH,
+            Self::Local => Self::LOCAL,
+            Self::Stalker => Self::STALKER,
+            Self::HdHomeRun => Self::HDHOMERUN,
+            Self::Json => Self::JSON,
+            Self::Csv => Self::CSV,
         })
     }
 }
@@ -797,6 +1210,16 @@ impl FromStr for InputType {
             Ok(Self::M3u)
         } else if s.eq(Self::XTREAM) {
             Ok(Self::Xtream)
+        } else if s.eq(Self::LOCAL) {
+            Ok(Self::Local)
+        } else if s.eq(Self::STALKER) {
+            Ok(Self::Stalker)
+        } else if s.eq(Self::HDHOMERUN) {
+            Ok(Self::HdHomeRun)
+        } else if s.eq(Self::JSON) {
+            Ok(Self::Json)
+        } else if s.eq(Self::CSV) {
+            Ok(Self::Csv)
         } else {
             create_m3u_filter_error_result!(M3uFilterErrorKind::Info, "Unknown InputType: {}", s)
         }
@@ -863,6 +1286,115 @@ pub struct ConfigInputOptions {
     pub xtream_live_stream_without_extension: bool,
 }
 
+/// Retries failed input downloads, the delay is doubled after each attempt up to `max_delay_secs`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigInputRetry {
+    #[serde(default)]
+    pub attempts: u8,
+    #[serde(default = "default_retry_delay_secs")]
+    pub delay_secs: u16,
+    #[serde(default = "default_retry_max_delay_secs")]
+    pub max_delay_secs: u16,
+}
+
+impl ConfigInputRetry {
+    pub fn get_delay_secs(&self, attempt: u8) -> u64 {
+        let delay = u64::from(self.delay_secs).saturating_mul(1u64 << u32::from(attempt.min(16)));
+        delay.min(u64::from(self.max_delay_secs.max(self.delay_secs)))
+    }
+}
+
+const fn default_auth_method() -> InputFetchMethod { InputFetchMethod::POST }
+
+/// Login request of an input, the issued token is extracted from the json response (`token_path`)
+/// or from the `Set-Cookie` header (`cookie`) and sent with the playlist, epg and stream requests.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigInputAuth {
+    pub url: String,
+    #[serde(default = "default_auth_method")]
+    pub method: InputFetchMethod,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub form: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cookie: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_path: Option<String>,
+    #[serde(default)]
+    pub lifetime_secs: u64,
+    #[serde(default = "default_auth_header")]
+    pub header: String,
+    #[serde(default = "default_auth_prefix")]
+    pub prefix: String,
+    #[serde(skip)]
+    pub t_token: Arc<tokio::sync::Mutex<Option<InputAuthToken>>>,
+}
+
+impl ConfigInputAuth {
+    fn prepare(&mut self) -> Result<(), M3uFilterError> {
+        self.url = self.url.trim().to_string();
+        if Url::parse(&self.url).is_err() {
+            return Err(info_err!(format!("auth url for input is invalid: {}", sanitize_sensitive_info(&self.url))));
+        }
+        self.token_path = get_trimmed_string(&self.token_path);
+        self.cookie = get_trimmed_string(&self.cookie);
+        self.expires_path = get_trimmed_string(&self.expires_path);
+        if self.token_path.is_some() == self.cookie.is_some() {
+            return Err(info_err!("auth for input needs either token_path or cookie".to_string()));
+        }
+        if self.json.is_some() && !self.form.is_empty() {
+            return Err(info_err!("auth for input can't have form and json body".to_string()));
+        }
+        self.header = self.header.trim().to_string();
+        if self.token_path.is_some() && reqwest::header::HeaderName::from_bytes(self.header.as_bytes()).is_err() {
+            return Err(info_err!(format!("auth header for input is invalid: {}", self.header)));
+        }
+        Ok(())
+    }
+}
+
+pub const INPUT_MAPPING_FIELDS: &[&str] = &["id", "name", "title", "group", "logo", "logo_small", "chno", "parent_code",
+    "audio_track", "time_shift", "rec", "catchup", "catchup_days", "catchup_source", "url", "epg_channel_id", "type"];
+
+/// Maps the columns of a `csv` input or the (dot separated) paths of a `json` input to the playlist item fields.
+/// Unmapped fields are read from the column or path with the field name.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigInputMapping {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub separator: Option<char>,
+    #[serde(default)]
+    pub fields: HashMap<String, String>,
+}
+
+impl ConfigInputMapping {
+    pub fn prepare(&mut self) -> Result<(), M3uFilterError> {
+        self.root = get_trimmed_string(&self.root);
+        for (field, source) in &self.fields {
+            if !INPUT_MAPPING_FIELDS.contains(&field.as_str()) {
+                return Err(info_err!(format!("Unsupported input mapping field {field}, valid fields are {}", INPUT_MAPPING_FIELDS.join(", "))));
+            }
+            if source.trim().is_empty() {
+                return Err(info_err!(format!("Input mapping for field {field} is empty")));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_source<'a>(&'a self, field: &'a str) -> &'a str {
+        self.fields.get(field).map_or(field, String::as_str)
+    }
+}
+
 pub struct InputUserInfo {
     pub base_url: String,
     pub username: String,
@@ -914,10 +1446,29 @@ macro_rules! check_input_credentials {
                     return Err(info_err!("for input type xtream: username and password are mandatory".to_string()));
                 }
             }
+            InputType::Local | InputType::HdHomeRun | InputType::Json | InputType::Csv => {
+                if $this.username.is_some() || $this.password.is_some() {
+                    debug!("for input type {}: username and password are ignored", $input_type);
+                }
+            }
+            InputType::Stalker => {
+                if $this.username.is_none() {
+                    return Err(info_err!("for input type stalker: username with the mac address is mandatory".to_string()));
+                }
+            }
         }
     };
 }
 
+fn check_proxy_url(proxy: Option<&str>) -> Result<(), M3uFilterError> {
+    if let Some(proxy_url) = proxy {
+        if let Err(err) = reqwest::Proxy::all(proxy_url) {
+            return Err(info_err!(format!("invalid proxy url {}: {err}", sanitize_sensitive_info(proxy_url))));
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 #[serde(deny_unknown_fields)]
 pub struct ConfigInputAlias {
@@ -932,8 +1483,14 @@ pub struct ConfigInputAlias {
     pub priority: i16,
     #[serde(default)]
     pub max_connections: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
     #[serde(skip)]
     pub t_base_url: String,
+    #[serde(skip)]
+    pub t_client: Option<Arc<reqwest::Client>>,
+    #[serde(skip)]
+    pub t_portal_token: Arc<tokio::sync::Mutex<Option<String>>>,
 }
 
 
@@ -954,6 +1511,8 @@ impl ConfigInputAlias {
         self.username = get_trimmed_string(&self.username);
         self.password = get_trimmed_string(&self.password);
         check_input_credentials!(self, input_type);
+        self.proxy = get_trimmed_string(&self.proxy);
+        check_proxy_url(self.proxy.as_deref())?;
 
         Ok(())
     }
@@ -1173,14 +1732,47 @@ pub struct ConfigInput {
     #[serde(default)]
     pub max_connections: u16,
     #[serde(default)]
+    pub auto_max_connections: bool,
+    #[serde(default)]
     pub method: InputFetchMethod,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mapping: Option<ConfigInputMapping>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<ConfigInputRetry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<ConfigInputAuth>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
     #[serde(skip)]
     pub t_base_url: String,
+    #[serde(skip)]
+    pub t_filter: Option<Filter>,
+    #[serde(skip)]
+    pub t_client: Option<Arc<reqwest::Client>>,
+    #[serde(skip)]
+    pub t_portal_token: Arc<tokio::sync::Mutex<Option<String>>>,
+}
+
+// base url, username, password and proxy client of the input or an alias
+type InputAccountClient<'a> = (&'a str, Option<&'a str>, Option<&'a str>, Option<&'a Arc<reqwest::Client>>);
+
+fn url_has_same_host(url: &Url, base_url: &str) -> bool {
+    Url::parse(base_url).is_ok_and(|base_url| base_url.host_str() == url.host_str() && base_url.port_or_known_default() == url.port_or_known_default())
+}
+
+// xtream stream urls have the credentials as path segments, m3u urls as query parameters
+fn url_has_credentials(url: &Url, username: Option<&str>, password: Option<&str>) -> bool {
+    let (Some(username), Some(password)) = (username, password) else { return false; };
+    let has_query_param = |name: &str, value: &str| url.query_pairs().any(|(key, param)| key == name && param == value);
+    url.path_segments().is_some_and(|segments| segments.clone().zip(segments.skip(1)).any(|(user, pass)| user == username && pass == password))
+        || (has_query_param("username", username) && has_query_param("password", password))
 }
 
 impl ConfigInput {
     #[allow(clippy::cast_possible_truncation)]
-    pub fn prepare(&mut self, index: u16, include_computed: bool) -> Result<u16, M3uFilterError> {
+    pub fn prepare(&mut self, index: u16, include_computed: bool, templates: Option<&Vec<PatternTemplate>>) -> Result<u16, M3uFilterError> {
         self.id = index;
         self.check_url()?;
         self.prepare_batch()?;
@@ -1194,6 +1786,18 @@ impl ConfigInput {
         self.password = get_trimmed_string(&self.password);
         check_input_credentials!(self, self.input_type);
         self.persist = get_trimmed_string(&self.persist);
+        self.proxy = get_trimmed_string(&self.proxy);
+        check_proxy_url(self.proxy.as_deref())?;
+        if let Some(auth) = self.auth.as_mut() {
+            auth.prepare()?;
+        }
+        self.filter = get_trimmed_string(&self.filter);
+        if let Some(filter) = self.filter.as_ref() {
+            self.t_filter = Some(get_filter(filter, templates)?);
+        }
+        if let Some(mapping) = self.mapping.as_mut() {
+            mapping.prepare()?;
+        }
         if let Some(base_url) = get_base_url_from_str(&self.url) {
             self.t_base_url = base_url;
         }
@@ -1251,6 +1855,9 @@ impl ConfigInput {
                         if let Some(mut first) = batch_aliases.pop() {
                             self.username = first.username.take();
                             self.password = first.password.take();
+                            if first.proxy.is_some() {
+                                self.proxy = first.proxy.take();
+                            }
                             self.url = first.url.trim().to_string();
                             if self.name.is_empty() {
                                 self.name = first.name.to_string();
@@ -1275,6 +1882,53 @@ impl ConfigInput {
         Ok(())
     }
 
+    /// Returns true if the item passes the input `filter` or no filter is defined.
+    pub fn filter(&self, provider: &ValueProvider) -> bool {
+        let mut processor = MockValueProcessor {};
+        self.t_filter.as_ref().is_none_or(|filter| filter.filter(provider, &mut processor))
+    }
+
+    /// Creates a copy of this input which fetches the playlist from the alias url with the alias credentials.
+    pub fn create_alias_input(&self, alias: &ConfigInputAlias) -> Self {
+        Self {
+            url: alias.url.clone(),
+            username: alias.username.clone(),
+            password: alias.password.clone(),
+            t_base_url: alias.t_base_url.clone(),
+            t_client: alias.t_client.clone(),
+            t_portal_token: Arc::clone(&alias.t_portal_token),
+            aliases: None,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the proxy client of the input or alias the url belongs to, otherwise the shared client.
+    /// Aliases can share the host of the input, an url with the credentials of an account takes precedence.
+    pub fn get_http_client(&self, client: &Arc<reqwest::Client>, url: &str) -> Arc<reqwest::Client> {
+        let Ok(url) = Url::parse(url) else {
+            return Arc::clone(self.t_client.as_ref().unwrap_or(client));
+        };
+        let accounts = || std::iter::once((self.t_base_url.as_str(), self.username.as_deref(), self.password.as_deref(), self.t_client.as_ref()))
+            .chain(self.aliases.iter().flatten().map(|alias| (alias.t_base_url.as_str(), alias.username.as_deref(), alias.password.as_deref(), alias.t_client.as_ref())))
+            .filter(|(base_url, _, _, _): &InputAccountClient| url_has_same_host(&url, base_url));
+        let matched_client = accounts()
+            .find(|(_, username, password, _)| url_has_credentials(&url, *username, *password))
+            .or_else(|| accounts().next())
+            .map_or(self.t_client.as_ref(), |(_, _, _, account_client)| account_client);
+        Arc::clone(matched_client.unwrap_or(client))
+    }
+
+    /// Returns the proxy client of the input or the alias with the name, the provider of a stream is selected by name.
+    pub fn get_http_client_by_name(&self, client: &Arc<reqwest::Client>, name: Option<&str>) -> Arc<reqwest::Client> {
+        let account_client = match name {
+            Some(name) if name != self.name => self.aliases.iter().flatten()
+                .find(|alias| alias.name == name)
+                .map_or(self.t_client.as_ref(), |alias| alias.t_client.as_ref()),
+            _ => self.t_client.as_ref(),
+        };
+        Arc::clone(account_client.unwrap_or(client))
+    }
+
     pub fn get_user_info(&self) -> Option<InputUserInfo> {
         InputUserInfo::new(self.input_type, self.username.as_deref(), self.password.as_deref(), &self.url)
     }
@@ -1347,6 +2001,28 @@ pub struct MessagingConfig {
 
 }
 
+/// Polls the `user_info` of the xtream provider accounts of all inputs and aliases.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct AccountMonitorConfig {
+    #[serde(default = "default_as_true")]
+    pub enabled: bool,
+    #[serde(default = "default_account_monitor_interval_mins")]
+    pub interval_mins: u32,
+    #[serde(default = "default_account_expiry_warning_days")]
+    pub expiry_warning_days: u16,
+    #[serde(default)]
+    pub disable_expired: bool,
+}
+
+impl AccountMonitorConfig {
+    pub fn prepare(&mut self) {
+        if self.interval_mins == 0 {
+            self.interval_mins = default_account_monitor_interval_mins();
+        }
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 #[serde(deny_unknown_fields)]
 pub struct LogConfig {
@@ -1783,6 +2459,8 @@ pub struct Config {
     pub reverse_proxy: Option<ReverseProxyConfig>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub hdhomerun: Option<HdHomeRunConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_monitor: Option<AccountMonitorConfig>,
     #[serde(skip)]
     pub t_api_proxy: Arc<RwLock<Option<ApiProxyConfig>>>,
     #[serde(skip)]
@@ -1833,10 +2511,16 @@ impl Config {
             for target in &source.targets {
                 for output in &target.output {
                     match output {
-                        TargetOutput::Xtream(_) | TargetOutput::M3u(_) => {}
+                        TargetOutput::Xtream(_) | TargetOutput::M3u(_) | TargetOutput::Xspf(_) | TargetOutput::Pls(_) => {}
                         TargetOutput::Strm(strm_output) => {
                             self.check_username(strm_output.username.as_deref(), &target.name).await?;
                         }
+                        TargetOutput::Enigma2(enigma2_output) => {
+                            self.check_username(enigma2_output.username.as_deref(), &target.name).await?;
+                        }
+                        TargetOutput::Json(json_output) => {
+                            self.check_username(json_output.username.as_deref(), &target.name).await?;
+                        }
                         TargetOutput::HdHomeRun(hdhomerun_output) => {
                             if check_homerun {
                                 let hdhr_name = &hdhomerun_output.device;
@@ -2057,10 +2741,14 @@ impl Config {
             reverse_proxy.prepare(&self.working_dir)?;
         }
         self.prepare_hdhomerun()?;
+        if let Some(account_monitor) = self.account_monitor.as_mut() {
+            account_monitor.prepare();
+        }
         self.api.prepare();
         self.prepare_api_web_root();
         self.prepare_templates()?;
         self.prepare_sources(include_computed)?;
+        self.prepare_proxy_clients()?;
         let target_names = self.check_unique_target_names()?;
         self.check_scheduled_targets(&target_names)?;
         self.check_unique_input_names()?;
@@ -2096,7 +2784,7 @@ impl Config {
         let mut source_index: u16 = 1;
         let mut target_index: u16 = 1;
         for source in &mut self.sources {
-            source_index = source.prepare(source_index, include_computed)?;
+            source_index = source.prepare(source_index, include_computed, self.templates.as_ref())?;
             for target in &mut source.targets {
                 // prepare target templates
                 let prepare_result = match &self.templates {
@@ -2110,6 +2798,26 @@ impl Config {
         Ok(())
     }
 
+    // Inputs with a proxy get their own http client, aliases without a proxy use the client of their input
+    fn prepare_proxy_clients(&mut self) -> Result<(), M3uFilterError> {
+        let connect_timeout_secs = self.connect_timeout_secs;
+        let create_client = |proxy: Option<&String>| -> Result<Option<Arc<reqwest::Client>>, M3uFilterError> {
+            proxy.map(|proxy_url| create_http_client(connect_timeout_secs, Some(proxy_url)).map(Arc::new)
+                .map_err(|err| info_err!(format!("failed to create http client for proxy {}: {err}", sanitize_sensitive_info(proxy_url)))))
+                .transpose()
+        };
+        for input in self.sources.iter_mut().flat_map(|source| source.inputs.iter_mut()) {
+            input.t_client = create_client(input.proxy.as_ref())?;
+            for alias in input.aliases.iter_mut().flatten() {
+                alias.t_client = match create_client(alias.proxy.as_ref())? {
+                    Some(alias_client) => Some(alias_client),
+                    None => input.t_client.clone(),
+                };
+            }
+        }
+        Ok(())
+    }
+
     fn prepare_templates(&mut self) -> Result<(), M3uFilterError> {
         if let Some(templates) = &mut self.templates {
             match prepare_templates(templates) {
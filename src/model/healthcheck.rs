@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use crate::model::provider_account::ProviderAccountStatus;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Healthcheck {
@@ -24,4 +25,8 @@ pub struct StatusCheck {
     pub active_user_connections: usize,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub active_provider_connections: Option<BTreeMap<String, u16>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_update: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_accounts: Option<Vec<ProviderAccountStatus>>,
 }
\ No newline at end of file
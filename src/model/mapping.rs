@@ -13,7 +13,7 @@ use crate::m3u_filter_error::{create_m3u_filter_error_result, handle_m3u_filter_
 use crate::m3u_filter_error::{M3uFilterError, M3uFilterErrorKind};
 use crate::model::config::valid_property;
 use crate::model::config::{ItemField, AFFIX_FIELDS, COUNTER_FIELDS, MAPPER_ATTRIBUTE_FIELDS};
-use crate::model::playlist::{FieldGetAccessor, FieldSetAccessor, PlaylistItem};
+use crate::model::playlist::{is_item_option_field, FieldGetAccessor, FieldSetAccessor, PlaylistItem};
 use crate::utils::constants::CONSTANTS;
 use crate::utils::string_utils::Capitalize;
 
@@ -206,7 +206,7 @@ impl Mapper {
     /// Will panic if default `RegEx` gets invalid
     pub fn prepare(&mut self, templates: Option<&Vec<PatternTemplate>>, tags: Option<&Vec<MappingTag>>) -> Result<(), M3uFilterError> {
         for key in self.attributes.keys() {
-            if !valid_property!(key.as_str(), MAPPER_ATTRIBUTE_FIELDS) {
+            if !valid_property!(key.as_str(), MAPPER_ATTRIBUTE_FIELDS) && !is_item_option_field(key) {
                 return Err(info_err!(format!("Invalid mapper attribute field {key}")));
             }
         }
@@ -221,10 +221,10 @@ impl Mapper {
             }
         }
         for (key, value) in &self.assignments {
-            if !valid_property!(key.as_str(), MAPPER_ATTRIBUTE_FIELDS) {
+            if !valid_property!(key.as_str(), MAPPER_ATTRIBUTE_FIELDS) && !is_item_option_field(key) {
                 return Err(info_err!(format!("Invalid mapper assignment field {key}")));
             }
-            if !valid_property!(value.as_str(), MAPPER_ATTRIBUTE_FIELDS) {
+            if !valid_property!(value.as_str(), MAPPER_ATTRIBUTE_FIELDS) && !is_item_option_field(value) {
                 return Err(info_err!(format!("Invalid mapper assignment field {value}")));
             }
         }
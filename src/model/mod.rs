@@ -9,4 +9,5 @@ pub mod healthcheck;
 pub mod playlist_categories;
 pub mod hdhomerun_config;
 pub mod xtream_const;
+pub mod provider_account;
 mod serde_utils;
\ No newline at end of file
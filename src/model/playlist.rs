@@ -1,4 +1,5 @@
 use std::cmp::PartialEq;
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 use crate::model::api_proxy::ProxyUserCredentials;
@@ -7,10 +8,12 @@ use crate::model::xmltv::TVGuide;
 use crate::model::xtream_const;
 use crate::model::xtream::{xtream_playlistitem_to_document, XtreamMappingOptions};
 use crate::utils::json_utils::{get_string_from_serde_value, get_u64_from_serde_value};
+use reqwest::header::{HeaderValue, REFERER, USER_AGENT};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use crate::utils::hash_utils::{generate_playlist_uuid, get_provider_id};
 use crate::utils::network::request::extract_extension_from_url;
+use crate::utils::catchup_utils::get_catchup_template;
 // https://de.wikipedia.org/wiki/M3U
 // https://siptv.eu/howto/playlist.html
 
@@ -158,7 +161,7 @@ pub trait FieldSetAccessor {
 
 pub type UUIDType = [u8; 32];
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct PlaylistItemHeader {
     pub uuid: UUIDType, // calculated
     pub id: String, // provider id
@@ -173,6 +176,8 @@ pub struct PlaylistItemHeader {
     pub audio_track: String,
     pub time_shift: String,
     pub rec: String,
+    pub vlc_opts: Vec<String>,
+    pub kodi_props: Vec<String>,
     pub url: String,
     pub epg_channel_id: Option<String>,
     pub xtream_cluster: XtreamCluster,
@@ -182,6 +187,9 @@ pub struct PlaylistItemHeader {
     #[serde(default)]
     pub category_id: u32,
     pub input_name: String,
+    pub catchup: String,
+    pub catchup_days: String,
+    pub catchup_source: String,
 }
 
 impl PlaylistItemHeader {
@@ -229,6 +237,75 @@ impl PlaylistItemHeader {
             None => None
         }
     }
+
+    fn get_item_option(&self, field: &str) -> Option<String> {
+        if let Some(name) = field.strip_prefix(VLC_OPT_FIELD_PREFIX) {
+            get_item_option(&self.vlc_opts, name)
+        } else if let Some(name) = field.strip_prefix(KODI_PROP_FIELD_PREFIX) {
+            get_item_option(&self.kodi_props, name)
+        } else {
+            None
+        }
+    }
+
+    fn set_item_option(&mut self, field: &str, value: &str) -> bool {
+        if let Some(name) = field.strip_prefix(VLC_OPT_FIELD_PREFIX) {
+            set_item_option(&mut self.vlc_opts, name, value);
+        } else if let Some(name) = field.strip_prefix(KODI_PROP_FIELD_PREFIX) {
+            set_item_option(&mut self.kodi_props, name, value);
+        } else {
+            return false;
+        }
+        true
+    }
+}
+
+/// Prefix of the fields for the `#EXTVLCOPT` options of an item, like `vlc_opt.http-user-agent`.
+pub const VLC_OPT_FIELD_PREFIX: &str = "vlc_opt.";
+/// Prefix of the fields for the `#KODIPROP` properties of an item, like `kodi_prop.inputstream.adaptive.license_type`.
+pub const KODI_PROP_FIELD_PREFIX: &str = "kodi_prop.";
+
+const VLC_OPT_USER_AGENT: &str = "http-user-agent";
+const VLC_OPT_REFERRER: &str = "http-referrer";
+const VLC_OPT_REFERER: &str = "http-referer";
+
+pub fn is_item_option_field(field: &str) -> bool {
+    field.len() > VLC_OPT_FIELD_PREFIX.len() && field.starts_with(VLC_OPT_FIELD_PREFIX)
+        || field.len() > KODI_PROP_FIELD_PREFIX.len() && field.starts_with(KODI_PROP_FIELD_PREFIX)
+}
+
+// options are stored like they are written in the playlist `name=value`
+fn get_item_option(options: &[String], name: &str) -> Option<String> {
+    options.iter().find_map(|option| match option.split_once('=') {
+        Some((key, value)) if key.trim() == name => Some(value.trim().to_string()),
+        None if option.trim() == name => Some(String::new()),
+        _ => None,
+    })
+}
+
+// the user agent and referrer of the `#EXTVLCOPT` options are sent to the provider when the stream is proxied
+fn get_stream_headers(vlc_opts: &[String]) -> HashMap<String, String> {
+    let mut headers = HashMap::new();
+    let is_valid = |value: &String| !value.is_empty() && HeaderValue::from_str(value).is_ok();
+    if let Some(user_agent) = get_item_option(vlc_opts, VLC_OPT_USER_AGENT).filter(is_valid) {
+        headers.insert(USER_AGENT.to_string(), user_agent);
+    }
+    if let Some(referer) = get_item_option(vlc_opts, VLC_OPT_REFERRER)
+        .or_else(|| get_item_option(vlc_opts, VLC_OPT_REFERER)).filter(is_valid) {
+        headers.insert(REFERER.to_string(), referer);
+    }
+    headers
+}
+
+// an empty value removes the option
+fn set_item_option(options: &mut Vec<String>, name: &str, value: &str) {
+    let index = options.iter().position(|option| option.split_once('=').map_or(option.as_str(), |(key, _)| key).trim() == name);
+    match (index, value.is_empty()) {
+        (Some(idx), true) => { options.remove(idx); }
+        (Some(idx), false) => options[idx] = format!("{name}={value}"),
+        (None, false) => options.push(format!("{name}={value}")),
+        (None, true) => {}
+    }
 }
 
 macro_rules! to_m3u_non_empty_fields {
@@ -261,7 +338,7 @@ macro_rules! generate_field_accessor_impl_for_playlist_item_header {
                     )*
                     "caption" =>  Some(if self.title.is_empty() { self.name.clone() } else { self.title.clone() }),
                     "epg_channel_id" | "epg_id" => self.epg_channel_id.clone(),
-                    _ => None,
+                    _ => self.get_item_option(field),
                 }
             }
          }
@@ -284,14 +361,14 @@ macro_rules! generate_field_accessor_impl_for_playlist_item_header {
                         self.epg_channel_id = Some(value.to_owned());
                         true
                     }
-                    _ => false,
+                    _ => self.set_item_option(field, value),
                 }
             }
         }
     }
 }
 
-generate_field_accessor_impl_for_playlist_item_header!(id, /*virtual_id,*/ name, chno, logo, logo_small, group, title, parent_code, audio_track, time_shift, rec, url;);
+generate_field_accessor_impl_for_playlist_item_header!(id, /*virtual_id,*/ name, chno, logo, logo_small, group, title, parent_code, audio_track, time_shift, rec, catchup, catchup_days, catchup_source, url;);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct M3uPlaylistItem {
@@ -307,14 +384,21 @@ pub struct M3uPlaylistItem {
     pub audio_track: String,
     pub time_shift: String,
     pub rec: String,
+    pub vlc_opts: Vec<String>,
+    pub kodi_props: Vec<String>,
     pub url: String,
     pub epg_channel_id: Option<String>,
     pub input_name: String,
     pub item_type: PlaylistItemType,
+    pub catchup: String,
+    pub catchup_days: String,
+    pub catchup_source: String,
     #[serde(skip)]
     pub t_stream_url: String,
     #[serde(skip)]
     pub t_resource_url: Option<String>,
+    #[serde(skip)]
+    pub t_catchup_url: Option<String>,
 }
 
 impl M3uPlaylistItem {
@@ -339,10 +423,46 @@ impl M3uPlaylistItem {
             (parent_code, "parent-code"),
             (audio_track, "audio-track"),
             (time_shift, "timeshift"),
-            (rec, "tvg-rec"););
+            (rec, "tvg-rec"),
+            (catchup_days, "catchup-days"););
+
+        if let Some(catchup_url) = self.t_catchup_url.as_ref() {
+            // the proxied catchup url is absolute, the player has not to derive it from the stream url
+            line = format!("{line} catchup=\"default\" catchup-source=\"{catchup_url}\"");
+        } else {
+            to_m3u_non_empty_fields!(self, line,
+                (catchup, "catchup"),
+                (catchup_source, "catchup-source"););
+        }
+
+        line.push(',');
+        line.push_str(&self.title);
+        for prop in &self.kodi_props {
+            line.push_str("\n#KODIPROP:");
+            line.push_str(prop);
+        }
+        for opt in &self.vlc_opts {
+            line.push_str("\n#EXTVLCOPT:");
+            line.push_str(opt);
+        }
 
         let url = if self.t_stream_url.is_empty() { &self.url } else { &self.t_stream_url };
-        format!("{line},{}\n{url}", self.title, )
+        line.push('\n');
+        line.push_str(url);
+        line
+    }
+
+    /// Returns the request headers from the `#EXTVLCOPT` options, used when the stream is proxied.
+    pub fn get_stream_headers(&self) -> HashMap<String, String> {
+        get_stream_headers(&self.vlc_opts)
+    }
+
+    /// Returns the provider catchup url template, `None` if the stream has no catchup.
+    pub fn get_catchup_template(&self) -> Option<String> {
+        if self.catchup.is_empty() && self.catchup_source.is_empty() {
+            return None;
+        }
+        get_catchup_template(&self.catchup, &self.catchup_source, &self.url)
     }
 }
 
@@ -412,6 +532,7 @@ pub struct XtreamPlaylistItem {
     pub category_id: u32,
     pub input_name: String,
     pub channel_no: u32,
+    pub vlc_opts: Vec<String>,
 }
 
 impl XtreamPlaylistItem {
@@ -427,6 +548,11 @@ impl XtreamPlaylistItem {
         }
         None
     }
+
+    /// Returns the request headers from the `#EXTVLCOPT` options, used when the stream is proxied.
+    pub fn get_stream_headers(&self) -> HashMap<String, String> {
+        get_stream_headers(&self.vlc_opts)
+    }
 }
 
 impl PlaylistEntry for XtreamPlaylistItem {
@@ -538,12 +664,18 @@ impl PlaylistItem {
             audio_track: header.audio_track.to_string(),
             time_shift: header.time_shift.to_string(),
             rec: header.rec.to_string(),
+            catchup: header.catchup.clone(),
+            catchup_days: header.catchup_days.clone(),
+            catchup_source: header.catchup_source.clone(),
+            vlc_opts: header.vlc_opts.clone(),
+            kodi_props: header.kodi_props.clone(),
             url: header.url.to_string(),
             epg_channel_id: header.epg_channel_id.clone(),
             input_name: header.input_name.to_string(),
             item_type: header.item_type,
             t_stream_url: header.url.to_string(),
             t_resource_url: None,
+            t_catchup_url: None,
         }
     }
 
@@ -600,7 +732,8 @@ impl PlaylistItem {
             item_type: header.item_type,
             category_id: header.category_id,
             input_name: header.input_name.to_string(),
-            channel_no: header.chno.parse::<u32>().unwrap_or(0)
+            channel_no: header.chno.parse::<u32>().unwrap_or(0),
+            vlc_opts: header.vlc_opts.clone(),
         }
     }
 }
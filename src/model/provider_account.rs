@@ -0,0 +1,141 @@
+use std::fmt::Display;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::utils::json_utils::get_string_from_serde_value;
+
+const ACCOUNT_STATUS_ACTIVE: &str = "Active";
+const SECS_PER_DAY: i64 = 86_400;
+
+/// The `user_info` of a provider account from `player_api.php`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProviderAccountInfo {
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp_date: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_connections: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_cons: Option<u16>,
+}
+
+impl ProviderAccountInfo {
+    pub fn from_player_api(content: &Value) -> Option<Self> {
+        let user_info = content.get("user_info")?;
+        let get_value = |field: &str| user_info.get(field).and_then(get_string_from_serde_value);
+        let status = get_value("status")?;
+        Some(Self {
+            status,
+            exp_date: get_value("exp_date").and_then(|v| v.parse::<i64>().ok()),
+            max_connections: get_value("max_connections").and_then(|v| v.parse::<u16>().ok()),
+            active_cons: get_value("active_cons").and_then(|v| v.parse::<u16>().ok()),
+        })
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.status.eq_ignore_ascii_case(ACCOUNT_STATUS_ACTIVE)
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.exp_date.is_some_and(|exp_date| exp_date <= now)
+    }
+
+    pub fn get_alert(&self, expiry_warning_days: u16, now: i64) -> Option<ProviderAccountAlert> {
+        if self.is_expired(now) {
+            return Some(ProviderAccountAlert::Expired);
+        }
+        if !self.is_active() {
+            return Some(ProviderAccountAlert::Inactive(self.status.clone()));
+        }
+        let days = self.exp_date.map(|exp_date| (exp_date - now) / SECS_PER_DAY)?;
+        if days < i64::from(expiry_warning_days) {
+            Some(ProviderAccountAlert::Expiring(days))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ProviderAccountAlert {
+    #[serde(rename = "expiring")]
+    Expiring(i64),
+    #[serde(rename = "expired")]
+    Expired,
+    #[serde(rename = "inactive")]
+    Inactive(String),
+}
+
+impl ProviderAccountAlert {
+    /// The days of an expiring account change daily, they are not part of the kind.
+    pub fn is_same_kind(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Expiring(_), Self::Expiring(_)) => true,
+            _ => self == other,
+        }
+    }
+}
+
+impl Display for ProviderAccountAlert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Expiring(days) => write!(f, "expires in {days} days"),
+            Self::Expired => write!(f, "is expired"),
+            Self::Inactive(status) => write!(f, "has status {status}"),
+        }
+    }
+}
+
+/// The last check result of an input or alias account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderAccountStatus {
+    pub name: String,
+    pub input: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub info: Option<ProviderAccountInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alert: Option<ProviderAccountAlert>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub disabled: bool,
+    pub checked_at: i64,
+}
+
+impl ProviderAccountStatus {
+    /// Returns true if the alert was already reported, an alert is identified by its kind and the expiry date of the account.
+    pub fn has_alert(&self, alert: &ProviderAccountAlert, exp_date: Option<i64>) -> bool {
+        self.alert.as_ref().is_some_and(|reported| reported.is_same_kind(alert))
+            && self.info.as_ref().and_then(|info| info.exp_date) == exp_date
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::provider_account::{ProviderAccountAlert, ProviderAccountInfo, ProviderAccountStatus};
+
+    #[test]
+    fn test_parse_account_info() {
+        let now = 1_700_000_000;
+        let content = serde_json::json!({
+            "user_info": {"username": "user", "status": "Active", "exp_date": (now + 3 * 86_400).to_string(),
+                          "max_connections": "2", "active_cons": 1},
+            "server_info": {}
+        });
+        let info = ProviderAccountInfo::from_player_api(&content).unwrap();
+        assert_eq!(info.max_connections, Some(2));
+        assert_eq!(info.active_cons, Some(1));
+        assert_eq!(info.get_alert(7, now), Some(ProviderAccountAlert::Expiring(3)));
+        assert_eq!(info.get_alert(2, now), None);
+        assert_eq!(info.get_alert(7, now + 4 * 86_400), Some(ProviderAccountAlert::Expired));
+
+        let banned = ProviderAccountInfo { status: "Banned".to_string(), exp_date: None, max_connections: None, active_cons: None };
+        assert_eq!(banned.get_alert(7, now), Some(ProviderAccountAlert::Inactive("Banned".to_string())));
+        assert!(ProviderAccountInfo::from_player_api(&serde_json::json!({})).is_none());
+
+        let status = ProviderAccountStatus { name: "input".to_string(), input: "input".to_string(), alert: info.get_alert(7, now), info: Some(info.clone()),
+            error: None, disabled: false, checked_at: now };
+        // the remaining days change on the next day, the expiry date stays the same
+        assert!(status.has_alert(&info.get_alert(7, now + 86_400).unwrap(), info.exp_date));
+        assert!(!status.has_alert(&ProviderAccountAlert::Expiring(3), info.exp_date.map(|exp_date| exp_date + 30 * 86_400)));
+        assert!(!status.has_alert(&ProviderAccountAlert::Expired, info.exp_date));
+    }
+}
@@ -1,6 +1,9 @@
-use serde::{Deserialize, Deserializer,};
-use serde::de::DeserializeOwned;
-use serde_json::Value;
+use std::fmt;
+use std::marker::PhantomData;
+use serde::{de, Deserialize, Deserializer,};
+use serde::de::{DeserializeOwned, IntoDeserializer, MapAccess, Visitor};
+use serde::de::value::MapAccessDeserializer;
+use serde_json::{Number, Value};
 
 fn value_to_string_array(value: &[Value]) -> Vec<String> {
     value.iter().filter_map(value_to_string).collect()
@@ -53,33 +56,81 @@ where
 }
 
 
+// Reads a number, a number as string or null without the detour over `Value`.
+struct NumberFromStringVisitor<T>(PhantomData<T>);
+
+impl<'de, T: DeserializeOwned> Visitor<'de> for NumberFromStringVisitor<T> {
+    type Value = Option<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a number, a number as string or null")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        T::deserialize(value.into_deserializer()).map(Some)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        T::deserialize(value.into_deserializer()).map(Some)
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        T::deserialize(value.into_deserializer()).map(Some)
+    }
+
+    // numbers as strings like "12", if it is any other string it is ignored
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(serde_json::from_str::<T>(value).ok())
+    }
+
+    // with `arbitrary_precision` serde_json passes numbers as a map holding the number text
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        let number = Number::deserialize(MapAccessDeserializer::new(map))?;
+        T::deserialize(number).map(Some).map_err(de::Error::custom)
+    }
+}
+
 pub fn deserialize_number_from_string<'de, D, T: DeserializeOwned>(
     deserializer: D,
 ) -> Result<Option<T>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    // we define a local enum type inside of the function
-    // because it is untagged, serde will deserialize as the first variant
-    // that it can
+    deserializer.deserialize_any(NumberFromStringVisitor(PhantomData))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use serde::Deserialize;
+    use crate::model::serde_utils::deserialize_number_from_string;
+
     #[derive(Deserialize)]
-    #[serde(untagged)]
-    enum MaybeNumber<U> {
-        // if it can be parsed as Option<T>, it will be
-        Value(Option<U>),
-        // otherwise try parsing as a string
-        NumberString(String),
+    struct Item {
+        #[serde(default, deserialize_with = "deserialize_number_from_string")]
+        id: Option<u32>,
+        #[serde(default, deserialize_with = "deserialize_number_from_string")]
+        rating: Option<f64>,
     }
 
-    // deserialize into local enum
-    let value: MaybeNumber<T> = Deserialize::deserialize(deserializer)?;
-    match value {
-        // if parsed as T or None, return that
-        MaybeNumber::Value(value) => Ok(value),
-
-        // (if it is any other string)
-        MaybeNumber::NumberString(string) => {
-            serde_json::from_str::<T>(string.as_str()).map_or_else(|_| Ok(None), |val| Ok(Some(val)))
-        }
+    #[test]
+    fn test_deserialize_number_from_string() {
+        let content = r#"[{"id": 12, "rating": 7.5}, {"id": "13", "rating": "8"}, {"id": null, "rating": "n/a"}, {}]"#;
+        let items: Vec<Item> = serde_json::from_reader(Cursor::new(content)).unwrap();
+        let values: Vec<(Option<u32>, Option<f64>)> = items.iter().map(|item| (item.id, item.rating)).collect();
+        assert_eq!(values, vec![(Some(12), Some(7.5)), (Some(13), Some(8.0)), (None, None), (None, None)]);
+        assert!(serde_json::from_str::<Item>(r#"{"id": true}"#).is_err());
     }
 }
@@ -1,4 +1,6 @@
+use std::collections::BTreeMap;
 use std::fmt::{Display};
+use std::time::Duration;
 use serde::{Serialize, Serializer};
 use crate::model::config::InputType;
 
@@ -42,6 +44,8 @@ pub struct InputStats {
     pub processed_stats: PlaylistStats,
     #[serde(rename = "took", serialize_with = "serialize_elapsed_time")]
     pub secs_took: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
 impl Display for InputStats {
@@ -51,19 +55,63 @@ impl Display for InputStats {
 }
 
 
+#[derive(Debug, Clone, Serialize)]
+pub struct StepStats {
+    pub step: String,
+    #[serde(rename = "took_ms")]
+    pub millis: u64,
+}
+
+impl StepStats {
+    pub fn new(step: &str, duration: Duration) -> Self {
+        Self { step: step.to_string(), millis: u64::try_from(duration.as_millis()).unwrap_or(u64::MAX) }
+    }
+}
+
+/// Counts collected while a target runs through the processing pipeline.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProcessingStats {
+    #[serde(rename = "filtered")]
+    pub filtered_count: usize,
+    #[serde(rename = "renamed")]
+    pub renamed_count: usize,
+    #[serde(rename = "mapped")]
+    pub mapped_count: BTreeMap<String, usize>,
+    #[serde(rename = "duplicates")]
+    pub duplicate_count: usize,
+    #[serde(rename = "epg_assigned")]
+    pub epg_assigned_count: usize,
+    #[serde(rename = "regrouped")]
+    pub regrouped_count: usize,
+    #[serde(rename = "overridden")]
+    pub overridden_count: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<StepStats>,
+}
+
+impl ProcessingStats {
+    pub fn add_mapped(&mut self, mapping_id: &str, count: usize) {
+        if count > 0 {
+            *self.mapped_count.entry(mapping_id.to_string()).or_default() += count;
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct TargetStats {
     #[serde(rename = "target")]
     pub name: String,
     pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub processing: Option<ProcessingStats>,
 }
 
 impl TargetStats {
-    pub fn success(name: &str) -> Self {
-        Self  {name: name.to_string(), success: true}
+    pub fn success(name: &str, processing: ProcessingStats) -> Self {
+        Self  {name: name.to_string(), success: true, processing: Some(processing)}
     }
-    pub fn failure(name: &str) -> Self {
-        Self  {name: name.to_string(), success: false}
+    pub fn failure(name: &str, processing: ProcessingStats) -> Self {
+        Self  {name: name.to_string(), success: false, processing: Some(processing)}
     }
 }
 
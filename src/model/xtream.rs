@@ -359,6 +359,9 @@ impl XtreamSeriesInfoEpisode {
         }
         add_str_property_if_exists!(result, info.map_or("", |i| i.name.as_str()), "series_name");
         add_str_property_if_exists!(result, info.map_or("", |i| i.release_date.as_str()), "series_release_date");
+        add_str_property_if_exists!(result, info.map_or("", |i| i.plot.as_str()), "series_plot");
+        add_str_property_if_exists!(result, info.map_or("", |i| i.cover.as_str()), "series_cover");
+        add_str_property_if_exists!(result, info.map_or("", |i| i.genre.as_str()), "series_genre");
         add_str_property_if_exists!(result, self.added.as_str(), "added");
         add_str_property_if_exists!(result, info.map_or("", |i| i.cast.as_str()), "cast");
         add_str_property_if_exists!(result, self.container_extension.as_str(), "container_extension");
@@ -0,0 +1,80 @@
+use serde_json::Value;
+use crate::model::config::ConfigInput;
+use crate::model::playlist::{PlaylistGroup, PlaylistItem, PlaylistItemHeader, PlaylistItemType, XtreamCluster};
+use crate::utils::json_utils::{get_string_from_serde_value, get_u64_from_serde_value};
+
+/// Returns the device url without a trailing `/` or `discover.json`.
+pub fn get_hdhomerun_base_url(url: &str) -> &str {
+    let url = url.trim().trim_end_matches('/');
+    url.strip_suffix("/discover.json").unwrap_or(url)
+}
+
+pub fn get_hdhomerun_tuner_count(discover: &Value) -> Option<u16> {
+    discover.get("TunerCount").and_then(get_u64_from_serde_value)
+        .and_then(|count| u16::try_from(count).ok())
+        .filter(|&count| count > 0)
+}
+
+fn get_field(doc: &Value, field: &str) -> Option<String> {
+    doc.get(field).and_then(get_string_from_serde_value)
+}
+
+/// Creates one live group for the device from the `lineup.json` entries.
+/// Channels marked as `DRM` can't be streamed and are skipped.
+pub fn parse_hdhomerun_lineup(input: &ConfigInput, discover: &Value, lineup: &Value) -> Vec<PlaylistGroup> {
+    let group = get_field(discover, "FriendlyName").filter(|name| !name.is_empty()).unwrap_or_else(|| input.name.clone());
+    let channels: Vec<PlaylistItem> = lineup.as_array().map(|entries| entries.iter().filter_map(|entry| {
+        if entry.get("DRM").and_then(get_u64_from_serde_value).unwrap_or(0) != 0 {
+            return None;
+        }
+        let url = get_field(entry, "URL")?;
+        let guide_number = get_field(entry, "GuideNumber").unwrap_or_default();
+        let name = get_field(entry, "GuideName").unwrap_or_else(|| guide_number.clone());
+        let mut header = PlaylistItemHeader {
+            id: guide_number.clone(),
+            title: name.clone(),
+            name,
+            chno: guide_number,
+            group: group.clone(),
+            url,
+            xtream_cluster: XtreamCluster::Live,
+            item_type: PlaylistItemType::Live,
+            input_name: input.name.clone(),
+            ..Default::default()
+        };
+        header.gen_uuid();
+        Some(PlaylistItem { header })
+    }).collect()).unwrap_or_default();
+
+    if channels.is_empty() {
+        return vec![];
+    }
+    vec![PlaylistGroup { id: 1, title: group, channels, xtream_cluster: XtreamCluster::Live }]
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::model::config::ConfigInput;
+    use super::{get_hdhomerun_base_url, get_hdhomerun_tuner_count, parse_hdhomerun_lineup};
+
+    #[test]
+    fn test_parse_hdhomerun_lineup() {
+        assert_eq!(get_hdhomerun_base_url("http://192.168.1.10/discover.json"), "http://192.168.1.10");
+        assert_eq!(get_hdhomerun_base_url("http://192.168.1.10/"), "http://192.168.1.10");
+
+        let input = ConfigInput { name: String::from("tuner"), ..Default::default() };
+        let discover = json!({"FriendlyName": "HDHomeRun DUO", "TunerCount": 2});
+        let lineup = json!([
+            {"GuideNumber": "2.1", "GuideName": "KTVU", "URL": "http://192.168.1.10:5004/auto/v2.1"},
+            {"GuideNumber": "4.1", "GuideName": "KRON", "DRM": 1, "URL": "http://192.168.1.10:5004/auto/v4.1"}
+        ]);
+        assert_eq!(get_hdhomerun_tuner_count(&discover), Some(2));
+        let groups = parse_hdhomerun_lineup(&input, &discover, &lineup);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].title, "HDHomeRun DUO");
+        assert_eq!(groups[0].channels.len(), 1);
+        assert_eq!(groups[0].channels[0].header.chno, "2.1");
+        assert_eq!(groups[0].channels[0].header.name, "KTVU");
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use regex::Regex;
+use serde_json::json;
+use url::Url;
+use crate::m3u_filter_error::{info_err, M3uFilterError, M3uFilterErrorKind};
+use crate::model::config::{Config, ConfigInput};
+use crate::model::playlist::{PlaylistGroup, PlaylistItem, PlaylistItemHeader, PlaylistItemType, XtreamCluster};
+use crate::repository::storage::hash_string;
+use crate::utils::constants::CONSTANTS;
+
+/// Returns the directory for a local input, the url can be a plain path or a `file://` url.
+pub fn get_local_input_root(input: &ConfigInput) -> PathBuf {
+    match Url::parse(&input.url) {
+        Ok(url) if url.scheme() == "file" => url.to_file_path().unwrap_or_else(|()| PathBuf::from(&input.url)),
+        _ => PathBuf::from(&input.url),
+    }
+}
+
+fn collect_video_files(dir: &Path, extensions: &[String], files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)?.filter_map(|entry| entry.ok().map(|e| e.path())).collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect_video_files(&path, extensions, files)?;
+        } else if path.extension().and_then(|ext| ext.to_str())
+            .is_some_and(|ext| extensions.iter().any(|e| e.eq_ignore_ascii_case(ext))) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn clean_name(name: &str) -> String {
+    let name = name.replace(['.', '_'], " ");
+    let name = CONSTANTS.re_whitespace.replace_all(&name, " ");
+    CONSTANTS.re_remove_filename_ending.replace(name.trim(), "").trim().to_string()
+}
+
+// a stable provider id, derived from the relative path, survives rescans
+fn get_local_provider_id(relative_path: &Path) -> u32 {
+    let hash = hash_string(&relative_path.to_string_lossy());
+    u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]]) & 0x7FFF_FFFF
+}
+
+fn create_local_item(input: &ConfigInput, root: &Path, path: &Path, episode_re: &Regex) -> Option<PlaylistItem> {
+    let relative_path = path.strip_prefix(root).ok()?;
+    let stem = path.file_stem()?.to_string_lossy().to_string();
+    let extension = path.extension().map(|ext| ext.to_string_lossy().to_string()).unwrap_or_default();
+    let url = Url::from_file_path(path).ok()?.to_string();
+    let dir_name = relative_path.parent()
+        .and_then(|parent| parent.file_name())
+        .map_or_else(|| input.name.clone(), |name| clean_name(&name.to_string_lossy()));
+
+    let series_name = episode_re.captures(&stem)
+        .and_then(|captures| captures.name("episode"))
+        .map(|episode| clean_name(&stem[..episode.start()]));
+    let (xtream_cluster, item_type, name, group) = match series_name {
+        Some(series_name) => {
+            let series_name = if series_name.is_empty() { dir_name } else { series_name };
+            (XtreamCluster::Series, PlaylistItemType::Series, series_name.clone(), series_name)
+        }
+        None => (XtreamCluster::Video, PlaylistItemType::Video, clean_name(&stem), dir_name),
+    };
+
+    let mut header = PlaylistItemHeader {
+        id: get_local_provider_id(relative_path).to_string(),
+        name,
+        title: clean_name(&stem),
+        group,
+        url,
+        xtream_cluster,
+        item_type,
+        additional_properties: Some(json!({"container_extension": extension})),
+        input_name: input.name.clone(),
+        ..Default::default()
+    };
+    header.gen_uuid();
+    Some(PlaylistItem { header })
+}
+
+/// Scans the directory tree of a local input and creates vod and series entries for all video files.
+/// Files matching the episode pattern are series episodes grouped by series name,
+/// all other files are vod entries grouped by their directory.
+pub fn get_local_playlist(cfg: &Config, input: &ConfigInput) -> (Vec<PlaylistGroup>, Vec<M3uFilterError>) {
+    let root = get_local_input_root(input);
+    if !root.is_dir() {
+        return (vec![], vec![info_err!(format!("Local input directory not found {}", root.display()))]);
+    }
+    let extensions = cfg.video.as_ref().map_or_else(Vec::new, |video| video.extensions.clone());
+    let episode_re = cfg.video.as_ref()
+        .and_then(|video| video.download.as_ref())
+        .and_then(|download| download.t_re_episode_pattern.as_ref())
+        .unwrap_or(&CONSTANTS.re_episode_pattern);
+
+    let mut files = vec![];
+    if let Err(err) = collect_video_files(&root, &extensions, &mut files) {
+        return (vec![], vec![info_err!(format!("Failed to scan local input directory {}: {err}", root.display()))]);
+    }
+
+    let mut groups: Vec<PlaylistGroup> = vec![];
+    let mut group_idx: HashMap<(XtreamCluster, String), usize> = HashMap::new();
+    for path in &files {
+        let Some(item) = create_local_item(input, &root, path, episode_re) else { continue; };
+        let key = (item.header.xtream_cluster, item.header.group.clone());
+        if let Some(&idx) = group_idx.get(&key) {
+            groups[idx].channels.push(item);
+        } else {
+            group_idx.insert(key, groups.len());
+            #[allow(clippy::cast_possible_truncation)]
+            let id = groups.len() as u32 + 1;
+            groups.push(PlaylistGroup {
+                id,
+                title: item.header.group.clone(),
+                xtream_cluster: item.header.xtream_cluster,
+                channels: vec![item],
+            });
+        }
+    }
+    (groups, vec![])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::model::config::ConfigInput;
+    use crate::model::playlist::{PlaylistItemType, XtreamCluster};
+    use crate::utils::constants::CONSTANTS;
+    use super::create_local_item;
+
+    #[test]
+    fn test_create_local_item() {
+        let input = ConfigInput { name: String::from("local"), ..Default::default() };
+        let root = Path::new("/media");
+        let episode = create_local_item(&input, root, Path::new("/media/Shows/The.Show.S01E02.mkv"), &CONSTANTS.re_episode_pattern).unwrap();
+        assert_eq!(episode.header.item_type, PlaylistItemType::Series);
+        assert_eq!(episode.header.xtream_cluster, XtreamCluster::Series);
+        assert_eq!(episode.header.group, "The Show");
+        assert_eq!(episode.header.url, "file:///media/Shows/The.Show.S01E02.mkv");
+
+        let movie = create_local_item(&input, root, Path::new("/media/Action/Big_Movie.mp4"), &CONSTANTS.re_episode_pattern).unwrap();
+        assert_eq!(movie.header.item_type, PlaylistItemType::Video);
+        assert_eq!(movie.header.group, "Action");
+        assert_eq!(movie.header.name, "Big Movie");
+        assert_ne!(movie.header.id, episode.header.id);
+    }
+}
@@ -117,12 +117,20 @@ fn process_header(input: &ConfigInput, video_suffixes: &[&str], content: &str, u
                         (logo, "tvg-logo"),
                         (logo_small, "tvg-logo-small"),
                         (time_shift, "timeshift"),
-                        (rec, "tvg-rec"); value);
+                        (rec, "tvg-rec"),
+                        (catchup, "catchup"),
+                        (catchup_days, "catchup-days"),
+                        (catchup_source, "catchup-source"); value);
                 }
             }
             c = it.next();
         }
 
+        // older playlists announce the archive days with timeshift
+        if plih.catchup_days.is_empty() && !plih.catchup.is_empty() {
+            plih.catchup_days.clone_from(&plih.time_shift);
+        }
+
         if plih.id.is_empty() {
             plih.epg_channel_id = None;
             if let Some(chanid) = extract_id_from_url(url) {
@@ -155,12 +163,36 @@ fn process_header(input: &ConfigInput, video_suffixes: &[&str], content: &str, u
 }
 
 
+pub(crate) fn get_video_suffixes(cfg: &Config) -> Vec<&str> {
+    cfg.video.as_ref().map_or_else(Vec::new, |video| video.extensions.iter().map(String::as_str).collect())
+}
+
+/// Creates an item for playlist formats which only have a title, url, logo and group like xspf and pls.
+pub(crate) fn create_playlist_item(input: &ConfigInput, video_suffixes: &[&str], title: &str, url: &str, logo: Option<&str>, group: Option<&str>) -> PlaylistItem {
+    let mut header = create_empty_playlistitem_header(input.name.as_str(), url);
+    header.title = title.trim().to_string();
+    header.name = header.title.clone();
+    header.logo = logo.map(str::trim).unwrap_or_default().to_string();
+    header.group = group.map(str::trim).filter(|g| !g.is_empty())
+        .map_or_else(|| string_utils::get_title_group(&header.title), ToString::to_string);
+    if let Some(chanid) = extract_id_from_url(url) {
+        header.id = chanid;
+    }
+    if video_suffixes.iter().any(|suffix| url.ends_with(suffix)) {
+        header.xtream_cluster = XtreamCluster::Video;
+        header.item_type = PlaylistItemType::Video;
+    }
+    PlaylistItem { header }
+}
+
 pub fn consume_m3u<'a, I, F: FnMut(PlaylistItem)>(cfg: &Config, input: &ConfigInput, lines: I, mut visit: F)
 where
     I: Iterator<Item=&'a str>,
 {
     let mut header: Option<String> = None;
     let mut group: Option<String> = None;
+    let mut vlc_opts: Vec<String> = vec![];
+    let mut kodi_props: Vec<String> = vec![];
 
     let video_suffixes = cfg.video.as_ref().unwrap().extensions.iter().map(String::as_str).collect::<Vec<&str>>();
     for line in lines {
@@ -172,12 +204,22 @@ where
             group = Some(String::from(&line[8..]));
             continue;
         }
+        if let Some(opt) = line.strip_prefix("#EXTVLCOPT:") {
+            vlc_opts.push(opt.trim().to_string());
+            continue;
+        }
+        if let Some(prop) = line.strip_prefix("#KODIPROP:") {
+            kodi_props.push(prop.trim().to_string());
+            continue;
+        }
         if line.starts_with('#') {
             continue;
         }
         if let Some(header_value) = header {
             let mut item = PlaylistItem { header: process_header(input, &video_suffixes, &header_value, line) };
             let header = &mut item.header;
+            header.vlc_opts = std::mem::take(&mut vlc_opts);
+            header.kodi_props = std::mem::take(&mut kodi_props);
             if header.group.is_empty() {
                 if let Some(group_value) = group {
                     header.group = group_value;
@@ -190,17 +232,29 @@ where
         }
         header = None;
         group = None;
+        vlc_opts.clear();
+        kodi_props.clear();
     }
 }
 
 pub fn parse_m3u<'a, I>(cfg: &Config, input: &ConfigInput, lines: I) -> Vec<PlaylistGroup>
 where
     I: Iterator<Item=&'a str>,
+{
+    let mut items = vec![];
+    consume_m3u(cfg, input, lines, |item| items.push(item));
+    group_playlist_items(items)
+}
+
+/// Groups the playlist items by cluster and group title, the original sort order is kept.
+pub fn group_playlist_items<I>(items: I) -> Vec<PlaylistGroup>
+where
+    I: IntoIterator<Item=PlaylistItem>,
 {
     let mut sort_order: Vec<Vec<PlaylistItem>> = vec![];
     let mut sort_order_idx: usize = 0;
     let mut group_map: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
-    consume_m3u(cfg, input, lines, |item| {
+    for item in items {
         // keep the original sort order for groups and group the playlist items
         let key = {
             let header = &item.header;
@@ -216,7 +270,7 @@ where
                 sort_order.get_mut(*o.get()).unwrap().push(item);
             }
         }
-    });
+    }
     let mut grp_id = 0;
     let result: Vec<PlaylistGroup> = sort_order.into_iter().map(|channels| {
         // create a group based on the first playlist item
@@ -228,3 +282,48 @@ where
     }).collect();
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::model::config::{Config, ConfigInput, VideoConfig};
+    use crate::model::playlist::{FieldGetAccessor, FieldSetAccessor};
+    use crate::processing::parser::m3u::{consume_m3u, process_header};
+
+    #[test]
+    fn test_process_catchup_header() {
+        let input = ConfigInput { name: "provider".to_string(), ..ConfigInput::default() };
+        let header = process_header(&input, &[], r#"#EXTINF:-1 tvg-id="ch1" catchup="append" catchup-source="?utc={utc}" timeshift="7",Channel 1"#, "http://provider.net/ch1.ts");
+        assert_eq!(header.catchup, "append");
+        assert_eq!(header.catchup_source, "?utc={utc}");
+        assert_eq!(header.time_shift, "7");
+        assert_eq!(header.catchup_days, "7");
+
+        let header = process_header(&input, &[], r#"#EXTINF:-1 catchup="shift" catchup-days="3" timeshift="7",Channel 2"#, "http://provider.net/ch2.ts");
+        assert_eq!(header.catchup_days, "3");
+    }
+
+    #[test]
+    fn test_consume_item_directives() {
+        let cfg = Config { video: Some(VideoConfig::default()), ..Config::default() };
+        let input = ConfigInput { name: "provider".to_string(), ..ConfigInput::default() };
+        let content = "#EXTM3U\n#EXTINF:-1 tvg-id=\"ch1\",Channel 1\n#KODIPROP:inputstream.adaptive.license_type=clearkey\n\
+            #EXTVLCOPT:http-user-agent=Player/1.0\n#EXTVLCOPT:http-referrer=http://provider.net/\nhttp://provider.net/ch1.mpd\n\
+            #EXTINF:-1 tvg-id=\"ch2\",Channel 2\nhttp://provider.net/ch2.ts";
+        let mut items = vec![];
+        consume_m3u(&cfg, &input, content.lines(), |item| items.push(item));
+        assert_eq!(items.len(), 2);
+        let header = &mut items[0].header;
+        assert_eq!(header.kodi_props, vec!["inputstream.adaptive.license_type=clearkey"]);
+        assert_eq!(header.get_field("vlc_opt.http-user-agent").as_deref(), Some("Player/1.0"));
+        assert!(header.set_field("vlc_opt.http-user-agent", "Other/2.0"));
+        assert!(header.set_field("vlc_opt.http-referrer", ""));
+        assert_eq!(header.vlc_opts, vec!["http-user-agent=Other/2.0"]);
+        assert!(items[1].header.vlc_opts.is_empty());
+
+        let m3u_item = items[0].to_m3u();
+        assert_eq!(m3u_item.get_stream_headers().get("user-agent").map(String::as_str), Some("Other/2.0"));
+        assert_eq!(items[0].to_xtream().get_stream_headers().get("user-agent").map(String::as_str), Some("Other/2.0"));
+        assert!(m3u_item.to_m3u(None, false).ends_with(",Channel 1\n#KODIPROP:inputstream.adaptive.license_type=clearkey\n\
+            #EXTVLCOPT:http-user-agent=Other/2.0\nhttp://provider.net/ch1.mpd"));
+    }
+}
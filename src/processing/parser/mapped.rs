@@ -0,0 +1,120 @@
+use std::io::{BufReader, Cursor};
+use serde_json::Value;
+use crate::m3u_filter_error::{info_err, M3uFilterError, M3uFilterErrorKind};
+use crate::model::config::{ConfigInput, ConfigInputMapping};
+use crate::model::playlist::{PlaylistGroup, PlaylistItem, PlaylistItemHeader, PlaylistItemType, XtreamCluster};
+use crate::processing::parser::m3u::group_playlist_items;
+use crate::utils::file::config_reader::csv_read_records;
+use crate::utils::json_utils::{get_json_path, get_string_from_serde_value};
+
+fn get_item_type(value: Option<&str>) -> (XtreamCluster, PlaylistItemType) {
+    match value.map(str::to_lowercase).as_deref() {
+        Some("video" | "vod" | "movie") => (XtreamCluster::Video, PlaylistItemType::Video),
+        Some("series" | "episode") => (XtreamCluster::Series, PlaylistItemType::Series),
+        _ => (XtreamCluster::Live, PlaylistItemType::Live),
+    }
+}
+
+fn create_mapped_item<F>(input: &ConfigInput, mapping: &ConfigInputMapping, get_value: F) -> Option<PlaylistItem>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let field = |name: &str| get_value(mapping.get_source(name)).map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+    let url = field("url")?;
+    let name = field("name").or_else(|| field("title")).unwrap_or_default();
+    let (xtream_cluster, item_type) = get_item_type(field("type").as_deref());
+    let mut header = PlaylistItemHeader {
+        id: field("id").unwrap_or_default(),
+        title: field("title").unwrap_or_else(|| name.clone()),
+        name,
+        chno: field("chno").unwrap_or_default(),
+        logo: field("logo").unwrap_or_default(),
+        logo_small: field("logo_small").unwrap_or_default(),
+        group: field("group").unwrap_or_else(|| input.name.clone()),
+        parent_code: field("parent_code").unwrap_or_default(),
+        audio_track: field("audio_track").unwrap_or_default(),
+        time_shift: field("time_shift").unwrap_or_default(),
+        rec: field("rec").unwrap_or_default(),
+        catchup: field("catchup").unwrap_or_default(),
+        catchup_days: field("catchup_days").unwrap_or_default(),
+        catchup_source: field("catchup_source").unwrap_or_default(),
+        epg_channel_id: field("epg_channel_id"),
+        url,
+        xtream_cluster,
+        item_type,
+        input_name: input.name.clone(),
+        ..Default::default()
+    };
+    header.gen_uuid();
+    Some(PlaylistItem { header })
+}
+
+/// Parses a json channel list, the items are read from the array at `mapping.root` or the document root.
+pub fn parse_json_playlist(input: &ConfigInput, content: &str) -> Result<Vec<PlaylistGroup>, M3uFilterError> {
+    let mapping = input.mapping.clone().unwrap_or_default();
+    let doc: Value = serde_json::from_str(content).map_err(|err| info_err!(format!("Failed to parse json input {}: {err}", input.name)))?;
+    let items = match mapping.root.as_deref() {
+        Some(root) => get_json_path(&doc, root),
+        None => Some(&doc),
+    }.and_then(Value::as_array)
+        .ok_or_else(|| info_err!(format!("No item list found in json input {}", input.name)))?;
+    Ok(group_playlist_items(items.iter().filter_map(|doc| create_mapped_item(input, &mapping, |path| get_json_path(doc, path).and_then(get_string_from_serde_value)))))
+}
+
+/// Parses a csv channel list, the first line contains the column names.
+pub fn parse_csv_playlist(input: &ConfigInput, content: &str) -> Result<Vec<PlaylistGroup>, M3uFilterError> {
+    let mapping = input.mapping.clone().unwrap_or_default();
+    let records = csv_read_records(BufReader::new(Cursor::new(content)), mapping.separator)
+        .map_err(|err| info_err!(format!("Failed to parse csv input {}: {err}", input.name)))?;
+    Ok(group_playlist_items(records.iter().filter_map(|record| create_mapped_item(input, &mapping, |column| record.get(column).cloned()))))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::model::config::{ConfigInput, ConfigInputMapping};
+    use crate::model::playlist::XtreamCluster;
+    use super::{parse_csv_playlist, parse_json_playlist};
+
+    #[test]
+    fn test_parse_json_playlist() {
+        let input = ConfigInput {
+            name: String::from("json"),
+            mapping: Some(ConfigInputMapping {
+                root: Some(String::from("data.channels")),
+                fields: HashMap::from([(String::from("url"), String::from("streams.0")), (String::from("group"), String::from("category.name"))]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let content = r#"{"data": {"channels": [
+            {"name": "Channel 1", "streams": ["http://localhost/1.ts"], "category": {"name": "News"}, "type": "live"},
+            {"name": "Movie", "streams": ["http://localhost/2.mp4"], "category": {"name": "Movies"}, "type": "movie"},
+            {"name": "No stream"}
+        ]}}"#;
+        let groups = parse_json_playlist(&input, content).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].title, "News");
+        assert_eq!(groups[0].channels[0].header.url, "http://localhost/1.ts");
+        assert_eq!(groups[1].xtream_cluster, XtreamCluster::Video);
+    }
+
+    #[test]
+    fn test_parse_csv_playlist() {
+        let input = ConfigInput {
+            name: String::from("csv"),
+            mapping: Some(ConfigInputMapping {
+                separator: Some(','),
+                fields: HashMap::from([(String::from("url"), String::from("stream")), (String::from("chno"), String::from("number"))]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let content = "number,name,stream\n1,Channel 1,http://localhost/1.ts\n2,Channel 2,http://localhost/2.ts\n";
+        let groups = parse_csv_playlist(&input, content).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].title, "csv");
+        assert_eq!(groups[0].channels.len(), 2);
+        assert_eq!(groups[0].channels[1].header.chno, "2");
+    }
+}
@@ -1,4 +1,10 @@
 pub mod m3u;
 pub mod xtream;
 pub mod xmltv;
-pub mod hls;
\ No newline at end of file
+pub mod hls;
+pub mod local;
+pub mod stalker;
+pub mod hdhomerun;
+pub mod mapped;
+pub mod xspf;
+pub mod pls;
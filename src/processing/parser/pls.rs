@@ -0,0 +1,37 @@
+use std::collections::BTreeMap;
+use crate::model::config::{Config, ConfigInput};
+use crate::model::playlist::PlaylistGroup;
+use crate::processing::parser::m3u::{create_playlist_item, get_video_suffixes, group_playlist_items};
+
+pub fn is_pls(content: &str) -> bool {
+    content.trim_start_matches('\u{feff}').trim_start().get(..10).is_some_and(|header| header.eq_ignore_ascii_case("[playlist]"))
+}
+
+/// Parses a pls playlist, the entries are numbered `File<n>` and `Title<n>` keys.
+pub fn parse_pls(cfg: &Config, input: &ConfigInput, content: &str) -> Vec<PlaylistGroup> {
+    let mut entries: BTreeMap<u32, (Option<&str>, Option<&str>)> = BTreeMap::new();
+    for line in content.lines() {
+        let Some((key, value)) = line.trim().split_once('=') else { continue; };
+        let key = key.trim().to_lowercase();
+        let (is_file, number) = if let Some(number) = key.strip_prefix("file") {
+            (true, number)
+        } else if let Some(number) = key.strip_prefix("title") {
+            (false, number)
+        } else {
+            continue;
+        };
+        let Ok(number) = number.parse::<u32>() else { continue; };
+        let entry = entries.entry(number).or_default();
+        if is_file {
+            entry.0 = Some(value.trim());
+        } else {
+            entry.1 = Some(value.trim());
+        }
+    }
+
+    let video_suffixes = get_video_suffixes(cfg);
+    group_playlist_items(entries.into_values().filter_map(|(url, title)| {
+        let url = url.filter(|u| !u.is_empty())?;
+        Some(create_playlist_item(input, &video_suffixes, title.unwrap_or(url), url, None, None))
+    }))
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use serde_json::Value;
+use url::Url;
+use crate::model::config::ConfigInput;
+use crate::model::playlist::{PlaylistGroup, PlaylistItem, PlaylistItemHeader, PlaylistItemType, XtreamCluster};
+use crate::utils::json_utils::get_string_from_serde_value;
+
+pub const STALKER_TYPE_LIVE: &str = "itv";
+pub const STALKER_TYPE_VOD: &str = "vod";
+pub const STALKER_TYPE_SERIES: &str = "series";
+
+/// The stream links of a portal are only valid for a short time, they are created on play with `create_link`.
+/// The playlist item stores the `create_link` request instead of the stream url.
+pub fn create_stalker_link_url(api_url: &str, stalker_type: &str, cmd: &str, series: Option<&str>) -> Option<String> {
+    let mut params = vec![("type", stalker_type), ("action", "create_link"), ("cmd", cmd)];
+    if let Some(episode) = series {
+        params.push(("series", episode));
+    }
+    params.push(("JsHttpRequest", "1-xml"));
+    Url::parse_with_params(api_url, &params).ok().map(|url| url.to_string())
+}
+
+/// Extracts the stream url from a `create_link` command like `ffmpeg http://...`.
+pub fn get_stalker_cmd_url(cmd: &str) -> Option<String> {
+    let url = cmd.trim().rsplit(' ').next()?.trim();
+    if url.is_empty() { None } else { Some(url.to_string()) }
+}
+
+fn get_field(doc: &Value, field: &str) -> Option<String> {
+    doc.get(field).and_then(get_string_from_serde_value)
+}
+
+fn get_categories(categories: &Value) -> HashMap<String, String> {
+    categories.as_array().map(|list| list.iter().filter_map(|category| {
+        Some((get_field(category, "id")?, get_field(category, "title")?))
+    }).collect()).unwrap_or_default()
+}
+
+fn create_stalker_item(input: &ConfigInput, doc: &Value, cluster: XtreamCluster, item_type: PlaylistItemType, group: &str, url: String) -> PlaylistItem {
+    let name = get_field(doc, "name").unwrap_or_default();
+    let mut header = PlaylistItemHeader {
+        id: get_field(doc, "id").unwrap_or_default(),
+        title: name.clone(),
+        name,
+        chno: get_field(doc, "number").unwrap_or_default(),
+        logo: get_field(doc, "logo").or_else(|| get_field(doc, "screenshot_uri")).unwrap_or_default(),
+        group: group.to_string(),
+        epg_channel_id: get_field(doc, "xmltv_id"),
+        url,
+        xtream_cluster: cluster,
+        item_type,
+        input_name: input.name.clone(),
+        ..Default::default()
+    };
+    header.gen_uuid();
+    PlaylistItem { header }
+}
+
+fn add_to_group(groups: &mut Vec<PlaylistGroup>, cluster: XtreamCluster, item: PlaylistItem) {
+    if let Some(group) = groups.iter_mut().find(|g| g.xtream_cluster == cluster && g.title == item.header.group) {
+        group.channels.push(item);
+    } else {
+        #[allow(clippy::cast_possible_truncation)]
+        let id = groups.len() as u32 + 1;
+        groups.push(PlaylistGroup { id, title: item.header.group.clone(), channels: vec![item], xtream_cluster: cluster });
+    }
+}
+
+/// Creates the live groups from the `itv` genres and the channel list.
+pub fn parse_stalker_live(input: &ConfigInput, api_url: &str, genres: &Value, channels: &[Value]) -> Vec<PlaylistGroup> {
+    let genres = get_categories(genres);
+    let mut groups = vec![];
+    for channel in channels {
+        let Some(cmd) = get_field(channel, "cmd") else { continue; };
+        let Some(url) = create_stalker_link_url(api_url, STALKER_TYPE_LIVE, &cmd, None) else { continue; };
+        let group = get_field(channel, "tv_genre_id").and_then(|id| genres.get(&id).cloned()).unwrap_or_else(|| input.name.clone());
+        let item = create_stalker_item(input, channel, XtreamCluster::Live, PlaylistItemType::Live, &group, url);
+        add_to_group(&mut groups, XtreamCluster::Live, item);
+    }
+    groups
+}
+
+/// Creates vod or series groups from the category list and the items of each category.
+/// Series have a list of episode numbers, each episode is a separate item.
+pub fn parse_stalker_category_items(input: &ConfigInput, api_url: &str, stalker_type: &str, categories: &Value, items: &[(String, Vec<Value>)]) -> Vec<PlaylistGroup> {
+    let categories = get_categories(categories);
+    let cluster = if stalker_type == STALKER_TYPE_SERIES { XtreamCluster::Series } else { XtreamCluster::Video };
+    let mut groups = vec![];
+    for (category_id, category_items) in items {
+        let group = categories.get(category_id).map_or(category_id.as_str(), String::as_str);
+        for doc in category_items {
+            let Some(cmd) = get_field(doc, "cmd") else { continue; };
+            let episodes: Vec<String> = doc.get("series").and_then(Value::as_array)
+                .map(|list| list.iter().filter_map(get_string_from_serde_value).collect()).unwrap_or_default();
+            if cluster == XtreamCluster::Series && !episodes.is_empty() {
+                for episode in &episodes {
+                    let Some(url) = create_stalker_link_url(api_url, STALKER_TYPE_VOD, &cmd, Some(episode)) else { continue; };
+                    let mut item = create_stalker_item(input, doc, cluster, PlaylistItemType::Series, group, url);
+                    item.header.id = String::new();
+                    item.header.title = format!("{} E{episode}", item.header.name);
+                    item.header.gen_uuid();
+                    add_to_group(&mut groups, cluster, item);
+                }
+            } else if let Some(url) = create_stalker_link_url(api_url, STALKER_TYPE_VOD, &cmd, None) {
+                let item = create_stalker_item(input, doc, XtreamCluster::Video, PlaylistItemType::Video, group, url);
+                add_to_group(&mut groups, XtreamCluster::Video, item);
+            }
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::model::config::ConfigInput;
+    use crate::model::playlist::{PlaylistItemType, XtreamCluster};
+    use super::{get_stalker_cmd_url, parse_stalker_category_items, parse_stalker_live, STALKER_TYPE_SERIES};
+
+    const API_URL: &str = "http://localhost/stalker_portal/server/load.php";
+
+    #[test]
+    fn test_parse_stalker() {
+        let input = ConfigInput { name: String::from("portal"), ..Default::default() };
+        let genres = json!([{"id": "1", "title": "News"}]);
+        let channels = vec![json!({"id": 10, "name": "News 1", "number": "5", "cmd": "ffmpeg http://localhost/ch/10_", "tv_genre_id": "1"})];
+        let live = parse_stalker_live(&input, API_URL, &genres, &channels);
+        assert_eq!(live.len(), 1);
+        assert_eq!(live[0].title, "News");
+        let header = &live[0].channels[0].header;
+        assert_eq!(header.chno, "5");
+        assert!(header.url.starts_with(API_URL) && header.url.contains("action=create_link"));
+
+        let categories = json!([{"id": "7", "title": "Drama"}]);
+        let items = vec![(String::from("7"), vec![json!({"id": "3", "name": "Show", "cmd": "/media/3.mpg", "series": [1, 2]})])];
+        let series = parse_stalker_category_items(&input, API_URL, STALKER_TYPE_SERIES, &categories, &items);
+        assert_eq!(series[0].xtream_cluster, XtreamCluster::Series);
+        assert_eq!(series[0].channels.len(), 2);
+        assert_eq!(series[0].channels[1].header.item_type, PlaylistItemType::Series);
+        assert_ne!(series[0].channels[0].header.uuid, series[0].channels[1].header.uuid);
+
+        assert_eq!(get_stalker_cmd_url("ffmpeg http://host/play?token=1").unwrap(), "http://host/play?token=1");
+        assert_eq!(get_stalker_cmd_url("http://host/play").unwrap(), "http://host/play");
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use crate::m3u_filter_error::{info_err, M3uFilterError, M3uFilterErrorKind};
+use crate::model::config::{Config, ConfigInput};
+use crate::model::playlist::PlaylistGroup;
+use crate::processing::parser::m3u::{create_playlist_item, get_video_suffixes, group_playlist_items};
+
+#[derive(Default)]
+struct XspfTrack {
+    location: String,
+    title: String,
+    image: Option<String>,
+    album: Option<String>,
+    id: Option<String>,
+}
+
+pub fn is_xspf(content: &str) -> bool {
+    let content = content.trim_start_matches('\u{feff}').trim_start();
+    content.starts_with("<?xml") && content.contains("<playlist") || content.starts_with("<playlist")
+}
+
+fn get_attribute(e: &BytesStart, name: &str) -> Option<String> {
+    e.try_get_attribute(name).ok().flatten()
+        .and_then(|attr| attr.unescape_value().ok().map(|value| value.to_string()))
+}
+
+/// Parses a xspf playlist. The groups are read from the vlc `node` extension, the `album` is used as fallback.
+pub fn parse_xspf(cfg: &Config, input: &ConfigInput, content: &str) -> Result<Vec<PlaylistGroup>, M3uFilterError> {
+    let mut reader = Reader::from_str(content);
+    reader.config_mut().trim_text(true);
+    let mut tracks: Vec<XspfTrack> = vec![];
+    let mut track: Option<XspfTrack> = None;
+    let mut element = String::new();
+    let mut nodes: Vec<String> = vec![];
+    let mut track_groups: HashMap<String, String> = HashMap::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = e.local_name();
+                match name.as_ref() {
+                    b"track" => track = Some(XspfTrack::default()),
+                    b"node" => nodes.push(get_attribute(&e, "title").unwrap_or_default()),
+                    tag => element = String::from_utf8_lossy(tag).to_string(),
+                }
+            }
+            Ok(Event::Empty(e)) if e.local_name().as_ref() == b"item" => {
+                if let (Some(tid), Some(group)) = (get_attribute(&e, "tid"), nodes.last()) {
+                    track_groups.insert(tid, group.clone());
+                }
+            }
+            Ok(Event::End(e)) => {
+                match e.local_name().as_ref() {
+                    b"track" => if let Some(t) = track.take() {
+                        if !t.location.is_empty() {
+                            tracks.push(t);
+                        }
+                    },
+                    b"node" => { nodes.pop(); }
+                    _ => {}
+                }
+                element.clear();
+            }
+            Ok(Event::Text(e)) => {
+                if let (Some(t), Ok(text)) = (track.as_mut(), e.unescape()) {
+                    match element.as_str() {
+                        "location" => t.location = text.trim().to_string(),
+                        "title" => t.title = text.trim().to_string(),
+                        "image" => t.image = Some(text.trim().to_string()),
+                        "album" => t.album = Some(text.trim().to_string()),
+                        "id" => t.id = Some(text.trim().to_string()),
+                        _ => {}
+                    }
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(err) => return Err(info_err!(format!("Failed to parse xspf input {} at position {}: {err}", input.name, reader.error_position()))),
+            _ => {}
+        }
+    }
+
+    let video_suffixes = get_video_suffixes(cfg);
+    Ok(group_playlist_items(tracks.iter().map(|t| {
+        let group = t.id.as_ref().and_then(|id| track_groups.get(id)).or(t.album.as_ref());
+        let title = if t.title.is_empty() { &t.location } else { &t.title };
+        create_playlist_item(input, &video_suffixes, title, &t.location, t.image.as_deref(), group.map(String::as_str))
+    })))
+}
@@ -2,10 +2,13 @@ use crate::m3u_filter_error::{create_m3u_filter_error_result, M3uFilterError, M3
 use crate::model::config::ConfigInput;
 use crate::model::playlist::{PlaylistGroup, PlaylistItem, PlaylistItemHeader, PlaylistItemType, XtreamCluster};
 use crate::model::xtream::{XtreamCategory, XtreamSeriesInfo, XtreamSeriesInfoEpisode, XtreamStream};
+use crate::utils::catchup_utils::get_xtream_catchup_source;
 use crate::utils::hash_utils::generate_playlist_uuid;
+use crate::utils::json_utils::json_iter_array;
 use crate::utils::network::xtream::{get_xtream_stream_url_base};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::io::Read;
 use std::sync::Arc;
 use crate::model::xtream_const;
 
@@ -18,15 +21,6 @@ fn map_to_xtream_category(categories: &Value) -> Result<Vec<XtreamCategory>, M3u
     }
 }
 
-fn map_to_xtream_streams(xtream_cluster: XtreamCluster, streams: &Value) -> Result<Vec<XtreamStream>, M3uFilterError> {
-    match serde_json::from_value::<Vec<XtreamStream>>(streams.to_owned()) {
-        Ok(stream_list) => Ok(stream_list),
-        Err(err) => {
-            create_m3u_filter_error_result!(M3uFilterErrorKind::Notify, "Failed to map to xtream streams {:?}: {}", xtream_cluster, &err)
-        }
-    }
-}
-
 fn create_xtream_series_episode_url(url: &str, username: &str, password: &str, episode: &XtreamSeriesInfoEpisode) -> Arc<String> {
     if episode.direct_source.is_empty() {
         let ext = episode.container_extension.clone();
@@ -108,84 +102,126 @@ pub fn create_xtream_url(xtream_cluster: XtreamCluster, url: &str, username: &st
     }
 }
 
-pub fn parse_xtream(input: &ConfigInput,
-                    xtream_cluster: XtreamCluster,
-                    categories: &Value,
-                    streams: &Value) -> Result<Option<Vec<PlaylistGroup>>, M3uFilterError> {
-    match map_to_xtream_category(categories) {
-        Ok(xtream_categories) => {
-            let input_name = Arc::new(input.name.to_string());
-            let url = input.url.as_str();
-            let username = input.username.as_ref().map_or("", |v| v);
-            let password = input.password.as_ref().map_or("", |v| v);
-
-            match map_to_xtream_streams(xtream_cluster, streams) {
-                Ok(mut xtream_streams) => {
-                    let mut group_map: HashMap<String, XtreamCategory> =
-                        xtream_categories.into_iter().map(|category|
-                            (category.category_id.to_string(), category)
-                        ).collect();
-                    let mut unknown_grp = XtreamCategory {
-                        category_id: "0".to_string(),
-                        category_name: "Unknown".to_string(),
-                        channels: vec![],
-                    };
-
-                    let (live_stream_use_prefix, live_stream_without_extension) = input.options.as_ref()
-                        .map_or((true, false), |o| (o.xtream_live_stream_use_prefix, o.xtream_live_stream_without_extension));
-
-                    for stream in &mut xtream_streams {
-                        let group = group_map.get_mut(&stream.category_id).unwrap_or(&mut unknown_grp);
-                        let category_name = &group.category_name;
-                        let stream_url = create_xtream_url(xtream_cluster, url, username, password, stream, live_stream_use_prefix, live_stream_without_extension);
-                        let item_type = PlaylistItemType::from(xtream_cluster);
-                        // EPG Channel id fix, remove empty
-                        stream.epg_channel_id = if let XtreamCluster::Live = xtream_cluster {
-                            stream.epg_channel_id.as_ref()
-                                .filter(|epg_id| !epg_id.trim().is_empty())
-                                .map(|epg_id| epg_id.to_lowercase())
-                                .or(None)
-                        } else {
-                            None
-                        };
-                        let item = PlaylistItem {
-                            header: PlaylistItemHeader {
-                                id: stream.get_stream_id().to_string(),
-                                uuid: generate_playlist_uuid(&input_name, &stream.get_stream_id().to_string(), item_type, &stream_url),
-                                name: stream.name.to_string(),
-                                logo: stream.stream_icon.to_string(),
-                                group: category_name.to_string(),
-                                title: stream.name.to_string(),
-                                url: stream_url.to_string(),
-                                epg_channel_id: stream.epg_channel_id.clone(),
-                                item_type,
-                                xtream_cluster,
-                                additional_properties: stream.get_additional_properties(),
-                                category_id: 0,
-                                input_name: input_name.to_string(),
-                                ..Default::default()
-                            },
-                        };
-                        group.add(item);
-                    }
-                    let has_channels = !unknown_grp.channels.is_empty();
-                    if has_channels {
-                        group_map.insert("0".to_string(), unknown_grp);
-                    }
-
-                    Ok(Some(group_map.values().filter(|category| !category.channels.is_empty())
-                        .map(|category| {
-                            PlaylistGroup {
-                                id: category.category_id.parse::<u32>().unwrap_or(0),
-                                xtream_cluster,
-                                title: category.category_name.to_string(),
-                                channels: category.channels.clone(),
-                            }
-                        }).collect()))
-                }
-                Err(err) => Err(err)
-            }
+fn create_xtream_item(input_name: &Arc<String>, xtream_cluster: XtreamCluster,
+                      category_name: &str, stream_url: &str, stream: &mut XtreamStream) -> PlaylistItem {
+    let item_type = PlaylistItemType::from(xtream_cluster);
+    // EPG Channel id fix, remove empty
+    stream.epg_channel_id = if let XtreamCluster::Live = xtream_cluster {
+        stream.epg_channel_id.as_ref()
+            .filter(|epg_id| !epg_id.trim().is_empty())
+            .map(|epg_id| epg_id.to_lowercase())
+            .or(None)
+    } else {
+        None
+    };
+    PlaylistItem {
+        header: PlaylistItemHeader {
+            id: stream.get_stream_id().to_string(),
+            uuid: generate_playlist_uuid(input_name, &stream.get_stream_id().to_string(), item_type, stream_url),
+            name: stream.name.to_string(),
+            logo: stream.stream_icon.to_string(),
+            group: category_name.to_string(),
+            title: stream.name.to_string(),
+            url: stream_url.to_string(),
+            epg_channel_id: stream.epg_channel_id.clone(),
+            item_type,
+            xtream_cluster,
+            additional_properties: stream.get_additional_properties(),
+            category_id: 0,
+            input_name: input_name.to_string(),
+            ..Default::default()
+        },
+    }
+}
+
+/// Parses the streams of a category.
+/// The `streams` json array is read one entry at a time and mapped straight into playlist items,
+/// so the whole response is never held in memory.
+pub fn parse_xtream<R: Read>(input: &ConfigInput,
+                             xtream_cluster: XtreamCluster,
+                             categories: &Value,
+                             streams: R) -> Result<Option<Vec<PlaylistGroup>>, M3uFilterError> {
+    let xtream_categories = map_to_xtream_category(categories)?;
+    let input_name = Arc::new(input.name.to_string());
+    let url = input.url.as_str();
+    let username = input.username.as_ref().map_or("", |v| v);
+    let password = input.password.as_ref().map_or("", |v| v);
+
+    let mut group_map: HashMap<String, XtreamCategory> =
+        xtream_categories.into_iter().map(|category|
+            (category.category_id.to_string(), category)
+        ).collect();
+    let mut unknown_grp = XtreamCategory {
+        category_id: "0".to_string(),
+        category_name: "Unknown".to_string(),
+        channels: vec![],
+    };
+
+    let (live_stream_use_prefix, live_stream_without_extension) = input.options.as_ref()
+        .map_or((true, false), |o| (o.xtream_live_stream_use_prefix, o.xtream_live_stream_without_extension));
+
+    for stream_entry in json_iter_array::<XtreamStream, R>(streams) {
+        let mut stream = match stream_entry {
+            Ok(stream) => stream,
+            Err(err) => return create_m3u_filter_error_result!(M3uFilterErrorKind::Notify, "Failed to map to xtream streams {:?}: {}", xtream_cluster, &err),
+        };
+        let group = group_map.get_mut(&stream.category_id).unwrap_or(&mut unknown_grp);
+        let stream_url = create_xtream_url(xtream_cluster, url, username, password, &stream, live_stream_use_prefix, live_stream_without_extension);
+        let mut item = create_xtream_item(&input_name, xtream_cluster, &group.category_name, &stream_url, &mut stream);
+        if xtream_cluster == XtreamCluster::Live && stream.tv_archive.unwrap_or(0) > 0 && stream.direct_source.is_empty() {
+            let header = &mut item.header;
+            header.catchup = "default".to_string();
+            header.catchup_days = stream.tv_archive_duration.map_or_else(String::new, |days| days.to_string());
+            header.catchup_source = get_xtream_catchup_source(url, username, password, stream.get_stream_id());
         }
-        Err(err) => Err(err)
+        group.add(item);
+    }
+    if !unknown_grp.channels.is_empty() {
+        group_map.insert("0".to_string(), unknown_grp);
+    }
+
+    Ok(Some(group_map.into_values().filter(|category| !category.channels.is_empty())
+        .map(|category| {
+            PlaylistGroup {
+                id: category.category_id.parse::<u32>().unwrap_or(0),
+                xtream_cluster,
+                title: category.category_name,
+                channels: category.channels,
+            }
+        }).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::config::ConfigInput;
+    use crate::model::playlist::XtreamCluster;
+    use crate::processing::parser::xtream::parse_xtream;
+
+    #[test]
+    fn test_parse_xtream_streams() {
+        let input = ConfigInput { name: "provider".to_string(), url: "http://provider.net".to_string(),
+            username: Some("user".to_string()), password: Some("pass".to_string()), ..ConfigInput::default() };
+        let categories = serde_json::json!([{"category_id": "1", "category_name": "Movies", "parent_id": 0}]);
+        let streams = r#"[
+            {"name": "Movie", "stream_id": "10", "category_id": "1", "rating": "6.5", "rating_5based": 3.2, "container_extension": "mkv"},
+            {"name": "Other", "stream_id": 11, "category_id": "99", "rating": "", "container_extension": "mp4"}
+        ]"#;
+        let mut groups = parse_xtream(&input, XtreamCluster::Video, &categories, streams.as_bytes()).unwrap().unwrap();
+        groups.sort_by(|a, b| a.title.cmp(&b.title));
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].title, "Movies");
+        assert_eq!(groups[0].channels[0].header.url, "http://provider.net/movie/user/pass/10.mkv");
+        assert_eq!(groups[1].title, "Unknown");
+        assert_eq!(groups[1].channels[0].header.id, "11");
+
+        assert!(parse_xtream(&input, XtreamCluster::Video, &categories, r#"{"error": "denied"}"#.as_bytes()).is_err());
+        assert!(parse_xtream(&input, XtreamCluster::Video, &categories, "[]".as_bytes()).unwrap().unwrap().is_empty());
+
+        let live_streams = r#"[{"name": "News", "stream_id": 12, "category_id": "1", "tv_archive": 1, "tv_archive_duration": "5"}]"#;
+        let groups = parse_xtream(&input, XtreamCluster::Live, &categories, live_streams.as_bytes()).unwrap().unwrap();
+        let header = &groups[0].channels[0].header;
+        assert_eq!(header.catchup, "default");
+        assert_eq!(header.catchup_days, "5");
+        assert_eq!(header.catchup_source, "http://provider.net/timeshift/user/pass/{duration:60}/{Y}-{m}-{d}:{H}-{M}/12.ts");
     }
 }
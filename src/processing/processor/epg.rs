@@ -130,9 +130,10 @@ impl EpgIdCache<'_> {
 /// let mut new_epg = Vec::new();
 /// let mut playlist = FetchedPlaylist::default();
 /// let mut id_cache = EpgIdCache::new(None);
-/// assign_channel_epg(&mut new_epg, &mut playlist, &mut id_cache);
+/// let assigned = assign_channel_epg(&mut new_epg, &mut playlist, &mut id_cache);
 /// ```
-fn assign_channel_epg(new_epg: &mut Vec<Epg>, fp: &mut FetchedPlaylist, id_cache: &mut EpgIdCache) {
+fn assign_channel_epg(new_epg: &mut Vec<Epg>, fp: &mut FetchedPlaylist, id_cache: &mut EpgIdCache) -> usize {
+    let mut assigned_count = 0;
     if let Some(tv_guide) = &fp.epg {
         if let Some(epg) = tv_guide.filter(id_cache) {
             // // icon tags
@@ -155,6 +156,9 @@ fn assign_channel_epg(new_epg: &mut Vec<Epg>, fp: &mut FetchedPlaylist, id_cache
                         let normalized = id_cache.normalize(&chan.header.name);
                         if let Some(epg_id) = id_cache.normalized.get(&normalized) {
                             chan.header.epg_channel_id.clone_from(epg_id);
+                            if epg_id.is_some() {
+                                assigned_count += 1;
+                            }
                         }
                     }
                 }
@@ -172,36 +176,47 @@ fn assign_channel_epg(new_epg: &mut Vec<Epg>, fp: &mut FetchedPlaylist, id_cache
                 }
             };
 
+            // channels with an epg id of the guide are assigned too
+            let matched_count = fp.playlistgroups.iter()
+                .flat_map(|g| &g.channels)
+                .filter(|chan| chan.header.xtream_cluster == XtreamCluster::Live)
+                .filter(|chan| chan.header.epg_channel_id.as_ref().is_some_and(|epg_id| id_cache.processed.contains(epg_id)))
+                .count();
+
             fp.playlistgroups.iter_mut()
                 .flat_map(|g| &mut g.channels)
                 .filter(filter_live)
                 .filter(filter_missing_epg_id)
                 .for_each(assign_values);
+            assigned_count += matched_count;
             new_epg.push(epg);
         }
     }
+    assigned_count
 }
 
 /// Processes a fetched playlist and assigns EPG data to its channels.
 ///
 /// Collects EPG channel IDs from the playlist, initializes an EPG ID cache, and assigns EPG data to channels using normalization and smart matching if enabled. Logs a debug message if no EPG IDs are found and smart matching is disabled.
+/// Returns the number of live channels with an EPG ID from the guide (already matching or newly assigned).
 ///
 /// # Examples
 ///
 /// ```
 /// let mut playlist = FetchedPlaylist::default();
 /// let mut epg_data = Vec::new();
-/// process_playlist_epg(&mut playlist, &mut epg_data);
+/// let assigned = process_playlist_epg(&mut playlist, &mut epg_data);
 /// ```
-pub fn process_playlist_epg(fp: &mut FetchedPlaylist, epg: &mut Vec<Epg>) {
+pub fn process_playlist_epg(fp: &mut FetchedPlaylist, epg: &mut Vec<Epg>) -> usize {
     // collect all epg_channel ids
     let mut id_cache = EpgIdCache::new(fp.input.epg.as_ref());
     id_cache.collect_epg_id(fp);
 
     if id_cache.is_empty() && !id_cache.smart_match_enabled {
         debug!("No epg ids found");
+        0
     } else {
-        assign_channel_epg(epg, fp, &mut id_cache);
+        assign_channel_epg(epg, fp, &mut id_cache)
     }
 }
 
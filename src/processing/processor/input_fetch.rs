@@ -0,0 +1,132 @@
+use std::sync::Arc;
+use std::time::Duration;
+use log::{info, warn};
+use crate::foundation::filter::ValueProvider;
+use crate::m3u_filter_error::M3uFilterError;
+use crate::model::config::{Config, ConfigInput, ConfigInputAlias, InputType};
+use crate::model::playlist::PlaylistGroup;
+use crate::processing::parser::local;
+use crate::utils::network::{hdhomerun, m3u, mapped, stalker, xtream};
+
+pub struct FetchedInputPlaylist {
+    pub playlistgroups: Vec<PlaylistGroup>,
+    pub errors: Vec<M3uFilterError>,
+    /// Name of the input or alias the playlist was fetched from, `None` if all failed.
+    pub source: Option<String>,
+}
+
+async fn fetch_playlist(client: &Arc<reqwest::Client>, cfg: &Config, input: &ConfigInput) -> (Vec<PlaylistGroup>, Vec<M3uFilterError>) {
+    match input.input_type {
+        InputType::M3u => m3u::get_m3u_playlist(Arc::clone(client), cfg, input, &cfg.working_dir).await,
+        InputType::Xtream => xtream::get_xtream_playlist(Arc::clone(client), input, &cfg.working_dir).await,
+        InputType::Local => local::get_local_playlist(cfg, input),
+        InputType::Stalker => stalker::get_stalker_playlist(Arc::clone(client), input).await,
+        InputType::HdHomeRun => hdhomerun::get_hdhomerun_playlist(Arc::clone(client), input).await,
+        InputType::Json | InputType::Csv => mapped::get_mapped_playlist(Arc::clone(client), input, &cfg.working_dir).await,
+        InputType::M3uBatch | InputType::XtreamBatch => (vec![], vec![])
+    }
+}
+
+const fn is_failed_fetch(playlistgroups: &[PlaylistGroup], errors: &[M3uFilterError]) -> bool {
+    playlistgroups.is_empty() && !errors.is_empty()
+}
+
+async fn fetch_playlist_with_retry(client: &Arc<reqwest::Client>, cfg: &Config, input: &ConfigInput, source_name: &str) -> (Vec<PlaylistGroup>, Vec<M3uFilterError>) {
+    let mut attempt = 0;
+    loop {
+        let (playlistgroups, errors) = fetch_playlist(client, cfg, input).await;
+        let retry = match input.retry.as_ref() {
+            Some(retry) if attempt < retry.attempts && is_failed_fetch(&playlistgroups, &errors) => retry,
+            _ => return (playlistgroups, errors),
+        };
+        let delay_secs = retry.get_delay_secs(attempt);
+        attempt += 1;
+        warn!("Failed to fetch input {source_name}, retry {attempt}/{} in {delay_secs} seconds", retry.attempts);
+        tokio::time::sleep(Duration::from_secs(delay_secs)).await;
+    }
+}
+
+/// Fetches the input playlist. Failed downloads are retried, if the input url still fails
+/// the aliases are tried in priority order because they point at the same provider.
+pub async fn fetch_input_playlist(client: &Arc<reqwest::Client>, cfg: &Config, input: &ConfigInput) -> FetchedInputPlaylist {
+    let (playlistgroups, mut errors) = fetch_playlist_with_retry(client, cfg, input, &input.name).await;
+    if !is_failed_fetch(&playlistgroups, &errors) {
+        return FetchedInputPlaylist { playlistgroups, errors, source: Some(input.name.clone()) };
+    }
+
+    if let Some(aliases) = input.aliases.as_ref() {
+        let mut sorted_aliases: Vec<&ConfigInputAlias> = aliases.iter().collect();
+        sorted_aliases.sort_by_key(|alias| alias.priority);
+        for alias in sorted_aliases {
+            info!("Fetching input {} failed, trying alias {}", input.name, alias.name);
+            let alias_input = input.create_alias_input(alias);
+            let (alias_playlistgroups, mut alias_errors) = fetch_playlist_with_retry(client, cfg, &alias_input, &alias.name).await;
+            if !is_failed_fetch(&alias_playlistgroups, &alias_errors) {
+                info!("Fetched input {} from alias {}", input.name, alias.name);
+                return FetchedInputPlaylist { playlistgroups: alias_playlistgroups, errors: alias_errors, source: Some(alias.name.clone()) };
+            }
+            errors.append(&mut alias_errors);
+        }
+    }
+    FetchedInputPlaylist { playlistgroups, errors, source: None }
+}
+
+/// Drops the items which don't match the input `filter`, before the playlist is shared with the targets.
+/// Returns the number of dropped items.
+pub fn filter_input_playlist(input: &ConfigInput, playlistgroups: &mut Vec<PlaylistGroup>) -> usize {
+    if input.t_filter.is_none() {
+        return 0;
+    }
+    let mut filtered_count = 0;
+    for group in playlistgroups.iter_mut() {
+        let channel_count = group.channels.len();
+        group.channels.retain(|pli| input.filter(&ValueProvider { pli }));
+        filtered_count += channel_count - group.channels.len();
+    }
+    playlistgroups.retain(|group| !group.channels.is_empty());
+    filtered_count
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::foundation::filter::get_filter;
+    use crate::model::config::{ConfigInput, ConfigInputRetry};
+    use crate::model::playlist::{PlaylistGroup, PlaylistItem, PlaylistItemHeader, PlaylistItemType, XtreamCluster};
+    use crate::processing::processor::input_fetch::filter_input_playlist;
+
+    #[test]
+    fn test_retry_backoff() {
+        let retry = ConfigInputRetry { attempts: 5, delay_secs: 5, max_delay_secs: 30 };
+        let delays: Vec<u64> = (0..5).map(|attempt| retry.get_delay_secs(attempt)).collect();
+        assert_eq!(delays, vec![5, 10, 20, 30, 30]);
+    }
+
+    fn create_group(title: &str, xtream_cluster: XtreamCluster, names: &[&str]) -> PlaylistGroup {
+        let item_type = PlaylistItemType::from(xtream_cluster);
+        PlaylistGroup {
+            id: 0,
+            title: title.to_string(),
+            channels: names.iter().map(|name| PlaylistItem {
+                header: PlaylistItemHeader { name: (*name).to_string(), group: title.to_string(), item_type, xtream_cluster, ..PlaylistItemHeader::default() },
+            }).collect(),
+            xtream_cluster,
+        }
+    }
+
+    #[test]
+    fn test_filter_input_playlist() {
+        let mut input = ConfigInput::default();
+        let mut playlistgroups = vec![
+            create_group("News", XtreamCluster::Live, &["News 1", "News 2"]),
+            create_group("FR Movies", XtreamCluster::Video, &["Film"]),
+            create_group("Movies", XtreamCluster::Video, &["Movie", "FR Movie"]),
+        ];
+        assert_eq!(filter_input_playlist(&input, &mut playlistgroups), 0);
+
+        input.t_filter = Some(get_filter(r#"NOT(Group ~ "^FR " OR Name ~ "^FR ")"#, None).unwrap());
+        assert_eq!(filter_input_playlist(&input, &mut playlistgroups), 2);
+        assert_eq!(playlistgroups.len(), 2);
+        assert_eq!(playlistgroups[1].channels.len(), 1);
+        assert_eq!(playlistgroups[1].channels[0].header.name, "Movie");
+    }
+}
@@ -4,6 +4,7 @@ mod affix;
 mod xtream_vod;
 mod xtream_series;
 pub mod epg;
+mod input_fetch;
 
 #[macro_export]
 macro_rules! handle_error {
@@ -1,10 +1,9 @@
 use crate::{Config};
 use crate::model::config::{ConfigInput, ConfigRename};
 use crate::utils::network::epg;
-use crate::utils::network::m3u;
-use crate::utils::network::xtream;
+use crate::processing::processor::input_fetch::{fetch_input_playlist, filter_input_playlist, FetchedInputPlaylist};
 use core::cmp::Ordering;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::{Arc};
 use tokio::sync::Mutex;
@@ -14,25 +13,30 @@ use log::{debug, error, info, log_enabled, trace, warn, Level};
 use std::time::Instant;
 use deunicode::deunicode;
 use crate::foundation::filter::{get_field_value, set_field_value, MockValueProcessor, ValueProvider};
-use crate::m3u_filter_error::{M3uFilterError, M3uFilterErrorKind, get_errors_notify_message, notify_err};
+use crate::m3u_filter_error::{M3uFilterError, M3uFilterErrorKind, get_errors_notify_message, info_err, notify_err};
 use crate::messaging::{send_message, MsgKind};
-use crate::model::config::{ConfigSortChannel, ConfigSortGroup, ConfigTarget, InputType,
-                           ItemField, ProcessTargets, ProcessingOrder, SortOrder::{Asc, Desc}};
+use crate::model::config::{ChannelNumberingStrategy, ConfigChannelNumbering, ConfigGroupMerge, ConfigGroupRule, ConfigGroupSplit, GroupSplitBy, ConfigSortChannel, ConfigSortGroup, ConfigSortKey, ConfigTarget, InputType,
+                           ItemField, ProcessTargets, ProcessingOrder, SortCompare, SortOrder, SortOrder::{Asc, Desc}};
 use crate::model::mapping::{CounterModifier, Mapping, MappingValueProcessor};
 use crate::model::playlist::{FetchedPlaylist, FieldGetAccessor, FieldSetAccessor, PlaylistEntry, PlaylistGroup, PlaylistItem, UUIDType, XtreamCluster};
-use crate::model::stats::{InputStats, PlaylistStats, SourceStats, TargetStats};
+use crate::model::stats::{InputStats, PlaylistStats, ProcessingStats, SourceStats, StepStats, TargetStats};
 use crate::processing::processor::affix::apply_affixes;
 use crate::processing::playlist_watch::process_group_watch;
 use crate::processing::processor::xtream_series::playlist_resolve_series;
 use crate::processing::processor::xtream_vod::playlist_resolve_vod;
-use crate::repository::playlist_repository::persist_playlist;
+use crate::repository::playlist_repository::{get_target_chno_mapping, persist_playlist};
+use crate::repository::storage::ensure_target_storage_path;
+use crate::repository::override_repository::{load_overrides, override_uuid, PlaylistItemOverride};
+use crate::repository::stats_repository::save_processing_stats;
 use crate::utils::default_utils::default_as_default;
 use crate::utils::{debug_if_enabled};
+use crate::utils::string_utils::natural_cmp;
 
 use crate::model::xmltv::{Epg};
 use crate::processing::parser::xmltv::flatten_tvguide;
 use crate::processing::processor::epg::process_playlist_epg;
 use crate::utils::step_measure::StepMeasure;
+use crate::tools::lru_cache::LRUResourceCache;
 
 fn is_valid(pli: &PlaylistItem, target: &ConfigTarget) -> bool {
     let provider = ValueProvider { pli };
@@ -40,13 +44,14 @@ fn is_valid(pli: &PlaylistItem, target: &ConfigTarget) -> bool {
 }
 
 #[allow(clippy::unnecessary_wraps)]
-fn filter_playlist(playlist: &mut [PlaylistGroup], target: &ConfigTarget) -> Option<Vec<PlaylistGroup>> {
+fn filter_playlist(playlist: &mut [PlaylistGroup], target: &ConfigTarget, stats: &mut ProcessingStats) -> Option<Vec<PlaylistGroup>> {
     debug!("Filtering {} groups", playlist.len());
     let mut new_playlist = Vec::with_capacity(128);
     for pg in playlist.iter_mut() {
         let channels = pg.channels.iter()
             .filter(|&pli| is_valid(pli, target)).cloned().collect::<Vec<PlaylistItem>>();
         trace!("Filtered group {} has now {}/{} items", pg.title, channels.len(), pg.channels.len());
+        stats.filtered_count += pg.channels.len() - channels.len();
         if !channels.is_empty() {
             new_playlist.push(PlaylistGroup {
                 id: pg.id,
@@ -59,55 +64,81 @@ fn filter_playlist(playlist: &mut [PlaylistGroup], target: &ConfigTarget) -> Opt
     Some(new_playlist)
 }
 
+fn compare_values(value_a: &str, value_b: &str, compare: &SortCompare, order: &SortOrder) -> Ordering {
+    match compare {
+        SortCompare::Text => apply_sort_order(value_a.cmp(value_b), order),
+        SortCompare::Natural => apply_sort_order(natural_cmp(value_a, value_b), order),
+        SortCompare::Numeric => {
+            // `NaN` and `inf` are parsed too, they are handled like values which are not numeric
+            let parse = |value: &str| value.trim().parse::<f64>().ok().filter(|num| num.is_finite());
+            match (parse(value_a), parse(value_b)) {
+                (Some(num_a), Some(num_b)) => apply_sort_order(num_a.total_cmp(&num_b), order),
+                // values which are not numeric are sorted to the end, regardless of the sort order
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => apply_sort_order(value_a.cmp(value_b), order),
+            }
+        }
+    }
+}
+
+fn apply_sort_order(ordering: Ordering, order: &SortOrder) -> Ordering {
+    match order {
+        Asc => ordering,
+        Desc => ordering.reverse()
+    }
+}
+
+fn compare_sequence_index(index_a: Option<usize>, index_b: Option<usize>) -> Option<Ordering> {
+    match (index_a, index_b) {
+        // Both items found in custom order, compare indices
+        (Some(idx_a), Some(idx_b)) => if idx_a == idx_b { None } else { Some(idx_a.cmp(&idx_b)) },
+        // Only 'a' found in custom order, it comes first
+        (Some(_), None) => Some(Ordering::Less),
+        // Only 'b' found in custom order, it comes first
+        (None, Some(_)) => Some(Ordering::Greater),
+        // Neither found, fall back to default ordering
+        (None, None) => None,
+    }
+}
+
 fn playlistgroup_comparator(a: &PlaylistGroup, b: &PlaylistGroup, group_sort: &ConfigSortGroup, match_as_ascii: bool) -> Ordering {
     let value_a = if match_as_ascii { deunicode(&a.title) } else { a.title.to_string() };
     let value_b = if match_as_ascii { deunicode(&b.title) } else { b.title.to_string() };
-    let ordering = value_a.partial_cmp(&value_b).unwrap();
-    match group_sort.order {
-        Asc => ordering,
-        Desc => ordering.reverse()
+    if let Some(sequence) = group_sort.t_re_sequence.as_ref() {
+        let index_a = sequence.iter().position(|re| re.is_match(&value_a));
+        let index_b = sequence.iter().position(|re| re.is_match(&value_b));
+        if let Some(ordering) = compare_sequence_index(index_a, index_b) {
+            return ordering;
+        }
     }
+    compare_values(&value_a, &value_b, &group_sort.compare, &group_sort.order)
 }
 
-fn playlistitem_comparator(a: &PlaylistItem, b: &PlaylistItem, channel_sort: &ConfigSortChannel, match_as_ascii: bool) -> Ordering {
-    let raw_value_a = get_field_value(a, &channel_sort.field);
-    let raw_value_b = get_field_value(b, &channel_sort.field);
+fn playlistitem_key_comparator(a: &PlaylistItem, b: &PlaylistItem, sort_key: &ConfigSortKey, match_as_ascii: bool) -> Ordering {
+    let raw_value_a = get_field_value(a, &sort_key.field);
+    let raw_value_b = get_field_value(b, &sort_key.field);
     let value_a = if match_as_ascii { deunicode(&raw_value_a) } else { raw_value_a };
     let value_b = if match_as_ascii { deunicode(&raw_value_b) } else { raw_value_b };
-    channel_sort.sequence.as_ref().map_or_else(|| {
-        let ordering = value_a.partial_cmp(&value_b).unwrap();
-        match channel_sort.order {
-            Asc => ordering,
-            Desc => ordering.reverse()
-        }
-    }, |custom_order| {
+    if let Some(custom_order) = sort_key.sequence.as_ref() {
         // Check indices in the custom order vector
         let index_a = custom_order.iter().position(|s| s == &value_a);
         let index_b = custom_order.iter().position(|s| s == &value_b);
+        if let Some(ordering) = compare_sequence_index(index_a, index_b) {
+            return ordering;
+        }
+    }
+    compare_values(&value_a, &value_b, &sort_key.compare, &sort_key.order)
+}
 
-        match (index_a, index_b) {
-            (Some(idx_a), Some(idx_b)) => {
-                // Both items found in custom order, compare indices
-                idx_a.cmp(&idx_b)
-            }
-            (Some(_), None) => {
-                // Only 'a' found in custom order, it comes first
-                Ordering::Less
-            }
-            (None, Some(_)) => {
-                // Only 'b' found in custom order, it comes first
-                Ordering::Greater
-            }
-            (None, None) => {
-                // Neither found, fall back to default ordering
-                let ordering = value_a.partial_cmp(&value_b).unwrap();
-                match channel_sort.order {
-                    Asc => ordering,
-                    Desc => ordering.reverse(),
-                }
-            }
+fn playlistitem_comparator(a: &PlaylistItem, b: &PlaylistItem, channel_sort: &ConfigSortChannel, match_as_ascii: bool) -> Ordering {
+    for sort_key in &channel_sort.t_keys {
+        let ordering = playlistitem_key_comparator(a, b, sort_key, match_as_ascii);
+        if ordering != Ordering::Equal {
+            return ordering;
         }
-    })
+    }
+    Ordering::Equal
 }
 
 fn sort_playlist(target: &ConfigTarget, new_playlist: &mut [PlaylistGroup]) {
@@ -149,24 +180,88 @@ fn channel_no_playlist(new_playlist: &mut [PlaylistGroup]) {
     }
 }
 
-fn exec_rename(pli: &mut PlaylistItem, rename: Option<&Vec<ConfigRename>>) {
+async fn persistent_channel_no_playlist(target: &ConfigTarget, cfg: &Config, numbering: &ConfigChannelNumbering,
+                                        new_playlist: &mut [PlaylistGroup]) -> Result<(), M3uFilterError> {
+    let target_path = ensure_target_storage_path(cfg, &target.name)?;
+    let (mut chno_mapping, file_lock) = get_target_chno_mapping(cfg, &target_path).await;
+    // channel numbers given by the provider are kept
+    new_playlist.iter().flat_map(|g| &g.channels)
+        .filter(|c| !c.header.chno.is_empty())
+        .flat_map(|c| c.header.chno.parse::<u32>())
+        .for_each(|chno| chno_mapping.reserve(chno));
+
+    // channels of groups without a range get the numbers outside the configured ranges
+    let unranged_segments = numbering.get_unranged_segments();
+    let range_segments: Vec<[(u32, u32); 1]> = numbering.ranges.iter().map(|range| [(range.start, range.end.unwrap_or(u32::MAX))]).collect();
+    let group_ranges: Vec<&[(u32, u32)]> = new_playlist.iter().map(|group| {
+        numbering.ranges.iter()
+            .position(|range| range.re.as_ref().is_some_and(|re| re.is_match(&group.title)))
+            .map_or(unranged_segments.as_slice(), |range_idx| range_segments[range_idx].as_slice())
+    }).collect();
+
+    // known channels first, otherwise new channels could take their numbers
+    let mut unassigned = vec![];
+    for (group_idx, group) in new_playlist.iter_mut().enumerate() {
+        for (chan_idx, chan) in group.channels.iter_mut().enumerate() {
+            if chan.header.chno.is_empty() {
+                match chno_mapping.get_known(&chan.get_uuid(), group_ranges[group_idx]) {
+                    Some(chno) => chan.header.chno = chno.to_string(),
+                    None => unassigned.push((group_idx, chan_idx)),
+                }
+            }
+        }
+    }
+
+    for (group_idx, chan_idx) in unassigned {
+        let group = &mut new_playlist[group_idx];
+        let chan = &mut group.channels[chan_idx];
+        match chno_mapping.assign(&chan.get_uuid(), group_ranges[group_idx]) {
+            Some(chno) => chan.header.chno = chno.to_string(),
+            None => warn!("No free channel number left in the range of group {} for {}", group.title, chan.header.name),
+        }
+    }
+
+    let result = chno_mapping.persist().map_err(|err| info_err!(err.to_string()));
+    drop(chno_mapping);
+    drop(file_lock);
+    result
+}
+
+async fn assign_channel_no_playlist(target: &ConfigTarget, cfg: &Config, new_playlist: &mut [PlaylistGroup]) -> Result<(), M3uFilterError> {
+    match target.options.as_ref().and_then(|options| options.channel_numbering.as_ref()) {
+        Some(numbering) if numbering.strategy == ChannelNumberingStrategy::Persistent => {
+            persistent_channel_no_playlist(target, cfg, numbering, new_playlist).await
+        }
+        _ => {
+            channel_no_playlist(new_playlist);
+            Ok(())
+        }
+    }
+}
+
+fn exec_rename(pli: &mut PlaylistItem, rename: Option<&Vec<ConfigRename>>) -> bool {
+    let mut renamed = false;
     if let Some(renames) = rename {
         if !renames.is_empty() {
             let result = pli;
             for r in renames {
                 let value = get_field_value(result, &r.field);
                 let cap = r.re.as_ref().unwrap().replace_all(value.as_str(), &r.new_name);
-                if log::log_enabled!(log::Level::Debug) && *value != cap {
-                    debug_if_enabled!("Renamed {}={} to {}", &r.field, value, cap);
+                if *value != cap {
+                    renamed = true;
+                    if log::log_enabled!(log::Level::Debug) {
+                        debug_if_enabled!("Renamed {}={} to {}", &r.field, value, cap);
+                    }
                 }
                 let value = cap.into_owned();
                 set_field_value(result, &r.field, value);
             }
         }
     }
+    renamed
 }
 
-fn rename_playlist(playlist: &mut [PlaylistGroup], target: &ConfigTarget) -> Option<Vec<PlaylistGroup>> {
+fn rename_playlist(playlist: &mut [PlaylistGroup], target: &ConfigTarget, stats: &mut ProcessingStats) -> Option<Vec<PlaylistGroup>> {
     match &target.rename {
         Some(renames) => {
             if !renames.is_empty() {
@@ -181,7 +276,7 @@ fn rename_playlist(playlist: &mut [PlaylistGroup], target: &ConfigTarget) -> Opt
                         }
                     }
 
-                    grp.channels.iter_mut().for_each(|pli| exec_rename(pli, target.rename.as_ref()));
+                    stats.renamed_count += grp.channels.iter_mut().map(|pli| exec_rename(pli, target.rename.as_ref())).filter(|&renamed| renamed).count();
                     new_playlist.push(grp);
                 }
                 return Some(new_playlist);
@@ -227,13 +322,23 @@ fn map_channel(mut channel: PlaylistItem, mapping: &Mapping) -> PlaylistItem {
     channel
 }
 
-fn map_playlist(playlist: &mut [PlaylistGroup], target: &ConfigTarget) -> Option<Vec<PlaylistGroup>> {
+fn map_playlist(playlist: &mut [PlaylistGroup], target: &ConfigTarget, stats: &mut ProcessingStats) -> Option<Vec<PlaylistGroup>> {
     if target.t_mapping.is_some() {
         let new_playlist: Vec<PlaylistGroup> = playlist.iter().map(|playlist_group| {
             let mut grp = playlist_group.clone();
             let mappings = target.t_mapping.as_ref().unwrap();
-            mappings.iter().filter(|&mapping| !mapping.mapper.is_empty()).for_each(|mapping|
-                grp.channels = grp.channels.drain(..).map(|chan| map_channel(chan, mapping)).collect());
+            mappings.iter().filter(|&mapping| !mapping.mapper.is_empty()).for_each(|mapping| {
+                let mut mapped_count = 0;
+                grp.channels = grp.channels.drain(..).map(|chan| {
+                    let original_header = chan.header.clone();
+                    let mapped_chan = map_channel(chan, mapping);
+                    if mapped_chan.header != original_header {
+                        mapped_count += 1;
+                    }
+                    mapped_chan
+                }).collect();
+                stats.add_mapped(&mapping.id, mapped_count);
+            });
             grp
         }).collect();
 
@@ -313,7 +418,7 @@ fn is_target_enabled(target: &ConfigTarget, user_targets: &ProcessTargets) -> bo
     (!user_targets.enabled && target.enabled) || (user_targets.enabled && user_targets.has_target(target.id))
 }
 
-async fn process_source(client: Arc<reqwest::Client>, cfg: Arc<Config>, source_idx: usize, user_targets: Arc<ProcessTargets>) -> (Vec<InputStats>, Vec<TargetStats>, Vec<M3uFilterError>) {
+async fn process_source(client: Arc<reqwest::Client>, resource_cache: Arc<Option<Mutex<LRUResourceCache>>>, cfg: Arc<Config>, source_idx: usize, user_targets: Arc<ProcessTargets>) -> (Vec<InputStats>, Vec<TargetStats>, Vec<M3uFilterError>) {
     let source = cfg.sources.get(source_idx).unwrap();
     let mut errors = vec![];
     let mut input_stats = HashMap::<String, InputStats>::new();
@@ -324,11 +429,11 @@ async fn process_source(client: Arc<reqwest::Client>, cfg: Arc<Config>, source_i
     for input in &source.inputs {
         if is_input_enabled(enabled_inputs, input, &user_targets) {
             let start_time = Instant::now();
-            let (mut playlistgroups, mut error_list) = match input.input_type {
-                InputType::M3u => m3u::get_m3u_playlist(Arc::clone(&client), &cfg, input, &cfg.working_dir).await,
-                InputType::Xtream => xtream::get_xtream_playlist(Arc::clone(&client), input, &cfg.working_dir).await,
-                InputType::M3uBatch | InputType::XtreamBatch => (vec![], vec![])
-            };
+            let FetchedInputPlaylist { mut playlistgroups, errors: mut error_list, source: fetch_source } = fetch_input_playlist(&client, &cfg, input).await;
+            let filtered_count = filter_input_playlist(input, &mut playlistgroups);
+            if filtered_count > 0 {
+                debug!("Input {} filtered {filtered_count} items", input.name);
+            }
             let (tvguide, mut tvguide_errors) = if error_list.is_empty() {
                 epg::get_xmltv(Arc::clone(&client), &cfg, input, &cfg.working_dir).await
             } else {
@@ -356,7 +461,7 @@ async fn process_source(client: Arc<reqwest::Client>, cfg: Arc<Config>, source_i
             }
             let elapsed = start_time.elapsed().as_secs();
             input_stats.insert(input_name.to_string(), create_input_stat(group_count, channel_count, error_list.len(),
-                                                           input.input_type, input_name, elapsed));
+                                                           input.input_type, input_name, elapsed, fetch_source));
         }
     }
     if source_playlists.is_empty() {
@@ -366,12 +471,13 @@ async fn process_source(client: Arc<reqwest::Client>, cfg: Arc<Config>, source_i
         debug_if_enabled!("Source has {} groups", source_playlists.iter().map(|fpl| fpl.playlistgroups.len()).sum::<usize>());
         for target in &source.targets {
             if is_target_enabled(target, &user_targets) {
-                match process_playlist_for_target(Arc::clone(&client), &mut source_playlists, target, &cfg, &mut input_stats, &mut errors).await {
+                let mut processing_stats = ProcessingStats::default();
+                match process_playlist_for_target(Arc::clone(&client), &resource_cache, &mut source_playlists, target, &cfg, &mut input_stats, &mut processing_stats, &mut errors).await {
                     Ok(()) => {
-                        target_stats.push(TargetStats::success(&target.name));
+                        target_stats.push(TargetStats::success(&target.name, processing_stats));
                     }
                     Err(mut err) => {
-                        target_stats.push(TargetStats::failure(&target.name));
+                        target_stats.push(TargetStats::failure(&target.name, processing_stats));
                         errors.append(&mut err);
                     }
                 }
@@ -381,7 +487,7 @@ async fn process_source(client: Arc<reqwest::Client>, cfg: Arc<Config>, source_i
     (input_stats.into_values().collect(), target_stats, errors)
 }
 
-fn create_input_stat(group_count: usize, channel_count: usize, error_count: usize, input_type: InputType, input_name: &str, secs_took: u64) -> InputStats {
+fn create_input_stat(group_count: usize, channel_count: usize, error_count: usize, input_type: InputType, input_name: &str, secs_took: u64, source: Option<String>) -> InputStats {
     InputStats {
         name: input_name.to_string(),
         input_type,
@@ -395,10 +501,11 @@ fn create_input_stat(group_count: usize, channel_count: usize, error_count: usiz
             channel_count: 0,
         },
         secs_took,
+        source,
     }
 }
 
-async fn process_sources(client: Arc<reqwest::Client>, config: Arc<Config>, user_targets: Arc<ProcessTargets>) -> (Vec<SourceStats>, Vec<M3uFilterError>) {
+async fn process_sources(client: Arc<reqwest::Client>, resource_cache: Arc<Option<Mutex<LRUResourceCache>>>, config: Arc<Config>, user_targets: Arc<ProcessTargets>) -> (Vec<SourceStats>, Vec<M3uFilterError>) {
     let mut handle_list = vec![];
     let thread_num = config.threads;
     let process_parallel = thread_num > 1 && config.sources.len() > 1;
@@ -421,12 +528,13 @@ async fn process_sources(client: Arc<reqwest::Client>, config: Arc<Config>, user
         let usr_trgts = user_targets.clone();
         if process_parallel {
             let http_client = Arc::clone(&client);
+            let cache = Arc::clone(&resource_cache);
             let handles = &mut handle_list;
             let process = move || {
                 // TODO better way ?
                 let rt  = tokio::runtime::Runtime::new().unwrap();
                 rt.block_on(async {
-                    let (input_stats, target_stats, mut res_errors) = process_source(Arc::clone(&http_client), cfg, index, usr_trgts).await;
+                    let (input_stats, target_stats, mut res_errors) = process_source(Arc::clone(&http_client), cache, cfg, index, usr_trgts).await;
                     shared_errors.lock().await.append(&mut res_errors);
                     let process_stats = SourceStats::new(input_stats, target_stats);
                     shared_stats.lock().await.push(process_stats);
@@ -437,7 +545,7 @@ async fn process_sources(client: Arc<reqwest::Client>, config: Arc<Config>, user
                 handles.drain(..).for_each(|handle| { let _ = handle.join(); });
             }
         } else {
-            let (input_stats, target_stats, mut res_errors) = process_source(Arc::clone(&client), cfg, index, usr_trgts).await;
+            let (input_stats, target_stats, mut res_errors) = process_source(Arc::clone(&client), Arc::clone(&resource_cache), cfg, index, usr_trgts).await;
             shared_errors.lock().await.append(&mut res_errors);
             let process_stats = SourceStats::new(input_stats, target_stats);
             shared_stats.lock().await.push(process_stats);
@@ -450,7 +558,7 @@ async fn process_sources(client: Arc<reqwest::Client>, config: Arc<Config>, user
     (Arc::try_unwrap(stats).unwrap().into_inner(), Arc::try_unwrap(errors).unwrap().into_inner())
 }
 
-pub type ProcessingPipe = Vec<fn(playlist: &mut [PlaylistGroup], target: &ConfigTarget) -> Option<Vec<PlaylistGroup>>>;
+pub type ProcessingPipe = Vec<fn(playlist: &mut [PlaylistGroup], target: &ConfigTarget, stats: &mut ProcessingStats) -> Option<Vec<PlaylistGroup>>>;
 
 fn get_processing_pipe(target: &ConfigTarget) -> ProcessingPipe {
     match &target.processing_order {
@@ -467,7 +575,8 @@ fn duplicate_hash(item: &PlaylistItem) -> UUIDType {
     item.get_uuid()
 }
 
-fn execute_pipe<'a>(target: &ConfigTarget, pipe: &ProcessingPipe, fpl: &FetchedPlaylist<'a>, duplicates: &mut HashSet<UUIDType>) -> FetchedPlaylist<'a> {
+fn execute_pipe<'a>(target: &ConfigTarget, pipe: &ProcessingPipe, fpl: &FetchedPlaylist<'a>, duplicates: &mut HashSet<UUIDType>,
+                    stats: &mut ProcessingStats) -> FetchedPlaylist<'a> {
     let mut new_fpl = FetchedPlaylist {
         input: fpl.input,
         playlistgroups: fpl.playlistgroups.clone(), // we need to clone, because of multiple target definitions, we cant change the initial playlist.
@@ -476,12 +585,14 @@ fn execute_pipe<'a>(target: &ConfigTarget, pipe: &ProcessingPipe, fpl: &FetchedP
     if target.options.as_ref().is_some_and(|opt| opt.remove_duplicates) {
         for group in &mut new_fpl.playlistgroups {
             // `HashSet::insert`  returns true for first insert, otherweise false
+            let channel_count = group.channels.len();
             group.channels.retain(|item| duplicates.insert(duplicate_hash(item)));
+            stats.duplicate_count += channel_count - group.channels.len();
         }
     }
 
     for f in pipe {
-        if let Some(groups) = f(&mut new_fpl.playlistgroups, target) {
+        if let Some(groups) = f(&mut new_fpl.playlistgroups, target, stats) {
             new_fpl.playlistgroups = groups;
         }
     }
@@ -510,11 +621,194 @@ fn flatten_groups(playlistgroups: Vec<PlaylistGroup>) -> Vec<PlaylistGroup> {
     sort_order
 }
 
+fn set_group_title(group: &mut PlaylistGroup, title: &str) {
+    group.title = title.to_string();
+    for pli in &mut group.channels {
+        pli.header.group = title.to_string();
+    }
+}
+
+fn merge_groups(playlist: Vec<PlaylistGroup>, merge: &ConfigGroupMerge, stats: &mut ProcessingStats) -> Vec<PlaylistGroup> {
+    let mut merged = false;
+    let mut new_playlist = playlist;
+    for group in &mut new_playlist {
+        if group.title != merge.name && merge.t_re.iter().any(|re| re.is_match(&group.title)) {
+            set_group_title(group, &merge.name);
+            stats.regrouped_count += 1;
+            merged = true;
+        }
+    }
+    if merged {
+        flatten_groups(new_playlist)
+    } else {
+        new_playlist
+    }
+}
+
+fn get_alphabet_key(pli: &PlaylistItem) -> String {
+    let caption = get_field_value(pli, &ItemField::Caption);
+    match deunicode(&caption).chars().find(|c| c.is_alphanumeric()) {
+        Some(c) if c.is_ascii_alphabetic() => c.to_ascii_uppercase().to_string(),
+        _ => String::from("#"),
+    }
+}
+
+fn split_group_alphabet(channels: Vec<PlaylistItem>, max_size: Option<usize>) -> Vec<(String, Vec<PlaylistItem>)> {
+    let mut letters: BTreeMap<String, Vec<PlaylistItem>> = BTreeMap::new();
+    for pli in channels {
+        letters.entry(get_alphabet_key(&pli)).or_default().push(pli);
+    }
+    let Some(max) = max_size else {
+        return letters.into_iter().collect();
+    };
+    // consecutive letters are combined into one bucket as long as they fit into max_size
+    let mut buckets: Vec<(String, String, Vec<PlaylistItem>)> = vec![];
+    for (letter, items) in letters {
+        match buckets.last_mut() {
+            Some((_, last, bucket)) if bucket.len() + items.len() <= max => {
+                *last = letter;
+                bucket.extend(items);
+            }
+            _ => buckets.push((letter.clone(), letter, items)),
+        }
+    }
+    buckets.into_iter().map(|(first, last, items)| {
+        let key = if first == last { first } else { format!("{first}-{last}") };
+        (key, items)
+    }).collect()
+}
+
+fn split_group_field(channels: Vec<PlaylistItem>, field: &ItemField) -> Vec<(String, Vec<PlaylistItem>)> {
+    let mut buckets: Vec<(String, Vec<PlaylistItem>)> = vec![];
+    let mut bucket_idx: HashMap<String, usize> = HashMap::new();
+    for pli in channels {
+        let key = get_field_value(&pli, field).trim().to_string();
+        if let Some(&idx) = bucket_idx.get(&key) {
+            buckets[idx].1.push(pli);
+        } else {
+            bucket_idx.insert(key.clone(), buckets.len());
+            buckets.push((key, vec![pli]));
+        }
+    }
+    buckets
+}
+
+fn split_groups(playlist: Vec<PlaylistGroup>, split: &ConfigGroupSplit, stats: &mut ProcessingStats) -> Vec<PlaylistGroup> {
+    let mut new_playlist = Vec::with_capacity(playlist.len());
+    for group in playlist {
+        let matches = split.re.as_ref().is_some_and(|re| re.is_match(&group.title));
+        let oversized = split.max_size.is_none_or(|max| group.channels.len() > max);
+        if !(matches && oversized) {
+            new_playlist.push(group);
+            continue;
+        }
+        let PlaylistGroup { id, title, channels, xtream_cluster } = group;
+        let buckets = match (&split.by, split.field.as_ref()) {
+            (GroupSplitBy::Field, Some(field)) => split_group_field(channels, field),
+            _ => split_group_alphabet(channels, split.max_size),
+        };
+        stats.regrouped_count += 1;
+        for (key, items) in buckets {
+            let sub_title = if key.is_empty() {
+                title.clone()
+            } else {
+                split.template.replace("{group}", &title).replace("{key}", &key)
+            };
+            let mut sub_group = PlaylistGroup { id, title: String::new(), channels: items, xtream_cluster };
+            set_group_title(&mut sub_group, sub_title.trim());
+            new_playlist.push(sub_group);
+        }
+    }
+    // the template can produce already existing group names
+    flatten_groups(new_playlist)
+}
+
+fn apply_group_rules(target: &ConfigTarget, playlist: Vec<PlaylistGroup>, stats: &mut ProcessingStats) -> Vec<PlaylistGroup> {
+    let Some(rules) = target.group_rules.as_ref() else {
+        return playlist;
+    };
+    let mut new_playlist = playlist;
+    for rule in rules {
+        new_playlist = match rule {
+            ConfigGroupRule::Merge(merge) => merge_groups(new_playlist, merge, stats),
+            ConfigGroupRule::Split(split) => split_groups(new_playlist, split, stats),
+            // parents are only meta information for xtream categories, see xtream_write_playlist
+            ConfigGroupRule::Parent(_) => new_playlist,
+        };
+    }
+    new_playlist
+}
+
+fn apply_item_override(pli: &mut PlaylistItem, item_override: &PlaylistItemOverride) {
+    let header = &mut pli.header;
+    if let Some(name) = item_override.name.as_ref() {
+        header.name.clone_from(name);
+    }
+    if let Some(title) = item_override.title.as_ref() {
+        header.title.clone_from(title);
+    }
+    if let Some(logo) = item_override.logo.as_ref() {
+        header.logo.clone_from(logo);
+    }
+    if let Some(epg_channel_id) = item_override.epg_channel_id.as_ref() {
+        header.epg_channel_id = Some(epg_channel_id.clone());
+    }
+}
+
+async fn apply_overrides(target: &ConfigTarget, cfg: &Config, playlist: Vec<PlaylistGroup>,
+                         stats: &mut ProcessingStats, errors: &mut Vec<M3uFilterError>) -> Vec<PlaylistGroup> {
+    let overrides = match load_overrides(cfg, &target.name).await {
+        Ok(overrides) => overrides,
+        Err(err) => {
+            errors.push(err);
+            return playlist;
+        }
+    };
+    if overrides.is_empty() {
+        return playlist;
+    }
+    let override_map: HashMap<&str, &PlaylistItemOverride> = overrides.iter().map(|o| (o.uuid.as_str(), o)).collect();
+    let mut new_playlist = playlist;
+    let mut moved: Vec<(String, PlaylistItem)> = vec![];
+    for group in &mut new_playlist {
+        let channels = std::mem::take(&mut group.channels);
+        for mut pli in channels {
+            let Some(item_override) = override_map.get(override_uuid(pli.header.get_uuid()).as_str()) else {
+                group.channels.push(pli);
+                continue;
+            };
+            stats.overridden_count += 1;
+            if item_override.hidden {
+                continue;
+            }
+            apply_item_override(&mut pli, item_override);
+            match item_override.group.as_ref() {
+                Some(new_group) if *new_group != group.title => moved.push((new_group.clone(), pli)),
+                _ => group.channels.push(pli),
+            }
+        }
+    }
+    for (title, mut pli) in moved {
+        pli.header.group.clone_from(&title);
+        let cluster = pli.header.xtream_cluster;
+        if let Some(group) = new_playlist.iter_mut().find(|g| g.title == title && g.xtream_cluster == cluster) {
+            group.channels.push(pli);
+        } else {
+            let id = new_playlist.iter().map(|g| g.id).max().unwrap_or(0) + 1;
+            new_playlist.push(PlaylistGroup { id, title, channels: vec![pli], xtream_cluster: cluster });
+        }
+    }
+    new_playlist.retain(|group| !group.channels.is_empty());
+    new_playlist
+}
+
 async fn process_playlist_for_target(client: Arc<reqwest::Client>,
+                                     resource_cache: &Arc<Option<Mutex<LRUResourceCache>>>,
                                      playlists: &mut [FetchedPlaylist<'_>],
                                      target: &ConfigTarget,
                                      cfg: &Config,
                                      stats: &mut HashMap<String, InputStats>,
+                                     processing_stats: &mut ProcessingStats,
                                      errors: &mut Vec<M3uFilterError>) -> Result<(), Vec<M3uFilterError>> {
     let pipe = get_processing_pipe(target);
     debug_if_enabled!("Processing order is {}", &target.processing_order);
@@ -524,10 +818,10 @@ async fn process_playlist_for_target(client: Arc<reqwest::Client>,
 
     debug!("Executing processing pipes");
 
-    let mut step = StepMeasure::new("Pipes processed");
+    let mut step = StepMeasure::recording("Pipes processed");
     for provider_fpl in playlists.iter_mut() {
-        let mut processed_fpl = execute_pipe(target, &pipe, provider_fpl, &mut duplicates);
-        playlist_resolve_series(Arc::clone(&client), cfg, target, errors, &pipe, provider_fpl, &mut processed_fpl).await;
+        let mut processed_fpl = execute_pipe(target, &pipe, provider_fpl, &mut duplicates, processing_stats);
+        playlist_resolve_series(Arc::clone(&client), cfg, target, errors, &pipe, processing_stats, provider_fpl, &mut processed_fpl).await;
         playlist_resolve_vod(Arc::clone(&client), cfg, target, errors, &mut processed_fpl).await;
         // stats
         let input_stats = stats.get_mut(&processed_fpl.input.name);
@@ -544,30 +838,36 @@ async fn process_playlist_for_target(client: Arc<reqwest::Client>,
     apply_affixes(&mut processed_fetched_playlists);
 
     step.tick("Processed epg");
-    let (new_epg, new_playlist) = process_epg(&mut processed_fetched_playlists);
+    let (new_epg, new_playlist) = process_epg(&mut processed_fetched_playlists, processing_stats);
 
-    if new_playlist.is_empty() {
+    let result = if new_playlist.is_empty() {
         info!("Playlist is empty: {}", &target.name);
         Ok(())
     } else {
         step.tick("Merged playlists");
-        let mut flat_new_playlist = flatten_groups(new_playlist);
+        let flat_new_playlist = flatten_groups(new_playlist);
+        step.tick("Applied group rules");
+        let mut flat_new_playlist = apply_group_rules(target, flat_new_playlist, processing_stats);
         step.tick("Sorted playlists");
         sort_playlist(target, &mut flat_new_playlist);
         step.tick("Assigned channel number");
-        channel_no_playlist(&mut flat_new_playlist);
+        if let Err(err) = assign_channel_no_playlist(target, cfg, &mut flat_new_playlist).await {
+            errors.push(err);
+        }
         step.tick("Assigned channel counter");
         map_playlist_counter(target, &mut flat_new_playlist);
+        step.tick("Applied overrides");
+        let mut flat_new_playlist = apply_overrides(target, cfg, flat_new_playlist, processing_stats, errors).await;
         step.tick("Processed group watches");
         process_watch(target, cfg, &flat_new_playlist);
         step.tick("Persisting playlists");
-        let result = persist_playlist(&mut flat_new_playlist, flatten_tvguide(&new_epg).as_ref(), target, cfg).await;
-        step.stop();
-        result
-    }
+        persist_playlist(&client, resource_cache, &mut flat_new_playlist, flatten_tvguide(&new_epg).as_ref(), target, cfg).await
+    };
+    processing_stats.steps = step.take_steps().into_iter().map(|(name, duration)| StepStats::new(&name, duration)).collect();
+    result
 }
 
-fn process_epg(processed_fetched_playlists: &mut Vec<FetchedPlaylist>) -> (Vec<Epg>, Vec<PlaylistGroup>) {
+fn process_epg(processed_fetched_playlists: &mut Vec<FetchedPlaylist>, processing_stats: &mut ProcessingStats) -> (Vec<Epg>, Vec<PlaylistGroup>) {
 
     let mut new_playlist = vec![];
     let mut new_epg = vec![];
@@ -575,7 +875,7 @@ fn process_epg(processed_fetched_playlists: &mut Vec<FetchedPlaylist>) -> (Vec<E
     // each fetched playlist can have its own epgl url.
     // we need to process each input epg.
     for fp in processed_fetched_playlists {
-        process_playlist_epg(fp, &mut new_epg);
+        processing_stats.epg_assigned_count += process_playlist_epg(fp, &mut new_epg);
         new_playlist.append(&mut fp.playlistgroups);
     }
     (new_epg, new_playlist)
@@ -596,13 +896,14 @@ fn process_watch(target: &ConfigTarget, cfg: &Config, new_playlist: &Vec<Playlis
     }
 }
 
-pub async fn exec_processing(client: Arc<reqwest::Client>, cfg: Arc<Config>, targets: Arc<ProcessTargets>) {
+pub async fn exec_processing(client: Arc<reqwest::Client>, resource_cache: Arc<Option<Mutex<LRUResourceCache>>>, cfg: Arc<Config>, targets: Arc<ProcessTargets>) {
     let start_time = Instant::now();
-    let (stats, errors) = process_sources(client, cfg.clone(), targets.clone()).await;
+    let (stats, errors) = process_sources(client, resource_cache, cfg.clone(), targets.clone()).await;
     // log errors
     for err in &errors {
         error!("{}", err.message);
     }
+    save_processing_stats(&cfg, &stats, start_time.elapsed().as_secs());
     if let Ok(stats_msg) = serde_json::to_string(&serde_json::Value::Object(serde_json::map::Map::from_iter([("stats".to_string(), serde_json::to_value(stats).unwrap())]))) {
         // print stats
         info!("{stats_msg}");
@@ -621,6 +922,51 @@ pub async fn exec_processing(client: Arc<reqwest::Client>, cfg: Arc<Config>, tar
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use crate::foundation::filter::get_filter;
+    use crate::model::config::{Config, ConfigChannelNumbering, ConfigInput, ConfigTarget, SortCompare, SortOrder};
+    use crate::model::playlist::{FetchedPlaylist, PlaylistGroup, PlaylistItem, PlaylistItemHeader, PlaylistItemType, XtreamCluster};
+    use crate::model::stats::ProcessingStats;
+    use crate::model::xmltv::TVGuide;
+    use super::{compare_values, process_playlist_for_target, split_group_alphabet};
+
+    #[test]
+    fn test_channel_numbering_ranges() {
+        let create_numbering = |ranges: &str| {
+            let mut numbering: ConfigChannelNumbering = serde_json::from_str(&format!(r#"{{"strategy": "persistent", "ranges": {ranges}}}"#)).unwrap();
+            numbering.prepare().map(|()| numbering)
+        };
+        let numbering = create_numbering(r#"[{"group_pattern": "^FR", "start": 200, "end": 299}, {"group_pattern": "^DE", "start": 100, "end": 199}]"#).unwrap();
+        assert_eq!(numbering.get_unranged_segments(), vec![(1, 99), (300, u32::MAX)]);
+        assert!(create_numbering(r#"[{"group_pattern": "^DE", "start": 100, "end": 199}, {"group_pattern": "^FR", "start": 150}]"#).is_err());
+        let numbering = create_numbering(r#"[{"group_pattern": "^DE", "start": 1}]"#).unwrap();
+        assert!(numbering.get_unranged_segments().is_empty());
+    }
+
+    #[test]
+    fn test_split_group_alphabet() {
+        let channels: Vec<PlaylistItem> = ["Alpha", "beta", "Bravo", "Charlie", "3sat", "Zulu"].iter()
+            .map(|name| PlaylistItem { header: PlaylistItemHeader { name: (*name).to_string(), ..Default::default() } })
+            .collect();
+        let keys = |buckets: Vec<(String, Vec<PlaylistItem>)>| buckets.iter()
+            .map(|(key, items)| format!("{key}:{}", items.len())).collect::<Vec<String>>();
+        assert_eq!(keys(split_group_alphabet(channels.clone(), None)), vec!["#:1", "A:1", "B:2", "C:1", "Z:1"]);
+        assert_eq!(keys(split_group_alphabet(channels, Some(3))), vec!["#-A:2", "B-C:3", "Z:1"]);
+    }
+
+    #[test]
+    fn test_compare_numeric_values() {
+        let sorted = |order: SortOrder| {
+            let mut values = vec!["10", "n/a", "2", "", "NaN", "33", "inf"];
+            values.sort_by(|a, b| compare_values(a, b, &SortCompare::Numeric, &order));
+            values
+        };
+        assert_eq!(sorted(SortOrder::Asc), vec!["2", "10", "33", "", "NaN", "inf", "n/a"]);
+        // values which are not numeric stay at the end
+        assert_eq!(sorted(SortOrder::Desc), vec!["33", "10", "2", "n/a", "inf", "NaN", ""]);
+    }
+
     #[test]
     fn test() {
         let data = [("yessport5", "heyessport5gold"), ("yessport5", "heyesport5gold")];
@@ -633,4 +979,38 @@ mod tests {
         // println!("osa distance {:?}", strsim::osa_distance(data.0, data.1));
         // println!("sorensen dice {:?}", strsim::sorensen_dice(data.0, data.1));
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_processing_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        let epg_path = dir.path().join("epg.xml");
+        std::fs::write(&epg_path, r#"<?xml version="1.0" encoding="UTF-8"?><tv><channel id="news.de"><display-name>News</display-name></channel></tv>"#).unwrap();
+        let cfg = Config { working_dir: dir.path().to_string_lossy().to_string(), ..Config::default() };
+        let target = ConfigTarget { name: String::from("stats"), t_filter: Some(get_filter(r#"NOT(Group ~ "^FR ")"#, None).unwrap()), ..ConfigTarget::default() };
+        let input = ConfigInput { name: String::from("input"), ..ConfigInput::default() };
+        let channel = |name: &str, group: &str, epg_channel_id: Option<&str>| PlaylistItem {
+            header: PlaylistItemHeader { name: name.to_string(), title: name.to_string(), group: group.to_string(), url: format!("http://localhost/{name}.ts"),
+                epg_channel_id: epg_channel_id.map(String::from), item_type: PlaylistItemType::Live, xtream_cluster: XtreamCluster::Live, ..PlaylistItemHeader::default() }
+        };
+        let mut playlists = vec![FetchedPlaylist {
+            input: &input,
+            playlistgroups: vec![
+                PlaylistGroup { id: 1, title: String::from("News"), xtream_cluster: XtreamCluster::Live,
+                    channels: vec![channel("News", "News", Some("news.de")), channel("Other", "News", Some("other.de"))] },
+                PlaylistGroup { id: 2, title: String::from("FR News"), xtream_cluster: XtreamCluster::Live, channels: vec![channel("Info", "FR News", None)] },
+            ],
+            epg: Some(TVGuide { file_paths: vec![epg_path] }),
+        }];
+        let mut processing_stats = ProcessingStats::default();
+        let mut errors = vec![];
+        let result = process_playlist_for_target(Arc::new(reqwest::Client::new()), &Arc::new(None), &mut playlists, &target, &cfg,
+                                                 &mut HashMap::new(), &mut processing_stats, &mut errors).await;
+        assert!(result.is_ok());
+        assert_eq!(processing_stats.filtered_count, 1);
+        // the existing epg id matches the guide
+        assert_eq!(processing_stats.epg_assigned_count, 1);
+        let steps: Vec<&str> = processing_stats.steps.iter().map(|step| step.step.as_str()).collect();
+        assert_eq!(steps, vec!["Pipes processed", "Processed affixes", "Processed epg", "Merged playlists", "Applied group rules", "Sorted playlists",
+                               "Assigned channel number", "Assigned channel counter", "Applied overrides", "Processed group watches", "Persisting playlists"]);
+    }
+}
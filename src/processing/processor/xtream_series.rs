@@ -17,6 +17,7 @@ use std::time::Instant;
 use log::{info, log_enabled, Level};
 use crate::model::xtream::{XtreamSeriesEpisode, XtreamSeriesInfoEpisode};
 use crate::model::xtream_const;
+use crate::model::stats::ProcessingStats;
 use crate::utils::bincode_utils::bincode_serialize;
 use crate::utils::file::file_utils::file_writer;
 
@@ -214,9 +215,11 @@ async fn process_series_info(
 }
 
 
+#[allow(clippy::too_many_arguments)]
 pub async fn playlist_resolve_series(client: Arc<reqwest::Client>, cfg: &Config, target: &ConfigTarget,
                                      errors: &mut Vec<M3uFilterError>,
                                      pipe: &ProcessingPipe,
+                                     stats: &mut ProcessingStats,
                                      provider_fpl: &mut FetchedPlaylist<'_>,
                                      processed_fpl: &mut FetchedPlaylist<'_>,
 ) {
@@ -233,7 +236,7 @@ pub async fn playlist_resolve_series(client: Arc<reqwest::Client>, cfg: &Config,
     // run processing pipe over new items
     let mut new_playlist = series_playlist;
     for f in pipe {
-        if let Some(v) = f(&mut new_playlist, target) {
+        if let Some(v) = f(&mut new_playlist, target, stats) {
             new_playlist = v;
         }
     }
@@ -0,0 +1,169 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Error;
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::model::playlist::UUIDType;
+use crate::repository::bplustree::BPlusTree;
+
+// channel numbers of channels which are not seen for this duration are released
+const EXPIRATION_DURATION: i64 = 30 * 86400;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChannelNumberRecord {
+    pub chno: u32,
+    pub uuid: UUIDType,
+    pub last_seen: i64,
+}
+
+impl ChannelNumberRecord {
+    fn new(chno: u32, uuid: UUIDType) -> Self {
+        Self { chno, uuid, last_seen: Local::now().timestamp() }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        (Local::now().timestamp() - self.last_seen) > EXPIRATION_DURATION
+    }
+}
+
+/// Persists the channel numbers assigned to playlist items (by uuid),
+/// so that a channel keeps its number over multiple updates.
+pub struct ChannelNumberMapping {
+    dirty: bool,
+    by_chno: BTreeMap<u32, ChannelNumberRecord>,
+    by_uuid: HashMap<UUIDType, u32>,
+    used: HashSet<u32>,
+    // next candidate per range start, the numbers below are taken
+    cursors: HashMap<u32, u32>,
+    path: PathBuf,
+}
+
+impl ChannelNumberMapping {
+    pub fn new(path: &Path) -> Self {
+        let tree: BPlusTree<u32, ChannelNumberRecord> = BPlusTree::<u32, ChannelNumberRecord>::load(path).unwrap_or_else(|_| BPlusTree::<u32, ChannelNumberRecord>::new());
+        let mut by_chno = BTreeMap::new();
+        let mut by_uuid = HashMap::new();
+        let mut dirty = false;
+        tree.traverse(|_keys, values| {
+            for record in values {
+                if record.is_expired() {
+                    dirty = true;
+                } else {
+                    by_uuid.insert(record.uuid, record.chno);
+                    by_chno.insert(record.chno, record.clone());
+                }
+            }
+        });
+        Self {
+            dirty,
+            by_chno,
+            by_uuid,
+            used: HashSet::new(),
+            cursors: HashMap::new(),
+            path: path.to_path_buf(),
+        }
+    }
+
+    /// Marks a channel number as taken for the current run, e.g. a channel number given by the provider.
+    pub fn reserve(&mut self, chno: u32) {
+        self.used.insert(chno);
+    }
+
+    /// Returns the stored channel number for the uuid if it is still available and inside one of the ranges.
+    pub fn get_known(&mut self, uuid: &UUIDType, ranges: &[(u32, u32)]) -> Option<u32> {
+        let chno = *self.by_uuid.get(uuid)?;
+        if !ranges.iter().any(|&(start, end)| (start..=end).contains(&chno)) || self.used.contains(&chno) {
+            return None;
+        }
+        self.used.insert(chno);
+        if let Some(record) = self.by_chno.get_mut(&chno) {
+            record.last_seen = Local::now().timestamp();
+            self.dirty = true;
+        }
+        Some(chno)
+    }
+
+    fn next_free(&mut self, start: u32, end: u32) -> Option<u32> {
+        let cursor = self.cursors.entry(start).or_insert(start);
+        let chno = (*cursor..=end).find(|chno| !self.used.contains(chno) && !self.by_chno.contains_key(chno));
+        *cursor = chno.unwrap_or(end);
+        chno
+    }
+
+    /// Assigns the next free channel number of the ranges to the uuid, the ranges are tried in order.
+    /// Numbers held by channels which are currently missing stay reserved until they expire.
+    pub fn assign(&mut self, uuid: &UUIDType, ranges: &[(u32, u32)]) -> Option<u32> {
+        let chno = ranges.iter().find_map(|&(start, end)| self.next_free(start, end))?;
+        if let Some(old_chno) = self.by_uuid.insert(*uuid, chno) {
+            self.by_chno.remove(&old_chno);
+        }
+        self.by_chno.insert(chno, ChannelNumberRecord::new(chno, *uuid));
+        self.used.insert(chno);
+        self.dirty = true;
+        Some(chno)
+    }
+
+    pub fn persist(&mut self) -> Result<(), Error> {
+        if self.dirty {
+            let mut tree = BPlusTree::<u32, ChannelNumberRecord>::new();
+            for (chno, record) in &self.by_chno {
+                tree.insert(*chno, record.clone());
+            }
+            tree.store(&self.path)?;
+        }
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+impl Drop for ChannelNumberMapping {
+    fn drop(&mut self) {
+        if let Err(err) = self.persist() {
+            error!("Failed to persist channel number mapping {} err:{err}", self.path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::repository::channel_number_mapping::ChannelNumberMapping;
+
+    #[test]
+    fn test_channel_number_reuse_and_gaps() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chno_mapping.db");
+        let (uuid_a, uuid_b, uuid_c) = ([1u8; 32], [2u8; 32], [3u8; 32]);
+        let all = [(1, u32::MAX)];
+        {
+            let mut mapping = ChannelNumberMapping::new(&path);
+            mapping.reserve(2);
+            assert_eq!(mapping.assign(&uuid_a, &all), Some(1));
+            assert_eq!(mapping.assign(&uuid_b, &all), Some(3));
+            mapping.persist().unwrap();
+        }
+        let mut mapping = ChannelNumberMapping::new(&path);
+        // a new channel inserted before the known ones does not shift them
+        assert_eq!(mapping.get_known(&uuid_c, &all), None);
+        assert_eq!(mapping.get_known(&uuid_b, &all), Some(3));
+        assert_eq!(mapping.assign(&uuid_c, &all), Some(2));
+        // known number outside the group range is not reused
+        assert_eq!(mapping.get_known(&uuid_a, &[(100, 199)]), None);
+        assert_eq!(mapping.assign(&uuid_a, &[(100, 199)]), Some(100));
+    }
+
+    #[test]
+    fn test_channel_number_ranges() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut mapping = ChannelNumberMapping::new(&dir.path().join("chno_mapping.db"));
+        // the numbers of the configured range 3-4 are skipped
+        let unranged = [(1, 2), (5, u32::MAX)];
+        let assigned: Vec<Option<u32>> = (1u8..=4).map(|id| mapping.assign(&[id; 32], &unranged)).collect();
+        assert_eq!(assigned, vec![Some(1), Some(2), Some(5), Some(6)]);
+        assert_eq!(mapping.assign(&[10u8; 32], &[(3, 4)]), Some(3));
+        assert_eq!(mapping.assign(&[11u8; 32], &[(3, 4)]), Some(4));
+        assert_eq!(mapping.assign(&[12u8; 32], &[(3, 4)]), None);
+    }
+}
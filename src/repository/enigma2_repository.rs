@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use log::error;
+use crate::m3u_filter_error::{info_err, notify_err, M3uFilterError, M3uFilterErrorKind};
+use crate::model::api_proxy::{ApiProxyServerInfo, ProxyUserCredentials};
+use crate::model::config::{ClusterFlags, Config, ConfigTarget, Enigma2TargetOutput, M3uTargetOutput};
+use crate::model::playlist::{PlaylistGroup, PlaylistItemType};
+use crate::repository::kodi_repository::get_credentials_and_server_info;
+use crate::repository::m3u_playlist_iterator::m3u_get_rewritten_url;
+use crate::repository::storage_const;
+use crate::utils::file::file_utils;
+
+pub const ENIGMA2_BOUQUETS_FILE: &str = "bouquets.tv";
+const ENIGMA2_BOUQUET_FILE_PREFIX: &str = "userbouquet.";
+const ENIGMA2_BOUQUET_FILE_SUFFIX: &str = ".tv";
+// 4097 is the service type for streams played by the gstreamer media player
+const ENIGMA2_STREAM_SERVICE_TYPE: u32 = 4097;
+
+pub struct Enigma2Service {
+    pub id: u32,
+    pub name: String,
+    pub url: String,
+}
+
+pub struct Enigma2Bouquet {
+    pub name: String,
+    pub services: Vec<Enigma2Service>,
+}
+
+/// The bouquet prefix is used for the bouquet filenames, default is the target name.
+pub fn enigma2_bouquet_prefix(target: &ConfigTarget, target_output: &Enigma2TargetOutput) -> String {
+    let prefix = target_output.bouquet_prefix.as_deref().unwrap_or(&target.name);
+    prefix.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' }).collect()
+}
+
+pub fn enigma2_bouquet_filename(prefix: &str, index: usize) -> String {
+    format!("{ENIGMA2_BOUQUET_FILE_PREFIX}{prefix}_{:03}{ENIGMA2_BOUQUET_FILE_SUFFIX}", index + 1)
+}
+
+fn clean_text(text: &str) -> String {
+    text.replace(['\r', '\n'], " ")
+}
+
+/// Groups the services into bouquets, the order of the first occurrence of a group is kept.
+pub fn enigma2_collect_bouquets<I>(services: I) -> Vec<Enigma2Bouquet>
+where
+    I: IntoIterator<Item=(String, Enigma2Service)>,
+{
+    let mut bouquets: Vec<Enigma2Bouquet> = vec![];
+    let mut bouquet_index: HashMap<String, usize> = HashMap::new();
+    for (group, service) in services {
+        if let Some(&index) = bouquet_index.get(&group) {
+            bouquets[index].services.push(service);
+        } else {
+            bouquet_index.insert(group.clone(), bouquets.len());
+            bouquets.push(Enigma2Bouquet { name: group, services: vec![service] });
+        }
+    }
+    bouquets
+}
+
+pub fn enigma2_create_bouquet(bouquet: &Enigma2Bouquet) -> String {
+    let mut content = String::new();
+    let _ = writeln!(content, "#NAME {}", clean_text(&bouquet.name));
+    for service in &bouquet.services {
+        let name = clean_text(&service.name);
+        // the colon is the field separator of the service reference
+        let url = clean_text(&service.url).replace(':', "%3a");
+        let _ = writeln!(content, "#SERVICE {ENIGMA2_STREAM_SERVICE_TYPE}:0:1:{:X}:0:0:0:0:0:0:{url}:{name}", service.id);
+        let _ = writeln!(content, "#DESCRIPTION {name}");
+    }
+    content
+}
+
+pub fn enigma2_create_bouquets_index(prefix: &str, bouquet_count: usize) -> String {
+    let mut content = String::from("#NAME User - Bouquets (TV)\n");
+    for index in 0..bouquet_count {
+        let _ = writeln!(content, "#SERVICE 1:7:1:0:0:0:0:0:0:0:FROM BOUQUET \"{}\" ORDER BY bouquet", enigma2_bouquet_filename(prefix, index));
+    }
+    content
+}
+
+async fn remove_old_bouquet_files(path: &Path, prefix: &str) {
+    let file_prefix = format!("{ENIGMA2_BOUQUET_FILE_PREFIX}{prefix}_");
+    if let Ok(mut entries) = tokio::fs::read_dir(path).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if file_name.starts_with(&file_prefix) && file_name.ends_with(ENIGMA2_BOUQUET_FILE_SUFFIX) {
+                if let Err(err) = tokio::fs::remove_file(entry.path()).await {
+                    error!("Failed to remove old bouquet file {file_name}: {err}");
+                }
+            }
+        }
+    }
+}
+
+// The same urls as the m3u playlist served to the user, the bouquets of the api are created from it.
+fn get_m3u_stream_url(
+    target_force_redirect: Option<&ClusterFlags>,
+    user_and_server_info: Option<&(ProxyUserCredentials, ApiProxyServerInfo)>,
+    m3u_output: Option<&M3uTargetOutput>,
+    item_type: PlaylistItemType,
+    virtual_id: u32,
+    url: &str,
+) -> String {
+    let (Some((user, server_info)), Some(m3u_output)) = (user_and_server_info, m3u_output) else { return url.to_string(); };
+    let redirect = user.proxy.is_redirect(item_type) || target_force_redirect.is_some_and(|f| f.has_cluster(item_type));
+    if redirect && !m3u_output.mask_redirect_url {
+        return url.to_string();
+    }
+    m3u_get_rewritten_url(&server_info.get_base_url(), &user.username, &user.password, storage_const::M3U_STREAM_PATH,
+                          item_type, virtual_id, m3u_output.include_type_in_url)
+}
+
+async fn write_bouquet_file(path: PathBuf, content: String) -> Result<(), M3uFilterError> {
+    tokio::fs::write(&path, content).await
+        .map_err(|err| notify_err!(format!("failed to write enigma2 bouquet: {} - {err}", path.display())))
+}
+
+pub async fn enigma2_write_playlist(target: &ConfigTarget, target_output: &Enigma2TargetOutput, cfg: &Config, playlist: &[PlaylistGroup]) -> Result<(), M3uFilterError> {
+    if playlist.is_empty() {
+        return Ok(());
+    }
+    let Some(path) = file_utils::get_file_path(&cfg.working_dir, Some(PathBuf::from(&target_output.directory))) else {
+        return Err(info_err!(format!("Failed to get file path for {}", target_output.directory)));
+    };
+    tokio::fs::create_dir_all(&path).await
+        .map_err(|err| notify_err!(format!("Failed to create enigma2 directory {}: {err}", path.display())))?;
+
+    let user_and_server_info = get_credentials_and_server_info(cfg, target_output.username.as_ref()).await;
+    let m3u_output = target.get_m3u_output();
+    let target_force_redirect = target.options.as_ref().and_then(|o| o.force_redirect.as_ref());
+    let bouquets = enigma2_collect_bouquets(playlist.iter()
+        .flat_map(|pg| &pg.channels)
+        .filter(|pli| pli.header.item_type != PlaylistItemType::SeriesInfo)
+        .map(|pli| {
+            let header = &pli.header;
+            let url = get_m3u_stream_url(target_force_redirect, user_and_server_info.as_ref(), m3u_output, header.item_type, header.virtual_id, &header.url);
+            (header.group.clone(), Enigma2Service { id: header.virtual_id, name: header.title.clone(), url })
+        }));
+
+    let prefix = enigma2_bouquet_prefix(target, target_output);
+    remove_old_bouquet_files(&path, &prefix).await;
+    for (index, bouquet) in bouquets.iter().enumerate() {
+        write_bouquet_file(path.join(enigma2_bouquet_filename(&prefix, index)), enigma2_create_bouquet(bouquet)).await?;
+    }
+    write_bouquet_file(path.join(ENIGMA2_BOUQUETS_FILE), enigma2_create_bouquets_index(&prefix, bouquets.len())).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{enigma2_bouquet_filename, enigma2_collect_bouquets, enigma2_create_bouquet, enigma2_create_bouquets_index, Enigma2Service};
+
+    #[test]
+    fn test_enigma2_bouquets() {
+        let service = |id: u32, name: &str| Enigma2Service { id, name: name.to_string(), url: format!("http://localhost:8901/live/user/pass/{id}.ts") };
+        let bouquets = enigma2_collect_bouquets(vec![
+            (String::from("News"), service(26, "News 1")),
+            (String::from("Sports"), service(2, "Sport 1")),
+            (String::from("News"), service(3, "News 2")),
+        ]);
+        assert_eq!(bouquets.len(), 2);
+        assert_eq!(bouquets[0].services.len(), 2);
+
+        let content = enigma2_create_bouquet(&bouquets[0]);
+        assert!(content.starts_with("#NAME News\n"));
+        assert!(content.contains("#SERVICE 4097:0:1:1A:0:0:0:0:0:0:http%3a//localhost%3a8901/live/user/pass/26.ts:News 1\n#DESCRIPTION News 1\n"));
+
+        assert_eq!(enigma2_bouquet_filename("all", 0), "userbouquet.all_001.tv");
+        let index = enigma2_create_bouquets_index("all", 2);
+        assert!(index.contains("FROM BOUQUET \"userbouquet.all_002.tv\" ORDER BY bouquet"));
+    }
+}
@@ -53,7 +53,7 @@ pub fn epg_write(target: &ConfigTarget, cfg: &Config, target_path: &Path, epg: O
                 debug_if_enabled!("writing m3u epg to {}", path.to_str().unwrap_or("?"));
                 epg_write_file(target, epg_data, &path)?;
             }
-            TargetOutput::Strm(_) | TargetOutput::HdHomeRun(_) => {}
+            TargetOutput::Strm(_) | TargetOutput::HdHomeRun(_) | TargetOutput::Xspf(_) | TargetOutput::Pls(_) | TargetOutput::Enigma2(_) | TargetOutput::Json(_) => {}
         }
     }
     Ok(())
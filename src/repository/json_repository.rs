@@ -0,0 +1,244 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::m3u_filter_error::{info_err, notify_err, M3uFilterError, M3uFilterErrorKind};
+use crate::model::api_proxy::{ApiProxyServerInfo, ProxyUserCredentials};
+use crate::model::config::{ClusterFlags, Config, ConfigTarget, JsonTargetOutput};
+use crate::model::playlist::{PlaylistGroup, PlaylistItemHeader, PlaylistItemType, XtreamCluster};
+use crate::repository::kodi_repository::{get_credentials_and_server_info, get_proxy_stream_url};
+use crate::repository::storage_const;
+use crate::utils::file::file_utils;
+use crate::utils::file::file_utils::{file_reader, file_writer};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonPlaylistChannel {
+    pub virtual_id: u32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub provider_id: String,
+    pub name: String,
+    pub title: String,
+    pub chno: String,
+    pub logo: String,
+    pub logo_small: String,
+    pub parent_code: String,
+    pub audio_track: String,
+    pub time_shift: String,
+    pub rec: String,
+    pub catchup: String,
+    pub catchup_days: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub catchup_source: String,
+    pub vlc_opts: Vec<String>,
+    pub kodi_props: Vec<String>,
+    pub epg_channel_id: Option<String>,
+    pub item_type: PlaylistItemType,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub input_name: String,
+    pub url: String,
+}
+
+impl JsonPlaylistChannel {
+    /// Removes the fields which expose the provider, the catchup source contains the provider credentials.
+    fn clear_provider_info(&mut self) {
+        self.provider_id.clear();
+        self.catchup_source.clear();
+        self.input_name.clear();
+    }
+}
+
+impl From<&PlaylistItemHeader> for JsonPlaylistChannel {
+    fn from(header: &PlaylistItemHeader) -> Self {
+        Self {
+            virtual_id: header.virtual_id,
+            provider_id: header.id.clone(),
+            name: header.name.clone(),
+            title: header.title.clone(),
+            chno: header.chno.clone(),
+            logo: header.logo.clone(),
+            logo_small: header.logo_small.clone(),
+            parent_code: header.parent_code.clone(),
+            audio_track: header.audio_track.clone(),
+            time_shift: header.time_shift.clone(),
+            rec: header.rec.clone(),
+            catchup: header.catchup.clone(),
+            catchup_days: header.catchup_days.clone(),
+            catchup_source: header.catchup_source.clone(),
+            vlc_opts: header.vlc_opts.clone(),
+            kodi_props: header.kodi_props.clone(),
+            epg_channel_id: header.epg_channel_id.clone(),
+            item_type: header.item_type,
+            input_name: header.input_name.clone(),
+            url: header.url.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonPlaylistGroup {
+    pub id: u32,
+    pub title: String,
+    pub cluster: XtreamCluster,
+    pub channels: Vec<JsonPlaylistChannel>,
+}
+
+/// The exported document, `page` and `page_size` are only set for paginated requests.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonPlaylist {
+    pub target: String,
+    pub total: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<usize>,
+    pub groups: Vec<JsonPlaylistGroup>,
+}
+
+impl JsonPlaylist {
+    pub fn new(target: &ConfigTarget, groups: Vec<JsonPlaylistGroup>) -> Self {
+        let total = groups.iter().map(|group| group.channels.len()).sum();
+        Self { target: target.name.clone(), total, page: None, page_size: None, groups }
+    }
+
+    /// Keeps only the channels of the page, the page starts with 1. Groups without channels on the page are removed.
+    pub fn paginate(&mut self, page: usize, page_size: usize) {
+        let mut skip = page.saturating_sub(1).saturating_mul(page_size);
+        let mut take = page_size;
+        self.groups.retain_mut(|group| {
+            let skipped = skip.min(group.channels.len());
+            skip -= skipped;
+            let channels: Vec<JsonPlaylistChannel> = group.channels.drain(skipped..).take(take).collect();
+            take -= channels.len();
+            group.channels = channels;
+            !group.channels.is_empty()
+        });
+        self.page = Some(page);
+        self.page_size = Some(page_size);
+    }
+
+    /// Replaces the provider urls with the stream urls of the user and removes the provider information.
+    pub fn rewrite_urls<F>(&mut self, get_stream_url: F)
+    where
+        F: Fn(&JsonPlaylistChannel) -> String,
+    {
+        for channel in self.groups.iter_mut().flat_map(|group| group.channels.iter_mut()) {
+            channel.url = get_stream_url(channel);
+            channel.clear_provider_info();
+        }
+    }
+
+    /// Replaces the provider urls with the xtream stream urls of the user, redirected streams keep the provider url.
+    pub fn rewrite_xtream_urls(&mut self, target_force_redirect: Option<&ClusterFlags>,
+                               user_and_server_info: Option<&(ProxyUserCredentials, ApiProxyServerInfo)>) {
+        self.rewrite_urls(|channel| get_proxy_stream_url(target_force_redirect, user_and_server_info, channel.item_type, channel.virtual_id, &channel.url));
+    }
+}
+
+fn json_get_file_path(target_path: &Path) -> PathBuf {
+    target_path.join(storage_const::FILE_JSON_PLAYLIST)
+}
+
+fn json_create_groups(playlist: &[PlaylistGroup]) -> Vec<JsonPlaylistGroup> {
+    playlist.iter().map(|group| JsonPlaylistGroup {
+        id: group.id,
+        title: group.title.clone(),
+        cluster: group.xtream_cluster,
+        channels: group.channels.iter()
+            .filter(|pli| pli.header.item_type != PlaylistItemType::SeriesInfo)
+            .map(|pli| JsonPlaylistChannel::from(&pli.header)).collect(),
+    }).filter(|group| !group.channels.is_empty()).collect()
+}
+
+fn write_json_file<T: Serialize>(path: &Path, value: &T) -> Result<(), M3uFilterError> {
+    let file = File::create(path).map_err(|err| notify_err!(format!("failed to write json playlist: {} - {err}", path.display())))?;
+    let mut writer = file_writer(&file);
+    serde_json::to_writer(&mut writer, value).map_err(|err| notify_err!(format!("failed to write json playlist: {} - {err}", path.display())))?;
+    writer.flush().map_err(|err| notify_err!(format!("failed to write json playlist: {} - {err}", path.display())))
+}
+
+/// Stores the groups with the provider urls for the api, if `filename` is given the document is exported with the urls of `username`.
+pub async fn json_write_playlist(cfg: &Config, target: &ConfigTarget, target_output: &JsonTargetOutput,
+                                 target_path: &Path, playlist: &[PlaylistGroup]) -> Result<(), M3uFilterError> {
+    if playlist.is_empty() {
+        return Ok(());
+    }
+    let groups = json_create_groups(playlist);
+    let json_path = json_get_file_path(target_path);
+    {
+        let _file_lock = cfg.file_locks.write_lock(&json_path).await;
+        write_json_file(&json_path, &groups)?;
+    }
+
+    if let Some(filename) = target_output.filename.as_ref() {
+        let Some(export_path) = file_utils::get_file_path(&cfg.working_dir, Some(PathBuf::from(filename))) else {
+            return Err(info_err!(format!("Failed to get file path for {filename}")));
+        };
+        let user_and_server_info = get_credentials_and_server_info(cfg, target_output.username.as_ref()).await;
+        let mut document = JsonPlaylist::new(target, groups);
+        document.rewrite_xtream_urls(target.options.as_ref().and_then(|o| o.force_redirect.as_ref()), user_and_server_info.as_ref());
+        write_json_file(&export_path, &document)?;
+    }
+    Ok(())
+}
+
+/// Loads the stored playlist of the target, the urls are the provider urls.
+pub async fn json_load_playlist(cfg: &Config, target: &ConfigTarget) -> Result<JsonPlaylist, M3uFilterError> {
+    let target_path = crate::repository::storage::get_target_storage_path(cfg, &target.name)
+        .ok_or_else(|| info_err!(format!("Could not find path for target {}", target.name)))?;
+    let json_path = json_get_file_path(&target_path);
+    let _file_lock = cfg.file_locks.read_lock(&json_path).await;
+    let file = File::open(&json_path).map_err(|err| info_err!(format!("Could not open json playlist {} - {err}", json_path.display())))?;
+    let groups: Vec<JsonPlaylistGroup> = serde_json::from_reader(file_reader(file))
+        .map_err(|err| info_err!(format!("Could not deserialize json playlist {} - {err}", json_path.display())))?;
+    Ok(JsonPlaylist::new(target, groups))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::config::ConfigTarget;
+    use crate::model::playlist::{PlaylistGroup, PlaylistItem, PlaylistItemHeader, XtreamCluster};
+    use super::{json_create_groups, JsonPlaylist};
+
+    #[test]
+    fn test_json_playlist_paginate() {
+        let group = |id: u32, title: &str, virtual_ids: &[u32]| PlaylistGroup {
+            id, title: title.to_string(), xtream_cluster: XtreamCluster::Live,
+            channels: virtual_ids.iter().map(|&virtual_id| PlaylistItem {
+                header: PlaylistItemHeader { virtual_id, name: format!("Channel {virtual_id}"), ..PlaylistItemHeader::default() }
+            }).collect(),
+        };
+        let playlist = vec![group(1, "News", &[1, 2, 3]), group(2, "Sports", &[4, 5])];
+        let mut document = JsonPlaylist::new(&ConfigTarget::default(), json_create_groups(&playlist));
+        assert_eq!(document.total, 5);
+
+        document.paginate(2, 2);
+        assert_eq!(document.groups.len(), 2);
+        assert_eq!(document.groups[0].channels.iter().map(|c| c.virtual_id).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(document.groups[1].channels.iter().map(|c| c.virtual_id).collect::<Vec<_>>(), vec![4]);
+        assert_eq!(document.total, 5);
+
+        let mut document = JsonPlaylist::new(&ConfigTarget::default(), json_create_groups(&playlist));
+        document.paginate(4, 2);
+        assert!(document.groups.is_empty());
+    }
+
+    #[test]
+    fn test_json_playlist_rewrite_urls_removes_provider_info() {
+        let playlist = vec![PlaylistGroup {
+            id: 1, title: "News".to_string(), xtream_cluster: XtreamCluster::Live,
+            channels: vec![PlaylistItem {
+                header: PlaylistItemHeader {
+                    virtual_id: 1, id: "101".to_string(), input_name: "provider".to_string(),
+                    url: "http://provider/live/user/pass/101.ts".to_string(),
+                    catchup_source: "http://provider/timeshift/user/pass/{duration}/{Y}-{m}-{d}:{H}-{M}/101.ts".to_string(),
+                    ..PlaylistItemHeader::default()
+                }
+            }],
+        }];
+        let mut document = JsonPlaylist::new(&ConfigTarget::default(), json_create_groups(&playlist));
+        document.rewrite_urls(|channel| format!("http://localhost/m3u-stream/u/p/{}", channel.virtual_id));
+        let json = serde_json::to_string(&document).unwrap();
+        assert!(!json.contains("provider"));
+        assert!(json.contains("http://localhost/m3u-stream/u/p/1"));
+    }
+}
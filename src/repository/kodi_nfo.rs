@@ -0,0 +1,221 @@
+use std::fmt::Write as _;
+use quick_xml::escape::escape;
+use serde_json::{Map, Value};
+use crate::model::xtream_const;
+use crate::utils::json_utils::{get_string_from_serde_value, get_u32_from_serde_value};
+
+const NFO_XML_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#;
+
+/// Metadata of a movie, series or episode for the nfo files read by kodi and jellyfin.
+/// The values are collected from the xtream properties of the playlist items.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct KodiNfo {
+    pub title: String,
+    pub show_title: String,
+    pub season: Option<u32>,
+    pub episode: Option<u32>,
+    pub plot: String,
+    pub cast: Vec<String>,
+    pub director: Vec<String>,
+    pub genre: Vec<String>,
+    pub rating: Option<f64>,
+    pub year: Option<u32>,
+    pub premiered: String,
+    pub tmdb_id: Option<u32>,
+    pub poster: String,
+    pub fanart: String,
+}
+
+fn get_prop(props: &Map<String, Value>, fields: &[&str]) -> String {
+    fields.iter()
+        .find_map(|field| props.get(*field).and_then(get_string_from_serde_value))
+        .map_or_else(String::new, |value| value.trim().to_string())
+}
+
+fn get_prop_list(props: &Map<String, Value>, fields: &[&str]) -> Vec<String> {
+    get_prop(props, fields).split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn get_prop_u32(props: &Map<String, Value>, fields: &[&str]) -> Option<u32> {
+    fields.iter().find_map(|field| props.get(*field).and_then(get_u32_from_serde_value)).filter(|value| *value > 0)
+}
+
+fn get_rating(props: &Map<String, Value>) -> Option<f64> {
+    match props.get("rating")? {
+        Value::Number(value) => value.as_f64(),
+        Value::String(value) => value.trim().parse::<f64>().ok(),
+        _ => None,
+    }.filter(|rating| *rating > 0_f64)
+}
+
+fn get_backdrop(props: &Map<String, Value>) -> String {
+    match props.get(xtream_const::XC_PROP_BACKDROP_PATH) {
+        Some(Value::Array(paths)) => paths.iter().find_map(get_string_from_serde_value).unwrap_or_default(),
+        Some(value) => get_string_from_serde_value(value).unwrap_or_default(),
+        None => String::new(),
+    }
+}
+
+fn get_year(props: &Map<String, Value>, release_date: &str) -> Option<u32> {
+    get_prop_u32(props, &["year"])
+        .or_else(|| release_date.get(0..4).and_then(|year| year.parse::<u32>().ok()))
+        .filter(|year| *year >= 1900)
+}
+
+fn write_tag(content: &mut String, tag: &str, value: &str) {
+    if !value.is_empty() {
+        let _ = writeln!(content, "  <{tag}>{}</{tag}>", escape(value));
+    }
+}
+
+impl KodiNfo {
+    /// Movie metadata from the properties of a vod stream, merged with the stored vod info.
+    pub fn movie(name: &str, props: &Map<String, Value>) -> Self {
+        let premiered = get_prop(props, &["release_date", "releasedate", "releaseDate"]);
+        let title = get_prop(props, &["name", "title"]);
+        Self {
+            title: if title.is_empty() { name.to_string() } else { title },
+            plot: get_prop(props, &["plot", "description"]),
+            cast: get_prop_list(props, &["cast", "actors"]),
+            director: get_prop_list(props, &["director"]),
+            genre: get_prop_list(props, &["genre"]),
+            rating: get_rating(props),
+            year: get_year(props, &premiered),
+            premiered,
+            tmdb_id: get_prop_u32(props, &[xtream_const::XC_TAG_VOD_INFO_TMDB_ID, "tmdb"]),
+            poster: get_prop(props, &["movie_image", "cover_big", xtream_const::XC_PROP_COVER, "stream_icon"]),
+            fanart: get_backdrop(props),
+            ..Self::default()
+        }
+    }
+
+    /// Series metadata from the properties of an episode, the series values are prefixed with `series_`.
+    pub fn series(props: &Map<String, Value>) -> Self {
+        let premiered = get_prop(props, &["series_release_date"]);
+        Self {
+            title: get_prop(props, &["series_name"]),
+            plot: get_prop(props, &["series_plot"]),
+            cast: get_prop_list(props, &["cast"]),
+            director: get_prop_list(props, &["director"]),
+            genre: get_prop_list(props, &["series_genre"]),
+            rating: get_rating(props),
+            year: get_year(props, &premiered),
+            premiered,
+            poster: get_prop(props, &["series_cover"]),
+            fanart: get_backdrop(props),
+            ..Self::default()
+        }
+    }
+
+    /// Episode metadata from the properties of an episode.
+    pub fn episode(title: &str, props: &Map<String, Value>) -> Self {
+        let episode_title = get_prop(props, &["title"]);
+        Self {
+            title: if episode_title.is_empty() { title.to_string() } else { episode_title },
+            show_title: get_prop(props, &["series_name"]),
+            season: get_prop_u32(props, &["season"]),
+            episode: get_prop_u32(props, &["episode"]),
+            plot: get_prop(props, &["plot"]),
+            premiered: get_prop(props, &["release_date"]),
+            tmdb_id: get_prop_u32(props, &["tmdb_id"]),
+            poster: get_prop(props, &[xtream_const::XC_PROP_COVER]),
+            ..Self::default()
+        }
+    }
+
+    pub fn to_movie_nfo(&self) -> String {
+        self.to_nfo("movie", "premiered")
+    }
+
+    pub fn to_tvshow_nfo(&self) -> String {
+        self.to_nfo("tvshow", "premiered")
+    }
+
+    pub fn to_episode_nfo(&self) -> String {
+        self.to_nfo("episodedetails", "aired")
+    }
+
+    fn to_nfo(&self, root: &str, date_tag: &str) -> String {
+        let mut content = String::new();
+        write_tag(&mut content, "title", &self.title);
+        write_tag(&mut content, "showtitle", &self.show_title);
+        if let Some(season) = self.season {
+            let _ = writeln!(content, "  <season>{season}</season>");
+        }
+        if let Some(episode) = self.episode {
+            let _ = writeln!(content, "  <episode>{episode}</episode>");
+        }
+        write_tag(&mut content, "plot", &self.plot);
+        if let Some(year) = self.year {
+            let _ = writeln!(content, "  <year>{year}</year>");
+        }
+        write_tag(&mut content, date_tag, &self.premiered);
+        if let Some(rating) = self.rating {
+            let _ = writeln!(content, "  <rating>{rating:.1}</rating>");
+        }
+        self.genre.iter().for_each(|genre| write_tag(&mut content, "genre", genre));
+        self.director.iter().for_each(|director| write_tag(&mut content, "director", director));
+        for actor in &self.cast {
+            let _ = writeln!(content, "  <actor>\n    <name>{}</name>\n  </actor>", escape(actor));
+        }
+        if let Some(tmdb_id) = self.tmdb_id {
+            let _ = writeln!(content, "  <uniqueid type=\"tmdb\" default=\"true\">{tmdb_id}</uniqueid>");
+        }
+        if !self.poster.is_empty() {
+            let aspect = if self.episode.is_some() { "thumb" } else { "poster" };
+            let _ = writeln!(content, "  <thumb aspect=\"{aspect}\">{}</thumb>", escape(&self.poster));
+        }
+        if !self.fanart.is_empty() {
+            let _ = writeln!(content, "  <fanart>\n    <thumb>{}</thumb>\n  </fanart>", escape(&self.fanart));
+        }
+        format!("{NFO_XML_HEADER}\n<{root}>\n{content}</{root}>\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map, Value};
+    use crate::repository::kodi_nfo::KodiNfo;
+
+    fn to_props(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(props) => props,
+            _ => Map::new(),
+        }
+    }
+
+    #[test]
+    fn test_kodi_nfo() {
+        let movie = KodiNfo::movie("Movie", &to_props(json!({
+            "name": "The Movie & Friends", "plot": "A plot", "cast": "Actor One, Actor Two", "genre": "Drama",
+            "rating": "7.5", "releasedate": "2021-05-01", "tmdb_id": "1234", "movie_image": "http://img.net/poster.jpg",
+            "backdrop_path": ["http://img.net/fanart.jpg"]
+        })));
+        assert_eq!(movie.title, "The Movie & Friends");
+        assert_eq!(movie.cast, vec!["Actor One".to_string(), "Actor Two".to_string()]);
+        assert_eq!(movie.year, Some(2021));
+        assert_eq!(movie.tmdb_id, Some(1234));
+        let nfo = movie.to_movie_nfo();
+        assert!(nfo.contains("<movie>\n  <title>The Movie &amp; Friends</title>"));
+        assert!(nfo.contains("<rating>7.5</rating>"));
+        assert!(nfo.contains("<uniqueid type=\"tmdb\" default=\"true\">1234</uniqueid>"));
+        assert!(nfo.contains("<fanart>\n    <thumb>http://img.net/fanart.jpg</thumb>\n  </fanart>"));
+
+        let episode_props = to_props(json!({
+            "series_name": "Show", "series_release_date": "2019-01-01", "series_plot": "Show plot", "title": "Pilot",
+            "season": 1, "episode": 2, "plot": "Episode plot", "rating": 8, "tmdb_id": 0
+        }));
+        let series = KodiNfo::series(&episode_props);
+        assert_eq!(series.title, "Show");
+        assert_eq!(series.year, Some(2019));
+        assert!(series.to_tvshow_nfo().contains("<plot>Show plot</plot>"));
+        let episode = KodiNfo::episode("Show S01E02", &episode_props);
+        assert_eq!(episode.tmdb_id, None);
+        let nfo = episode.to_episode_nfo();
+        assert!(nfo.contains("<episodedetails>\n  <title>Pilot</title>\n  <showtitle>Show</showtitle>\n  <season>1</season>\n  <episode>2</episode>"));
+    }
+}
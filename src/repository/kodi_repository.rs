@@ -3,27 +3,39 @@ use crate::m3u_filter_error::{M3uFilterError, M3uFilterErrorKind};
 use crate::model::api_proxy::{ApiProxyServerInfo, ProxyUserCredentials};
 use crate::model::config::{ClusterFlags, Config, ConfigTarget, StrmTargetOutput};
 use crate::model::playlist::{
-    FieldGetAccessor, PlaylistGroup, PlaylistItem, PlaylistItemType, UUIDType,
+    FieldGetAccessor, PlaylistGroup, PlaylistItem, PlaylistItemType, UUIDType, XtreamCluster,
 };
 use crate::model::xtream::XtreamSeriesEpisode;
+use crate::model::xtream_const;
 use crate::repository::bplustree::BPlusTree;
+use crate::repository::kodi_nfo::KodiNfo;
 use crate::repository::storage::{ensure_target_storage_path, get_input_storage_path, hash_bytes};
 use crate::repository::storage_const;
-use crate::repository::xtream_repository::{xtream_get_record_file_path, InputVodInfoRecord};
+use crate::repository::xtream_repository::{xtream_get_input_info, xtream_get_record_file_path, InputVodInfoRecord};
+use crate::tools::lru_cache::LRUResourceCache;
 use crate::utils::constants::{KodiStyle, CONSTANTS};
 use crate::utils::file::file_lock_manager::FileReadGuard;
 use crate::utils::file::file_utils;
-use crate::utils::network::request::extract_extension_from_url;
-use chrono::Datelike;
+use crate::utils::network::request::{extract_extension_from_url, sanitize_sensitive_info};
+use chrono::{Datelike, Local};
+use futures::StreamExt;
 use filetime::{set_file_times, FileTime};
-use log::{error, trace};
+use log::{debug, error, trace};
 use regex::Regex;
 use serde::Serialize;
+use serde_json::{Map, Value};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::fs::{create_dir_all, remove_dir, remove_file, File};
 use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, BufWriter};
+use tokio::sync::Mutex;
+
+const ARTWORK_DOWNLOAD_CONCURRENCY: usize = 8;
+const ARTWORK_DOWNLOAD_TIMEOUT_SECS: u64 = 30;
+// failed downloads are retried after 7 days
+const ARTWORK_RETRY_SECS: i64 = 7 * 24 * 60 * 60;
 
 fn sanitize_for_filename(text: &str, underscore_whitespace: bool) -> String {
     text.trim()
@@ -349,6 +361,7 @@ struct StrmItemInfo {
     season: Option<String>,
     episode: Option<String>,
     added: Option<u64>,
+    additional_properties: Option<Map<String, Value>>,
 }
 
 impl StrmItemInfo {
@@ -357,7 +370,7 @@ impl StrmItemInfo {
     }
 }
 
-fn extract_item_info(pli: &mut PlaylistItem) -> StrmItemInfo {
+fn extract_item_info(pli: &mut PlaylistItem, with_properties: bool) -> StrmItemInfo {
     let header = &mut pli.header;
     let group = header.group.to_string();
     let title = header.title.to_string();
@@ -388,6 +401,11 @@ fn extract_item_info(pli: &mut PlaylistItem) -> StrmItemInfo {
         }
         _ => (None, None, None, None, None),
     };
+    // the properties are only needed for the nfo files and artwork
+    let additional_properties = match header.additional_properties.as_ref() {
+        Some(Value::Object(props)) if with_properties => Some(props.clone()),
+        _ => None,
+    };
     StrmItemInfo {
         group,
         title,
@@ -401,6 +419,7 @@ fn extract_item_info(pli: &mut PlaylistItem) -> StrmItemInfo {
         season,
         episode,
         added,
+        additional_properties,
     }
 }
 
@@ -500,6 +519,8 @@ fn get_relative_path_str(full_path: &Path, root_path: &Path) -> String {
 struct StrmFile {
     file_name: Arc<String>,
     dir_path: PathBuf,
+    // the series directory of kodi style episodes
+    series_dir_path: Option<PathBuf>,
     strm_info: StrmItemInfo,
 }
 
@@ -507,9 +528,11 @@ async fn prepare_strm_files(
     cfg: &Config,
     new_playlist: &mut [PlaylistGroup],
     root_path: &Path,
-    underscore_whitespace: bool,
-    kodi_style: bool,
+    target_output: &StrmTargetOutput,
 ) -> Vec<StrmFile> {
+    let underscore_whitespace = target_output.underscore_whitespace;
+    let kodi_style = target_output.kodi_style;
+    let with_properties = target_output.nfo || target_output.artwork;
     let channel_count = new_playlist
         .iter()
         .map(|g| g.filter_count(filter_strm_item))
@@ -524,7 +547,7 @@ async fn prepare_strm_files(
     // first we create the names to identify name collisions
     for pg in new_playlist.iter_mut() {
         for pli in pg.channels.iter_mut().filter(|c| filter_strm_item(c)) {
-            let strm_item_info = extract_item_info(pli);
+            let strm_item_info = extract_item_info(pli, with_properties);
             let (dir_path, strm_file_name) = if kodi_style {
                 kodi_style_rename(
                     cfg,
@@ -547,14 +570,37 @@ async fn prepare_strm_files(
                 collisions.insert(Arc::clone(&filename));
             }
             all_filenames.insert(Arc::clone(&filename));
+            // kodi style episodes are stored in `<group>/<series>/<season>`
+            let series_dir_path = (kodi_style && strm_item_info.item_type == PlaylistItemType::Series && strm_item_info.series_name.is_some())
+                .then(|| dir_path.iter().take(2).collect::<PathBuf>());
             result.push(StrmFile {
                 file_name: Arc::clone(&filename),
                 dir_path,
+                series_dir_path,
                 strm_info: strm_item_info,
             });
         }
     }
 
+    // without kodi style the episodes are stored in `<group>`, the group directory only gets
+    // the series files if it contains the episodes of one series
+    if !kodi_style {
+        let mut series_by_dir: HashMap<&PathBuf, Option<&str>> = HashMap::new();
+        for strm_file in &result {
+            let strm_info = &strm_file.strm_info;
+            let series_name = if strm_info.item_type == PlaylistItemType::Series { strm_info.series_name.as_deref() } else { None };
+            series_by_dir.entry(&strm_file.dir_path)
+                .and_modify(|name| if *name != series_name { *name = None; })
+                .or_insert(series_name);
+        }
+        let series_dirs: HashSet<PathBuf> = series_by_dir.into_iter()
+            .filter_map(|(dir_path, series_name)| series_name.map(|_| dir_path.clone()))
+            .collect();
+        for strm_file in result.iter_mut().filter(|s| series_dirs.contains(&s.dir_path)) {
+            strm_file.series_dir_path = Some(strm_file.dir_path.clone());
+        }
+    }
+
     if !collisions.is_empty() {
         let separator = if underscore_whitespace { "_" } else { " " };
         result
@@ -571,6 +617,8 @@ async fn prepare_strm_files(
 }
 
 pub async fn kodi_write_strm_playlist(
+    client: &Arc<reqwest::Client>,
+    resource_cache: &Arc<Option<Mutex<LRUResourceCache>>>,
     target: &ConfigTarget,
     target_output: &StrmTargetOutput,
     cfg: &Config,
@@ -591,8 +639,9 @@ pub async fn kodi_write_strm_playlist(
     };
 
     let user_and_server_info = get_credentials_and_server_info(cfg, target_output.username.as_ref()).await;
-    let strm_index_path =
-        strm_get_file_paths(&ensure_target_storage_path(cfg, target.name.as_str())?);
+    let target_path = ensure_target_storage_path(cfg, target.name.as_str())?;
+    let strm_index_path = strm_get_file_paths(&target_path);
+    let mut artwork = ArtworkDownloads::new(&target_path.join(storage_const::FILE_STRM_ARTWORK_FAILED));
     let existing_strm = {
         let _file_lock = cfg
             .file_locks
@@ -619,48 +668,28 @@ pub async fn kodi_write_strm_playlist(
         cfg,
         new_playlist,
         &root_path,
-        target_output.underscore_whitespace,
-        target_output.kodi_style,
+        target_output,
     ).await;
     for strm_file in strm_files {
         // file paths
         let output_path = root_path.join(&strm_file.dir_path);
         let file_path = output_path.join(format!("{}.strm", strm_file.file_name));
-        let file_exists = file_path.exists();
-        let relative_file_path = get_relative_path_str(&file_path, &root_path);
 
         // create content
         let url = get_strm_url(target_force_redirect, user_and_server_info.as_ref(), &strm_file.strm_info);
         let mut content = target_output.strm_props.as_ref().map_or_else(Vec::new, std::clone::Clone::clone);
         content.push(url);
         let content_text = content.join("\r\n");
-        let content_as_bytes = content_text.as_bytes();
-        let content_hash = hash_bytes(content_as_bytes);
-
-        // check if file exists and has same hash
-        if file_exists && has_strm_file_same_hash(&file_path, content_hash).await {
-            processed_strm.insert(relative_file_path);
-            continue; // skip creation
-        }
 
-        // if we cant create the directory skip this entry
-        if !ensure_strm_file_directory(&mut failed, &output_path).await {
-            continue;
+        let mut output = StrmOutput { root_path: &root_path, processed: &mut processed_strm, failed: &mut failed, artwork: &mut artwork };
+        if output.write_file(&file_path, content_text.as_bytes(), strm_file.strm_info.get_file_ts()).await {
+            kodi_write_metadata(client, cfg, target_output, &strm_file, &mut output).await;
         }
+    }
 
-        match write_strm_file(
-            &file_path,
-            content_as_bytes,
-            strm_file.strm_info.get_file_ts(),
-        ).await
-        {
-            Ok(()) => {
-                processed_strm.insert(relative_file_path);
-            }
-            Err(err) => {
-                failed.push(err);
-            }
-        };
+    artwork.download(resource_cache, &root_path, &mut processed_strm).await;
+    if let Err(err) = artwork.store() {
+        failed.push(err);
     }
 
     if let Err(err) = write_strm_index_file(cfg, &processed_strm, &strm_index_path).await {
@@ -679,6 +708,232 @@ pub async fn kodi_write_strm_playlist(
         Err(info_err!(failed.join(", ")))
     }
 }
+/// Tracks the written files of the strm output, files which are not processed are removed on cleanup.
+struct StrmOutput<'a> {
+    root_path: &'a Path,
+    processed: &'a mut HashSet<String>,
+    failed: &'a mut Vec<String>,
+    artwork: &'a mut ArtworkDownloads,
+}
+
+impl StrmOutput<'_> {
+    /// Writes the file if the content has changed, returns false if the file could not be written.
+    async fn write_file(&mut self, file_path: &Path, content_as_bytes: &[u8], timestamp: Option<u64>) -> bool {
+        let relative_file_path = get_relative_path_str(file_path, self.root_path);
+
+        // check if file exists and has same hash
+        if file_path.exists() && has_strm_file_same_hash(file_path, hash_bytes(content_as_bytes)).await {
+            self.processed.insert(relative_file_path);
+            return true; // skip creation
+        }
+
+        // if we cant create the directory skip this entry
+        let Some(output_path) = file_path.parent() else { return false; };
+        if !ensure_strm_file_directory(self.failed, output_path).await {
+            return false;
+        }
+
+        match write_strm_file(file_path, content_as_bytes, timestamp).await {
+            Ok(()) => {
+                self.processed.insert(relative_file_path);
+                true
+            }
+            Err(err) => {
+                self.failed.push(err);
+                false
+            }
+        }
+    }
+
+    fn is_processed(&self, file_path: &Path) -> bool {
+        self.processed.contains(&get_relative_path_str(file_path, self.root_path))
+    }
+
+    /// Queues the download of the image, existing images are kept.
+    fn add_artwork(&mut self, client: &Arc<reqwest::Client>, url: &str, dir_path: &Path, name: &str) {
+        if !url.starts_with("http") {
+            return;
+        }
+        let ext = extract_extension_from_url(url)
+            .filter(|ext| (2..=5).contains(&ext.len()) && ext[1..].chars().all(char::is_alphanumeric))
+            .unwrap_or(".jpg");
+        let file_path = dir_path.join(format!("{name}{ext}"));
+        if self.is_processed(&file_path) {
+            return;
+        }
+        if !file_path.exists() {
+            if self.artwork.has_failed(url) {
+                return;
+            }
+            self.artwork.pending.push(ArtworkDownload { client: Arc::clone(client), url: url.to_string(), file_path: file_path.clone() });
+        }
+        self.processed.insert(get_relative_path_str(&file_path, self.root_path));
+    }
+}
+
+struct ArtworkDownload {
+    client: Arc<reqwest::Client>,
+    url: String,
+    file_path: PathBuf,
+}
+
+/// The images are downloaded after the strm files are written.
+/// Urls which failed are stored with the time of the failure and retried after `ARTWORK_RETRY_SECS`.
+struct ArtworkDownloads {
+    failed_path: PathBuf,
+    failed: HashMap<String, i64>,
+    pending: Vec<ArtworkDownload>,
+    now: i64,
+}
+
+impl ArtworkDownloads {
+    fn new(failed_path: &Path) -> Self {
+        let failed = std::fs::read_to_string(failed_path).ok()
+            .and_then(|content| serde_json::from_str::<HashMap<String, i64>>(&content).ok())
+            .unwrap_or_default();
+        Self { failed_path: failed_path.to_path_buf(), failed, pending: vec![], now: Local::now().timestamp() }
+    }
+
+    fn has_failed(&self, url: &str) -> bool {
+        self.failed.get(url).is_some_and(|ts| self.now - ts < ARTWORK_RETRY_SECS)
+    }
+
+    async fn download(&mut self, resource_cache: &Arc<Option<Mutex<LRUResourceCache>>>, root_path: &Path, processed: &mut HashSet<String>) {
+        let results: Vec<(ArtworkDownload, Result<(), String>)> = futures::stream::iter(std::mem::take(&mut self.pending))
+            .map(|download| async move {
+                let result = download_artwork(resource_cache, &download).await;
+                (download, result)
+            })
+            .buffer_unordered(ARTWORK_DOWNLOAD_CONCURRENCY)
+            .collect()
+            .await;
+        for (download, result) in results {
+            match result {
+                Ok(()) => {
+                    self.failed.remove(&download.url);
+                }
+                Err(err) => {
+                    debug!("Failed to download artwork {}: {err}", sanitize_sensitive_info(&download.url));
+                    processed.remove(&get_relative_path_str(&download.file_path, root_path));
+                    self.failed.insert(download.url, self.now);
+                }
+            }
+        }
+    }
+
+    fn store(&mut self) -> Result<(), String> {
+        let now = self.now;
+        self.failed.retain(|_, ts| now - *ts < ARTWORK_RETRY_SECS);
+        if self.failed.is_empty() && !self.failed_path.exists() {
+            return Ok(());
+        }
+        serde_json::to_string(&self.failed).map_err(|err| err.to_string())
+            .and_then(|content| std::fs::write(&self.failed_path, content).map_err(|err| err.to_string()))
+            .map_err(|err| format!("Failed to write artwork failures {}: {err}", self.failed_path.display()))
+    }
+}
+
+async fn download_artwork(resource_cache: &Arc<Option<Mutex<LRUResourceCache>>>, download: &ArtworkDownload) -> Result<(), String> {
+    // images requested by the clients are already stored in the resource cache of the reverse proxy
+    if let Some(cache) = resource_cache.as_ref() {
+        let cache_file_path = cache.lock().await.get_content(&download.url);
+        if let Some(cache_file_path) = cache_file_path {
+            return tokio::fs::copy(&cache_file_path, &download.file_path).await.map(|_| ()).map_err(|err| err.to_string());
+        }
+    }
+    let response = download.client.get(&download.url)
+        .timeout(Duration::from_secs(ARTWORK_DOWNLOAD_TIMEOUT_SECS))
+        .send().await.map_err(|err| err.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("status {}", response.status()));
+    }
+    let content = response.bytes().await.map_err(|err| err.to_string())?;
+    if let Some(cache) = resource_cache.as_ref() {
+        let cache_file_path = cache.lock().await.store_path(&download.url);
+        if tokio::fs::write(&cache_file_path, &content).await.is_ok() {
+            let _ = cache.lock().await.add_content(&download.url, content.len());
+        }
+    }
+    tokio::fs::write(&download.file_path, &content).await.map_err(|err| err.to_string())
+}
+
+/// The stored vod info of the input has more details than the properties of the vod stream.
+fn get_vod_properties(cfg: &Config, strm_info: &StrmItemInfo, props: &Map<String, Value>) -> Map<String, Value> {
+    let mut result = props.clone();
+    let vod_info = strm_info.provider_id
+        .zip(cfg.get_input_by_name(&strm_info.input_name))
+        .and_then(|(provider_id, input)| xtream_get_input_info(cfg, input, provider_id, XtreamCluster::Video))
+        .and_then(|content| serde_json::from_str::<Map<String, Value>>(&content).ok());
+    if let Some(Value::Object(info)) = vod_info.and_then(|mut doc| doc.remove(xtream_const::XC_TAG_VOD_INFO_INFO)) {
+        for (key, value) in info {
+            if !(value.is_null() || value.as_str().is_some_and(str::is_empty)) {
+                result.insert(key, value);
+            }
+        }
+    }
+    result
+}
+
+/// Writes the nfo files and artwork for movies and episodes with xtream properties.
+/// Kodi style movies have their own directory and use `movie.nfo`, the other files are named after the strm file.
+async fn kodi_write_metadata(
+    client: &Arc<reqwest::Client>,
+    cfg: &Config,
+    target_output: &StrmTargetOutput,
+    strm_file: &StrmFile,
+    output: &mut StrmOutput<'_>,
+) {
+    let strm_info = &strm_file.strm_info;
+    let Some(props) = strm_info.additional_properties.as_ref() else { return; };
+    let output_path = output.root_path.join(&strm_file.dir_path);
+    let timestamp = strm_info.get_file_ts();
+    // the artwork is loaded through the proxy of the input
+    let artwork_client = target_output.artwork.then(|| cfg.get_input_by_name(&strm_info.input_name)
+        .map_or_else(|| Arc::clone(client), |input| input.get_http_client(client, &strm_info.url)));
+    match strm_info.item_type {
+        PlaylistItemType::Video => {
+            let nfo = KodiNfo::movie(&strm_info.title, &get_vod_properties(cfg, strm_info, props));
+            let (nfo_file_name, artwork_prefix) = if target_output.kodi_style {
+                (String::from("movie.nfo"), String::new())
+            } else {
+                (format!("{}.nfo", strm_file.file_name), format!("{}-", strm_file.file_name))
+            };
+            if target_output.nfo {
+                output.write_file(&output_path.join(nfo_file_name), nfo.to_movie_nfo().as_bytes(), timestamp).await;
+            }
+            if let Some(client) = artwork_client.as_ref() {
+                output.add_artwork(client, &nfo.poster, &output_path, &format!("{artwork_prefix}poster"));
+                output.add_artwork(client, &nfo.fanart, &output_path, &format!("{artwork_prefix}fanart"));
+            }
+        }
+        PlaylistItemType::Series => {
+            let nfo = KodiNfo::episode(&strm_info.title, props);
+            if target_output.nfo {
+                output.write_file(&output_path.join(format!("{}.nfo", strm_file.file_name)), nfo.to_episode_nfo().as_bytes(), timestamp).await;
+            }
+            if let Some(client) = artwork_client.as_ref() {
+                output.add_artwork(client, &nfo.poster, &output_path, &format!("{}-thumb", strm_file.file_name));
+            }
+            if let Some(series_path) = strm_file.series_dir_path.as_ref().map(|dir| output.root_path.join(dir)) {
+                let tvshow_nfo_path = series_path.join("tvshow.nfo");
+                // the series files are written once for all episodes
+                if output.is_processed(&tvshow_nfo_path) {
+                    return;
+                }
+                let series_nfo = KodiNfo::series(props);
+                if target_output.nfo {
+                    output.write_file(&tvshow_nfo_path, series_nfo.to_tvshow_nfo().as_bytes(), None).await;
+                }
+                if let Some(client) = artwork_client.as_ref() {
+                    output.add_artwork(client, &series_nfo.poster, &series_path, "poster");
+                    output.add_artwork(client, &series_nfo.fanart, &series_path, "fanart");
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
 async fn write_strm_index_file(
     cfg: &Config,
     entries: &HashSet<String>,
@@ -745,7 +1000,7 @@ async fn write_strm_file(
     Ok(())
 }
 
-async fn has_strm_file_same_hash(file_path: &PathBuf, content_hash: UUIDType) -> bool {
+async fn has_strm_file_same_hash(file_path: &Path, content_hash: UUIDType) -> bool {
     if let Ok(file) = File::open(&file_path).await {
         let mut reader = BufReader::new(file);
         let mut buffer = Vec::new();
@@ -764,7 +1019,7 @@ async fn has_strm_file_same_hash(file_path: &PathBuf, content_hash: UUIDType) ->
     false
 }
 
-async fn get_credentials_and_server_info(
+pub(crate) async fn get_credentials_and_server_info(
     cfg: &Config,
     username: Option<&String>,
 ) -> Option<(ProxyUserCredentials, ApiProxyServerInfo)> {
@@ -790,20 +1045,30 @@ fn get_strm_url(
     user_and_server_info: Option<&(ProxyUserCredentials, ApiProxyServerInfo)>,
     str_item_info: &StrmItemInfo,
 ) -> String {
-    let Some((user, server_info)) = user_and_server_info else { return str_item_info.url.to_string(); };
+    get_proxy_stream_url(target_force_redirect, user_and_server_info, str_item_info.item_type, str_item_info.virtual_id, &str_item_info.url)
+}
+
+/// Returns the xtream proxy url of the item for the user, or the provider url for redirects and without user.
+pub(crate) fn get_proxy_stream_url(
+    target_force_redirect: Option<&ClusterFlags>,
+    user_and_server_info: Option<&(ProxyUserCredentials, ApiProxyServerInfo)>,
+    item_type: PlaylistItemType,
+    virtual_id: u32,
+    url: &str,
+) -> String {
+    let Some((user, server_info)) = user_and_server_info else { return url.to_string(); };
 
-    let redirect = user.proxy.is_redirect(str_item_info.item_type) || target_force_redirect.is_some_and(|f| f.has_cluster(str_item_info.item_type));
+    let redirect = user.proxy.is_redirect(item_type) || target_force_redirect.is_some_and(|f| f.has_cluster(item_type));
     if redirect {
-        return str_item_info.url.to_string();
+        return url.to_string();
     }
 
-    if let Some(stream_type) = match str_item_info.item_type {
+    if let Some(stream_type) = match item_type {
         PlaylistItemType::Series => Some("series"),
         PlaylistItemType::Live => Some("live"),
         PlaylistItemType::Video => Some("movie"),
         _ => None,
     } {
-        let url = str_item_info.url.as_str();
         let ext = extract_extension_from_url(url)
             .map_or_else(String::new, std::string::ToString::to_string);
         format!(
@@ -811,10 +1076,10 @@ fn get_strm_url(
             server_info.get_base_url(),
             user.username,
             user.password,
-            str_item_info.virtual_id
+            virtual_id
         )
     } else {
-        str_item_info.url.to_string()
+        url.to_string()
     }
 }
 
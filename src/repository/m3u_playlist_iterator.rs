@@ -1,16 +1,38 @@
 use crate::m3u_filter_error::info_err;
 use crate::m3u_filter_error::{M3uFilterError, M3uFilterErrorKind};
 use crate::model::api_proxy::{ProxyType, ProxyUserCredentials};
-use crate::model::config::{Config, ConfigTarget, ConfigTargetOptions, TargetType};
+use crate::model::config::{Config, ConfigTarget, ConfigTargetOptions, M3uSplitMode, TargetType};
 use crate::model::playlist::{M3uPlaylistItem, PlaylistItemType, XtreamCluster};
 use crate::repository::indexed_document::IndexedDocumentIterator;
-use crate::repository::m3u_repository::m3u_get_file_paths;
+use crate::repository::m3u_repository::{m3u_get_file_paths, m3u_get_split_title};
 use crate::repository::storage::ensure_target_storage_path;
 use crate::repository::storage_const;
 use crate::repository::user_repository::user_get_bouquet_filter;
+use crate::utils::catchup_utils::get_catchup_query;
 use crate::utils::file::file_lock_manager::FileReadGuard;
 use std::collections::HashSet;
 
+/// Creates the url of a stream or resource of the m3u api for a user.
+/// If `typed` is set, the stream type `live`, `movie`, `series` is part of the url.
+pub fn m3u_get_rewritten_url(base_url: &str, username: &str, password: &str, prefix_path: &str,
+                             item_type: PlaylistItemType, virtual_id: u32, typed: bool) -> String {
+    if typed {
+        let stream_type = match item_type {
+            PlaylistItemType::Live
+            | PlaylistItemType::Catchup
+            | PlaylistItemType::LiveUnknown
+            | PlaylistItemType::LiveHls
+            | PlaylistItemType::LiveDash => "live",
+            PlaylistItemType::Video => "movie",
+            PlaylistItemType::Series
+            | PlaylistItemType::SeriesInfo => "series",
+        };
+        format!("{base_url}/{prefix_path}/{stream_type}/{username}/{password}/{virtual_id}")
+    } else {
+        format!("{base_url}/{prefix_path}/{username}/{password}/{virtual_id}")
+    }
+}
+
 #[allow(clippy::struct_excessive_bools)]
 pub struct M3uPlaylistIterator {
     reader: IndexedDocumentIterator<u32, M3uPlaylistItem>,
@@ -61,28 +83,7 @@ impl M3uPlaylistIterator {
     }
 
     fn get_rewritten_url(&self, m3u_pli: &M3uPlaylistItem, typed: bool, prefix_path: &str) -> String {
-        if typed {
-            let stream_type = match m3u_pli.item_type {
-                PlaylistItemType::Live
-                | PlaylistItemType::Catchup
-                | PlaylistItemType::LiveUnknown
-                | PlaylistItemType::LiveHls
-                | PlaylistItemType::LiveDash => "live",
-                PlaylistItemType::Video => "movie",
-                PlaylistItemType::Series
-                | PlaylistItemType::SeriesInfo => "series",
-            };
-            format!("{}/{prefix_path}/{stream_type}/{}/{}/{}",
-                    &self.base_url,
-                    &self.username,
-                    &self.password,
-                    m3u_pli.virtual_id
-            )
-        } else {
-            format!("{}/{prefix_path}/{}/{}/{}",
-                    &self.base_url, &self.username, &self.password, m3u_pli.virtual_id
-            )
-        }
+        m3u_get_rewritten_url(&self.base_url, &self.username, &self.password, prefix_path, m3u_pli.item_type, m3u_pli.virtual_id, typed)
     }
 
     fn get_stream_url(&self, m3u_pli: &M3uPlaylistItem, typed: bool) -> String {
@@ -91,6 +92,10 @@ impl M3uPlaylistIterator {
     fn get_resource_url(&self, m3u_pli: &M3uPlaylistItem) -> String {
         self.get_rewritten_url(m3u_pli, false, storage_const::M3U_RESOURCE_PATH)
     }
+    fn get_catchup_url(&self, m3u_pli: &M3uPlaylistItem) -> Option<String> {
+        m3u_pli.get_catchup_template().map(|template|
+            format!("{}?{}", self.get_rewritten_url(m3u_pli, false, storage_const::M3U_CATCHUP_PATH), get_catchup_query(&template)))
+    }
 
     fn get_next(&mut self) -> Option<(M3uPlaylistItem, bool)> {
         let entry = if let Some(set) = &self.filter {
@@ -114,6 +119,7 @@ impl M3uPlaylistIterator {
 
             m3u_pli.t_stream_url = stream_url.to_string();
             m3u_pli.t_resource_url = resource_url.map(|s| s.to_string());
+            m3u_pli.t_catchup_url = if should_rewrite_urls { self.get_catchup_url(&m3u_pli) } else { None };
             (m3u_pli, self.reader.has_next())
         })
     }
@@ -130,7 +136,8 @@ impl Iterator for M3uPlaylistIterator {
 pub struct M3uPlaylistM3uTextIterator {
     inner: M3uPlaylistIterator,
     started: bool,
-
+    // only the items of this split group or cluster title are returned
+    split: Option<(M3uSplitMode, String)>,
 }
 
 impl M3uPlaylistM3uTextIterator {
@@ -138,12 +145,24 @@ impl M3uPlaylistM3uTextIterator {
         cfg: &Config,
         target: &ConfigTarget,
         user: &ProxyUserCredentials,
+        split: Option<(M3uSplitMode, String)>,
     ) -> Result<Self, M3uFilterError> {
         Ok(Self {
             inner: M3uPlaylistIterator::new(cfg, target, user).await?,
             started: false,
+            split,
         })
     }
+
+    fn get_next_item(&mut self) -> Option<M3uPlaylistItem> {
+        loop {
+            let (m3u_pli, _has_next) = self.inner.get_next()?;
+            match self.split.as_ref() {
+                Some((split, title)) if m3u_get_split_title(&m3u_pli, *split) != title.as_str() => {}
+                _ => return Some(m3u_pli),
+            }
+        }
+    }
 }
 
 impl Iterator for M3uPlaylistM3uTextIterator {
@@ -156,7 +175,7 @@ impl Iterator for M3uPlaylistM3uTextIterator {
         }
 
         // TODO hls and unknown reverse proxy
-        self.inner.get_next().map(|(m3u_pli, _has_next)| {
+        self.get_next_item().map(|m3u_pli| {
             let target_options = self.inner.target_options.as_ref();
             m3u_pli.to_m3u(target_options, true)
         })
@@ -1,8 +1,8 @@
 use crate::m3u_filter_error::{create_m3u_filter_error,info_err};
 use crate::m3u_filter_error::{str_to_io_error, M3uFilterError, M3uFilterErrorKind};
 use crate::model::api_proxy::ProxyUserCredentials;
-use crate::model::config::{Config, ConfigTarget, M3uTargetOutput};
-use crate::model::playlist::{M3uPlaylistItem, PlaylistGroup, PlaylistItem, PlaylistItemType};
+use crate::model::config::{Config, ConfigTarget, M3uSplitMode, M3uTargetOutput};
+use crate::model::playlist::{M3uPlaylistItem, PlaylistGroup, PlaylistItem, PlaylistItemType, XtreamCluster};
 use crate::repository::indexed_document::{IndexedDocumentDirectAccess, IndexedDocumentIterator, IndexedDocumentWriter};
 use crate::repository::m3u_playlist_iterator::{M3uPlaylistM3uTextIterator};
 use crate::repository::storage::{get_target_storage_path};
@@ -10,12 +10,19 @@ use crate::utils::file::file_lock_manager::FileReadGuard;
 use crate::utils::file::file_utils;
 use crate::utils::file::file_utils::file_writer;
 use log::error;
+use std::borrow::{Borrow, Cow};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
 use std::fs::File;
 use std::io::{Error, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::Mutex;
 use crate::repository::storage_const;
 
+pub const M3U_SPLIT_INDEX: &str = "index";
+
 macro_rules! cant_write_result {
     ($path:expr, $err:expr) => {
         create_m3u_filter_error!(M3uFilterErrorKind::Notify, "failed to write m3u playlist: {} - {}", $path.to_str().unwrap() ,$err)
@@ -33,20 +40,192 @@ pub fn m3u_get_epg_file_path(target_path: &Path) -> PathBuf {
     file_utils::add_prefix_to_filename(&path, "epg_", Some("xml"))
 }
 
-fn persist_m3u_playlist_as_text(cfg: &Config, target: &ConfigTarget, target_output: &M3uTargetOutput, m3u_playlist: &Vec<M3uPlaylistItem>) {
+// The sanitized name of the split playlist an item belongs to, it can collide with other groups.
+fn m3u_get_split_base_name(m3u: &M3uPlaylistItem, split: M3uSplitMode) -> String {
+    let name = match split {
+        M3uSplitMode::Group => file_utils::sanitize_filename(&m3u.group).to_lowercase(),
+        M3uSplitMode::Cluster => XtreamCluster::try_from(m3u.item_type).unwrap_or(XtreamCluster::Live).as_stream_type().to_string(),
+    };
+    if name.is_empty() { String::from("_") } else { name }
+}
+
+/// The title of the split playlist an item belongs to, the group title or the cluster.
+pub fn m3u_get_split_title(m3u: &M3uPlaylistItem, split: M3uSplitMode) -> Cow<'_, str> {
+    match split {
+        M3uSplitMode::Group => Cow::Borrowed(m3u.group.as_str()),
+        M3uSplitMode::Cluster => Cow::Owned(XtreamCluster::try_from(m3u.item_type).unwrap_or(XtreamCluster::Live).as_str().to_string()),
+    }
+}
+
+/// The names and titles of the split playlists in the order of the first occurrence.
+/// The name is used for the filename and the api path, names which collide after sanitizing
+/// or with the index get a numeric suffix.
+#[derive(Debug)]
+pub struct M3uSplitNames {
+    split: M3uSplitMode,
+    entries: Vec<(String, String)>,
+    by_title: HashMap<String, usize>,
+    // the groups of each split playlist, used for the bouquet filter of the user
+    groups: Vec<HashSet<String>>,
+}
+
+impl M3uSplitNames {
+    pub fn collect<I, T>(items: I, split: M3uSplitMode) -> Self
+    where
+        I: IntoIterator<Item=T>,
+        T: Borrow<M3uPlaylistItem>,
+    {
+        let mut names = Self { split, entries: vec![], by_title: HashMap::new(), groups: vec![] };
+        let mut used_names = HashSet::from([M3U_SPLIT_INDEX.to_string()]);
+        for item in items {
+            let m3u = item.borrow();
+            let title = m3u_get_split_title(m3u, split);
+            if let Some(&index) = names.by_title.get(title.as_ref()) {
+                if !names.groups[index].contains(&m3u.group) {
+                    names.groups[index].insert(m3u.group.clone());
+                }
+            } else {
+                let base_name = m3u_get_split_base_name(m3u, split);
+                let mut name = base_name.clone();
+                let mut suffix = 2;
+                while used_names.contains(&name) {
+                    name = format!("{base_name}_{suffix}");
+                    suffix += 1;
+                }
+                used_names.insert(name.clone());
+                names.by_title.insert(title.to_string(), names.entries.len());
+                names.entries.push((name, title.into_owned()));
+                names.groups.push(HashSet::from([m3u.group.clone()]));
+            }
+        }
+        names
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The position of the split playlist the item belongs to.
+    pub fn get_index(&self, m3u: &M3uPlaylistItem) -> Option<usize> {
+        self.by_title.get(m3u_get_split_title(m3u, self.split).as_ref()).copied()
+    }
+
+    /// The title of the split playlist, only if it contains at least one group of the bouquet filter.
+    pub fn get_title<'a>(&'a self, name: &str, filter: Option<&'a HashSet<String>>) -> Option<&'a str> {
+        self.iter_filtered(filter).find(|(entry_name, _)| *entry_name == name).map(|(_, title)| title)
+    }
+
+    /// Returns the name and title of each split playlist.
+    pub fn iter(&self) -> impl Iterator<Item=(&str, &str)> {
+        self.entries.iter().map(|(name, title)| (name.as_str(), title.as_str()))
+    }
+
+    /// Returns the name and title of each split playlist containing at least one group of the bouquet filter.
+    pub fn iter_filtered<'a>(&'a self, filter: Option<&'a HashSet<String>>) -> impl Iterator<Item=(&'a str, &'a str)> {
+        self.iter().zip(&self.groups)
+            .filter(move |(_, groups)| filter.is_none_or(|set| groups.iter().any(|group| set.contains(group))))
+            .map(|(entry, _)| entry)
+    }
+}
+
+/// The split names of each target, they are collected again when the stored playlist changed.
+pub type M3uSplitNamesCache = Mutex<HashMap<String, (SystemTime, Arc<M3uSplitNames>)>>;
+
+/// Returns the split names of the stored target playlist, the whole playlist is only read after an update.
+pub async fn m3u_get_split_names(cfg: &Arc<Config>, target: &ConfigTarget, split: M3uSplitMode, cache: &M3uSplitNamesCache) -> Option<Arc<M3uSplitNames>> {
+    let target_path = get_target_storage_path(cfg, target.name.as_str())?;
+    let (m3u_path, _idx_path) = m3u_get_file_paths(&target_path);
+    let modified = std::fs::metadata(&m3u_path).and_then(|metadata| metadata.modified()).ok()?;
+    let mut cache = cache.lock().await;
+    if let Some((cached_modified, names)) = cache.get(&target.name) {
+        if *cached_modified == modified && names.split == split {
+            return Some(Arc::clone(names));
+        }
+    }
+    let (_file_lock, m3u_iter) = iter_raw_m3u_playlist(cfg, target).await?;
+    let names = Arc::new(M3uSplitNames::collect(m3u_iter.map(|(m3u_pli, _has_next)| m3u_pli), split));
+    cache.insert(target.name.clone(), (modified, Arc::clone(&names)));
+    Some(names)
+}
+
+/// Creates the index playlist, each entry points to a split playlist.
+pub fn m3u_create_split_index<'a, I>(entries: I) -> String
+where
+    I: IntoIterator<Item=(&'a str, String)>,
+{
+    let mut content = String::from("#EXTM3U\n");
+    for (title, url) in entries {
+        let _ = writeln!(content, "#EXTINF:-1,{title}\n{url}");
+    }
+    content
+}
+
+fn write_m3u_file<'a, I>(path: &Path, target: &ConfigTarget, m3u_playlist: I) -> std::io::Result<()>
+where
+    I: IntoIterator<Item=&'a M3uPlaylistItem>,
+{
+    let file = File::create(path)?;
+    let mut buf_writer = file_writer(&file);
+    buf_writer.write_all(b"#EXTM3U\n")?;
+    for m3u in m3u_playlist {
+        buf_writer.write_all(m3u.to_m3u(target.options.as_ref(), false).as_bytes())?;
+        buf_writer.write_all(b"\n")?;
+    }
+    buf_writer.flush()
+}
+
+// Only the playlists listed in the previous index are removed, other files in the directory are kept.
+fn remove_m3u_split_playlists(directory: &Path, index_path: &Path) -> std::io::Result<()> {
+    let Ok(index) = std::fs::read_to_string(index_path) else {
+        return Ok(());
+    };
+    for file_name in index.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+        // the index only contains plain file names, anything else was not written here
+        if Path::new(file_name).file_name().is_some_and(|name| name == file_name) {
+            let path = directory.join(file_name);
+            if path.is_file() {
+                std::fs::remove_file(&path)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// The split playlists are written into a directory named like the playlist file without extension.
+fn persist_m3u_split_playlists(m3u_filename: &Path, target: &ConfigTarget, split: M3uSplitMode, m3u_playlist: &[M3uPlaylistItem]) -> std::io::Result<()> {
+    let directory = m3u_filename.with_extension("");
+    let extension = m3u_filename.extension().map_or_else(|| String::from("m3u"), |ext| ext.to_string_lossy().to_string());
+    let index_path = directory.join(format!("{M3U_SPLIT_INDEX}.{extension}"));
+    std::fs::create_dir_all(&directory)?;
+    // remove playlists of groups which don't exist anymore
+    remove_m3u_split_playlists(&directory, &index_path)?;
+    let names = M3uSplitNames::collect(m3u_playlist, split);
+    let mut split_playlists: Vec<Vec<&M3uPlaylistItem>> = vec![Vec::new(); names.len()];
+    for m3u in m3u_playlist {
+        if let Some(index) = names.get_index(m3u) {
+            split_playlists[index].push(m3u);
+        }
+    }
+    for ((name, _title), items) in names.iter().zip(split_playlists) {
+        write_m3u_file(&directory.join(format!("{name}.{extension}")), target, items)?;
+    }
+    let index = m3u_create_split_index(names.iter().map(|(name, title)| (title, format!("{name}.{extension}"))));
+    std::fs::write(index_path, index)
+}
+
+fn persist_m3u_playlist_as_text(cfg: &Config, target: &ConfigTarget, target_output: &M3uTargetOutput, m3u_playlist: &[M3uPlaylistItem]) {
     if let Some(filename) = target_output.filename.as_ref() {
         if let Some(m3u_filename) = file_utils::get_file_path(&cfg.working_dir, Some(PathBuf::from(filename))) {
-            match File::create(&m3u_filename) {
-                Ok(file) => {
-                    let mut buf_writer = file_writer(&file);
-                    let _ = buf_writer.write(b"#EXTM3U\n");
-                    for m3u in m3u_playlist {
-                        let _ = buf_writer.write(m3u.to_m3u(target.options.as_ref(), false).to_string().as_bytes());
-                        let _ = buf_writer.write(b"\n");
-                    }
-                }
-                Err(_) => {
-                    error!("Can't write m3u plain playlist {}", &m3u_filename.to_str().unwrap());
+            if write_m3u_file(&m3u_filename, target, m3u_playlist).is_err() {
+                error!("Can't write m3u plain playlist {}", m3u_filename.display());
+            }
+            if let Some(split) = target_output.split {
+                if let Err(err) = persist_m3u_split_playlists(&m3u_filename, target, split, m3u_playlist) {
+                    error!("Can't write m3u split playlists for {}: {err}", m3u_filename.display());
                 }
             }
         }
@@ -86,7 +265,18 @@ pub async fn m3u_load_rewrite_playlist(
     target: &ConfigTarget,
     user: &ProxyUserCredentials,
 ) -> Result<M3uPlaylistM3uTextIterator, M3uFilterError> {
-    M3uPlaylistM3uTextIterator::new(cfg, target, user).await
+    M3uPlaylistM3uTextIterator::new(cfg, target, user, None).await
+}
+
+/// Loads the playlist of a split group or cluster with the urls of the user, the title is the group or cluster.
+pub async fn m3u_load_rewrite_split_playlist(
+    cfg: &Config,
+    target: &ConfigTarget,
+    user: &ProxyUserCredentials,
+    split: M3uSplitMode,
+    title: &str,
+) -> Result<M3uPlaylistM3uTextIterator, M3uFilterError> {
+    M3uPlaylistM3uTextIterator::new(cfg, target, user, Some((split, title.to_string()))).await
 }
 
 pub async fn m3u_get_item_for_stream_id(stream_id: u32, cfg: &Config, target: &ConfigTarget) -> Result<M3uPlaylistItem, Error> {
@@ -114,3 +304,64 @@ pub async fn iter_raw_m3u_playlist(config: &Arc<Config>, target: &ConfigTarget)
         Err(_) => None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::model::config::{ConfigTarget, M3uSplitMode};
+    use crate::model::playlist::{M3uPlaylistItem, PlaylistItem, PlaylistItemHeader, PlaylistItemType};
+    use super::{persist_m3u_split_playlists, M3uSplitNames};
+    use std::collections::HashSet;
+
+    fn create_item(virtual_id: u32, group: &str, item_type: PlaylistItemType) -> M3uPlaylistItem {
+        PlaylistItem { header: PlaylistItemHeader { virtual_id, group: group.to_string(), title: format!("Channel {virtual_id}"),
+            url: format!("http://provider.net/{virtual_id}.ts"), item_type, ..PlaylistItemHeader::default() } }.to_m3u()
+    }
+
+    #[test]
+    fn test_m3u_split_playlists() {
+        let playlist = vec![create_item(1, "News HD", PlaylistItemType::Live),
+                            create_item(2, "Movies", PlaylistItemType::Video),
+                            create_item(3, "News HD", PlaylistItemType::Live)];
+        assert_eq!(M3uSplitNames::collect(&playlist, M3uSplitMode::Group).iter().collect::<Vec<_>>(),
+                   vec![("news_hd", "News HD"), ("movies", "Movies")]);
+        assert_eq!(M3uSplitNames::collect(&playlist, M3uSplitMode::Cluster).iter().map(|(name, _)| name).collect::<Vec<_>>(), vec!["live", "movie"]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let m3u_filename = dir.path().join("playlist.m3u");
+        persist_m3u_split_playlists(&m3u_filename, &ConfigTarget::default(), M3uSplitMode::Group, &playlist).unwrap();
+        let news = std::fs::read_to_string(dir.path().join("playlist/news_hd.m3u")).unwrap();
+        assert_eq!(news.matches("#EXTINF").count(), 2);
+        let index = std::fs::read_to_string(dir.path().join("playlist/index.m3u")).unwrap();
+        assert_eq!(index, "#EXTM3U\n#EXTINF:-1,News HD\nnews_hd.m3u\n#EXTINF:-1,Movies\nmovies.m3u\n");
+
+        // only the playlists of the previous index are removed
+        std::fs::write(dir.path().join("playlist/custom.m3u"), "#EXTM3U\n").unwrap();
+        persist_m3u_split_playlists(&m3u_filename, &ConfigTarget::default(), M3uSplitMode::Group, &playlist[1..2]).unwrap();
+        assert!(!dir.path().join("playlist/news_hd.m3u").exists());
+        assert!(dir.path().join("playlist/movies.m3u").exists());
+        assert!(dir.path().join("playlist/custom.m3u").exists());
+    }
+
+    #[test]
+    fn test_m3u_split_name_collisions() {
+        let playlist = vec![create_item(1, "News HD", PlaylistItemType::Live),
+                            create_item(2, "News/HD", PlaylistItemType::Live),
+                            create_item(3, "Index", PlaylistItemType::Live),
+                            create_item(4, "News HD", PlaylistItemType::Live)];
+        let names = M3uSplitNames::collect(&playlist, M3uSplitMode::Group);
+        assert_eq!(names.iter().collect::<Vec<_>>(), vec![("news_hd", "News HD"), ("news_hd_2", "News/HD"), ("index_2", "Index")]);
+        assert_eq!(names.get_index(&playlist[3]), Some(0));
+        assert_eq!(names.get_title("news_hd_2", None), Some("News/HD"));
+        let filter = HashSet::from(["News HD".to_string()]);
+        assert_eq!(names.get_title("news_hd_2", Some(&filter)), None);
+        assert_eq!(names.iter_filtered(Some(&filter)).collect::<Vec<_>>(), vec![("news_hd", "News HD")]);
+        let cluster_names = M3uSplitNames::collect(&playlist, M3uSplitMode::Cluster);
+        assert_eq!(cluster_names.iter_filtered(Some(&filter)).map(|(name, _)| name).collect::<Vec<_>>(), vec!["live"]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let m3u_filename = dir.path().join("playlist.m3u");
+        persist_m3u_split_playlists(&m3u_filename, &ConfigTarget::default(), M3uSplitMode::Group, &playlist).unwrap();
+        assert_eq!(std::fs::read_to_string(dir.path().join("playlist/news_hd_2.m3u")).unwrap().matches("#EXTINF").count(), 1);
+        assert!(std::fs::read_to_string(dir.path().join("playlist/index.m3u")).unwrap().contains("#EXTINF:-1,Index\nindex_2.m3u\n"));
+    }
+}
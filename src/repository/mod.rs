@@ -1,5 +1,6 @@
 pub mod storage;
 pub mod target_id_mapping;
+pub mod channel_number_mapping;
 pub mod bplustree;
 mod indexed_document;
 pub use indexed_document::IndexedDocumentReader;
@@ -8,8 +9,14 @@ pub mod m3u_repository;
 pub mod xtream_repository;
 pub mod epg_repository;
 pub mod kodi_repository;
+pub mod kodi_nfo;
+pub mod enigma2_repository;
+pub mod json_repository;
+pub mod playlist_file_repository;
 pub mod m3u_playlist_iterator;
 pub mod xtream_playlist_iterator;
 pub mod user_repository;
+pub mod stats_repository;
+pub mod override_repository;
 pub mod storage_const;
 
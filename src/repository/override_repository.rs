@@ -0,0 +1,130 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::m3u_filter_error::{info_err, M3uFilterError, M3uFilterErrorKind};
+use crate::model::config::Config;
+use crate::model::playlist::UUIDType;
+use crate::repository::storage::{ensure_target_storage_path, get_target_storage_path, hex_decode, hex_encode};
+use crate::repository::storage_const;
+use crate::utils::file::file_utils::file_reader;
+use crate::utils::json_utils::json_write_documents_to_file;
+
+/// Manual changes for a single playlist item, addressed by the hex encoded item uuid.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PlaylistItemOverride {
+    pub uuid: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logo: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epg_channel_id: Option<String>,
+    #[serde(default)]
+    pub hidden: bool,
+}
+
+/// Returns the normalized uuid, or an error if it is not a hex encoded item uuid.
+pub fn validate_override_uuid(uuid: &str) -> Result<String, M3uFilterError> {
+    let uuid = uuid.trim().to_uppercase();
+    match hex_decode(&uuid) {
+        Ok(bytes) if bytes.len() == size_of::<UUIDType>() => Ok(uuid),
+        _ => Err(info_err!(format!("Invalid override uuid: {uuid}"))),
+    }
+}
+
+impl PlaylistItemOverride {
+    pub fn prepare(&mut self) -> Result<(), M3uFilterError> {
+        self.uuid = validate_override_uuid(&self.uuid)?;
+        for value in [&mut self.name, &mut self.title, &mut self.group, &mut self.logo, &mut self.epg_channel_id] {
+            if value.as_ref().is_some_and(|v| v.trim().is_empty()) {
+                *value = None;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn override_uuid(uuid: &UUIDType) -> String {
+    hex_encode(uuid)
+}
+
+fn get_overrides_file_path(target_path: &Path) -> PathBuf {
+    target_path.join(storage_const::FILE_OVERRIDES)
+}
+
+fn read_overrides(path: &Path) -> Result<Vec<PlaylistItemOverride>, M3uFilterError> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let file = File::open(path).map_err(|err| info_err!(format!("Failed to open overrides {}: {err}", path.display())))?;
+    serde_json::from_reader(file_reader(file)).map_err(|err| info_err!(format!("Failed to read overrides {}: {err}", path.display())))
+}
+
+fn write_overrides(path: &Path, overrides: &[PlaylistItemOverride]) -> Result<(), M3uFilterError> {
+    json_write_documents_to_file(path, overrides).map_err(|err| info_err!(format!("Failed to write overrides {}: {err}", path.display())))
+}
+
+pub async fn load_overrides(cfg: &Config, target_name: &str) -> Result<Vec<PlaylistItemOverride>, M3uFilterError> {
+    match get_target_storage_path(cfg, target_name) {
+        Some(target_path) => {
+            let path = get_overrides_file_path(&target_path);
+            let _file_lock = cfg.file_locks.read_lock(&path).await;
+            read_overrides(&path)
+        }
+        None => Ok(vec![]),
+    }
+}
+
+/// Creates or replaces the override for the item uuid.
+pub async fn save_override(cfg: &Config, target_name: &str, item_override: PlaylistItemOverride) -> Result<(), M3uFilterError> {
+    let path = get_overrides_file_path(&ensure_target_storage_path(cfg, target_name)?);
+    let _file_lock = cfg.file_locks.write_lock(&path).await;
+    let mut overrides = read_overrides(&path)?;
+    match overrides.iter_mut().find(|o| o.uuid == item_override.uuid) {
+        Some(existing) => *existing = item_override,
+        None => overrides.push(item_override),
+    }
+    write_overrides(&path, &overrides)
+}
+
+/// Removes the override for the item uuid, returns false if there was none.
+pub async fn delete_override(cfg: &Config, target_name: &str, uuid: &str) -> Result<bool, M3uFilterError> {
+    let Some(target_path) = get_target_storage_path(cfg, target_name) else {
+        return Ok(false);
+    };
+    let path = get_overrides_file_path(&target_path);
+    let _file_lock = cfg.file_locks.write_lock(&path).await;
+    let mut overrides = read_overrides(&path)?;
+    let count = overrides.len();
+    overrides.retain(|o| !o.uuid.eq_ignore_ascii_case(uuid));
+    if overrides.len() == count {
+        return Ok(false);
+    }
+    write_overrides(&path, &overrides)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::repository::override_repository::{override_uuid, validate_override_uuid, PlaylistItemOverride};
+
+    #[test]
+    fn test_validate_override_uuid() {
+        let uuid = override_uuid(&[0xAB; 32]);
+        assert_eq!(validate_override_uuid(&format!(" {} ", uuid.to_lowercase())).unwrap(), uuid);
+        assert!(validate_override_uuid("").is_err());
+        assert!(validate_override_uuid("ABCD").is_err());
+        assert!(validate_override_uuid(&"ZZ".repeat(32)).is_err());
+        // multibyte characters must not panic on the byte based slicing
+        assert!(validate_override_uuid(&"Ä".repeat(32)).is_err());
+        assert!(validate_override_uuid(&format!("{}ÄÄ", &uuid[..60])).is_err());
+
+        let mut item_override = PlaylistItemOverride { uuid: "ü".repeat(64), ..PlaylistItemOverride::default() };
+        assert!(item_override.prepare().is_err());
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use quick_xml::escape::escape;
+use crate::m3u_filter_error::{notify_err, M3uFilterError, M3uFilterErrorKind};
+use crate::model::config::{Config, ConfigTarget, PlaylistFileTargetOutput};
+use crate::model::playlist::{PlaylistGroup, PlaylistItem, PlaylistItemType};
+use crate::utils::file::file_utils;
+use crate::utils::file::file_utils::file_writer;
+
+const XSPF_VLC_APPLICATION: &str = "http://www.videolan.org/vlc/playlist/0";
+
+fn get_playlist_items(playlist: &[PlaylistGroup]) -> impl Iterator<Item=&PlaylistItem> {
+    playlist.iter().flat_map(|pg| &pg.channels)
+        .filter(|pli| pli.header.item_type != PlaylistItemType::SeriesInfo)
+}
+
+fn write_playlist_file(cfg: &Config, filename: &str, content: &str) -> Result<(), M3uFilterError> {
+    let path = file_utils::get_file_path(&cfg.working_dir, Some(PathBuf::from(filename)))
+        .ok_or_else(|| notify_err!(format!("Invalid playlist filename {filename}")))?;
+    let file = File::create(&path).map_err(|err| notify_err!(format!("failed to write playlist: {} - {err}", path.display())))?;
+    let mut writer = file_writer(&file);
+    writer.write_all(content.as_bytes()).and_then(|()| writer.flush())
+        .map_err(|err| notify_err!(format!("failed to write playlist: {} - {err}", path.display())))
+}
+
+/// Creates a xspf playlist, the groups are written as vlc `node` extension.
+pub fn xspf_create_playlist(target: &ConfigTarget, playlist: &[PlaylistGroup]) -> String {
+    let ignore_logo = target.options.as_ref().is_some_and(|o| o.ignore_logo);
+    let mut tracks = String::new();
+    let mut nodes = String::new();
+    let mut track_id = 0;
+    for group in playlist {
+        let mut node_items = String::new();
+        for pli in group.channels.iter().filter(|pli| pli.header.item_type != PlaylistItemType::SeriesInfo) {
+            let header = &pli.header;
+            let _ = writeln!(tracks, "    <track>\n      <location>{}</location>\n      <title>{}</title>", escape(&header.url), escape(&header.title));
+            if !ignore_logo && !header.logo.is_empty() {
+                let _ = writeln!(tracks, "      <image>{}</image>", escape(&header.logo));
+            }
+            let _ = writeln!(tracks, "      <extension application=\"{XSPF_VLC_APPLICATION}\">\n        <vlc:id>{track_id}</vlc:id>\n      </extension>\n    </track>");
+            let _ = writeln!(node_items, "      <vlc:item tid=\"{track_id}\"/>");
+            track_id += 1;
+        }
+        if !node_items.is_empty() {
+            let _ = writeln!(nodes, "    <vlc:node title=\"{}\">\n{node_items}    </vlc:node>", escape(&group.title));
+        }
+    }
+    format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\" xmlns:vlc=\"http://www.videolan.org/vlc/playlist/ns/0/\">\n  <title>{}</title>\n  <trackList>\n{tracks}  </trackList>\n  <extension application=\"{XSPF_VLC_APPLICATION}\">\n{nodes}  </extension>\n</playlist>\n",
+            escape(&target.name))
+}
+
+pub fn pls_create_playlist(playlist: &[PlaylistGroup]) -> String {
+    let mut content = String::from("[playlist]\n");
+    let mut count = 0;
+    for (idx, pli) in get_playlist_items(playlist).enumerate() {
+        let number = idx + 1;
+        // a line break in the title would start a new entry
+        let title = pli.header.title.replace(['\r', '\n'], " ");
+        let _ = writeln!(content, "File{number}={}\nTitle{number}={title}\nLength{number}=-1", pli.header.url);
+        count = number;
+    }
+    let _ = writeln!(content, "NumberOfEntries={count}\nVersion=2");
+    content
+}
+
+pub fn xspf_write_playlist(cfg: &Config, target: &ConfigTarget, target_output: &PlaylistFileTargetOutput, playlist: &[PlaylistGroup]) -> Result<(), M3uFilterError> {
+    if playlist.is_empty() {
+        return Ok(());
+    }
+    write_playlist_file(cfg, &target_output.filename, &xspf_create_playlist(target, playlist))
+}
+
+pub fn pls_write_playlist(cfg: &Config, target_output: &PlaylistFileTargetOutput, playlist: &[PlaylistGroup]) -> Result<(), M3uFilterError> {
+    if playlist.is_empty() {
+        return Ok(());
+    }
+    write_playlist_file(cfg, &target_output.filename, &pls_create_playlist(playlist))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::config::{Config, ConfigInput, ConfigTarget};
+    use crate::model::playlist::{PlaylistGroup, PlaylistItem, PlaylistItemHeader, XtreamCluster};
+    use crate::processing::parser::{pls, xspf};
+    use super::{pls_create_playlist, xspf_create_playlist};
+
+    fn create_playlist() -> Vec<PlaylistGroup> {
+        let item = |title: &str, url: &str, group: &str| PlaylistItem {
+            header: PlaylistItemHeader { title: title.to_string(), url: url.to_string(), group: group.to_string(), logo: String::from("http://localhost/logo.png"), ..Default::default() }
+        };
+        vec![
+            PlaylistGroup { id: 1, title: String::from("News & Info"), xtream_cluster: XtreamCluster::Live, channels: vec![item("News 1", "http://localhost/1.ts", "News & Info")] },
+            PlaylistGroup { id: 2, title: String::from("Sports"), xtream_cluster: XtreamCluster::Live, channels: vec![item("Sport 1", "http://localhost/2.ts?a=1&b=2", "Sports"), item("Sport 2", "http://localhost/3.ts", "Sports")] },
+        ]
+    }
+
+    #[test]
+    fn test_xspf_roundtrip() {
+        let cfg = Config::default();
+        let input = ConfigInput { name: String::from("xspf"), ..Default::default() };
+        let target = ConfigTarget { name: String::from("target"), ..Default::default() };
+        let content = xspf_create_playlist(&target, &create_playlist());
+        assert!(xspf::is_xspf(&content));
+        let groups = xspf::parse_xspf(&cfg, &input, &content).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].title, "News & Info");
+        assert_eq!(groups[0].channels[0].header.logo, "http://localhost/logo.png");
+        assert_eq!(groups[1].channels.len(), 2);
+        assert_eq!(groups[1].channels[0].header.url, "http://localhost/2.ts?a=1&b=2");
+        assert!(xspf::parse_xspf(&cfg, &input, "<playlist><trackList><track></trackList></playlist>").is_err());
+    }
+
+    #[test]
+    fn test_pls_roundtrip() {
+        let cfg = Config::default();
+        let input = ConfigInput { name: String::from("pls"), ..Default::default() };
+        let content = pls_create_playlist(&create_playlist());
+        assert!(pls::is_pls(&content));
+        assert!(content.contains("NumberOfEntries=3"));
+        let groups = pls::parse_pls(&cfg, &input, &content);
+        let items: Vec<_> = groups.iter().flat_map(|g| &g.channels).collect();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[2].header.title, "Sport 2");
+
+        let mut playlist = create_playlist();
+        playlist[0].channels[0].header.title = String::from("News\r\nFile9=http://localhost/injected.ts");
+        let groups = pls::parse_pls(&cfg, &input, &pls_create_playlist(&playlist));
+        let items: Vec<_> = groups.iter().flat_map(|g| &g.channels).collect();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].header.title, "News  File9=http://localhost/injected.ts");
+    }
+}
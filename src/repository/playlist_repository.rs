@@ -3,17 +3,24 @@ use crate::m3u_filter_error::{M3uFilterError, M3uFilterErrorKind};
 use crate::model::config::{Config, ConfigTarget, TargetOutput};
 use crate::model::playlist::{PlaylistGroup, PlaylistItemType};
 use crate::model::xmltv::Epg;
+use crate::repository::enigma2_repository::enigma2_write_playlist;
 use crate::repository::epg_repository::epg_write;
+use crate::repository::json_repository::json_write_playlist;
 use crate::repository::kodi_repository::kodi_write_strm_playlist;
 use crate::repository::m3u_repository::m3u_write_playlist;
-use crate::repository::storage::{ensure_target_storage_path, get_target_id_mapping_file};
+use crate::repository::playlist_file_repository::{pls_write_playlist, xspf_write_playlist};
+use crate::repository::channel_number_mapping::ChannelNumberMapping;
+use crate::repository::storage::{ensure_target_storage_path, get_target_chno_mapping_file, get_target_id_mapping_file};
 use crate::repository::target_id_mapping::TargetIdMapping;
 use crate::repository::xtream_repository::xtream_write_playlist;
 use crate::utils::file::file_lock_manager::FileWriteGuard;
 use crate::utils::network::request::{is_dash_url, is_hls_url};
 use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use crate::tools::lru_cache::LRUResourceCache;
 
-pub async fn persist_playlist(playlist: &mut [PlaylistGroup], epg: Option<&Epg>,
+pub async fn persist_playlist(client: &Arc<reqwest::Client>, resource_cache: &Arc<Option<Mutex<LRUResourceCache>>>, playlist: &mut [PlaylistGroup], epg: Option<&Epg>,
                               target: &ConfigTarget, cfg: &Config) -> Result<(), Vec<M3uFilterError>> {
     let mut errors = vec![];
     let target_path = match ensure_target_storage_path(cfg, &target.name) {
@@ -51,8 +58,12 @@ pub async fn persist_playlist(playlist: &mut [PlaylistGroup], epg: Option<&Epg>,
         let result = match output {
             TargetOutput::Xtream(_xtream_output) => xtream_write_playlist(target, cfg, playlist).await,
             TargetOutput::M3u(m3u_output) => m3u_write_playlist(cfg, target, m3u_output, &target_path, playlist).await,
-            TargetOutput::Strm(strm_output) => kodi_write_strm_playlist(target, strm_output, cfg, playlist).await,
+            TargetOutput::Strm(strm_output) => kodi_write_strm_playlist(client, resource_cache, target, strm_output, cfg, playlist).await,
             TargetOutput::HdHomeRun(_hdhomerun_output) => Ok(()),
+            TargetOutput::Xspf(file_output) => xspf_write_playlist(cfg, target, file_output, playlist),
+            TargetOutput::Pls(file_output) => pls_write_playlist(cfg, file_output, playlist),
+            TargetOutput::Enigma2(enigma2_output) => enigma2_write_playlist(target, enigma2_output, cfg, playlist).await,
+            TargetOutput::Json(json_output) => json_write_playlist(cfg, target, json_output, &target_path, playlist).await,
         };
 
         if let Err(err) = result {
@@ -72,6 +83,12 @@ pub async fn persist_playlist(playlist: &mut [PlaylistGroup], epg: Option<&Epg>,
     if errors.is_empty() { Ok(()) } else { Err(errors) }
 }
 
+pub async fn get_target_chno_mapping(cfg: &Config, target_path: &Path) -> (ChannelNumberMapping, FileWriteGuard) {
+    let chno_mapping_file = get_target_chno_mapping_file(target_path);
+    let file_lock = cfg.file_locks.write_lock(&chno_mapping_file).await;
+    (ChannelNumberMapping::new(&chno_mapping_file), file_lock)
+}
+
 pub async fn get_target_id_mapping(cfg: &Config, target_path: &Path) -> (TargetIdMapping, FileWriteGuard) {
     let target_id_mapping_file = get_target_id_mapping_file(target_path);
     let file_lock = cfg.file_locks.write_lock(&target_id_mapping_file).await;
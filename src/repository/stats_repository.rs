@@ -0,0 +1,33 @@
+use std::fs::File;
+use std::path::PathBuf;
+use chrono::Local;
+use log::error;
+use serde_json::{json, Value};
+use crate::model::config::Config;
+use crate::model::stats::SourceStats;
+use crate::repository::storage_const;
+use crate::utils::file::file_utils::file_reader;
+use crate::utils::json_utils::json_write_documents_to_file;
+
+fn get_processing_stats_file_path(cfg: &Config) -> PathBuf {
+    PathBuf::from(&cfg.working_dir).join(storage_const::FILE_PROCESSING_STATS)
+}
+
+/// Stores the stats of the last processing run, they are shown by the status api.
+pub fn save_processing_stats(cfg: &Config, stats: &[SourceStats], secs_took: u64) {
+    let path = get_processing_stats_file_path(cfg);
+    let doc = json!({
+        "timestamp": Local::now().timestamp(),
+        "took_secs": secs_took,
+        "stats": stats,
+    });
+    if let Err(err) = json_write_documents_to_file(&path, &doc) {
+        error!("Failed to persist processing stats {}: {err}", path.display());
+    }
+}
+
+pub fn load_processing_stats(cfg: &Config) -> Option<Value> {
+    let path = get_processing_stats_file_path(cfg);
+    let file = File::open(path).ok()?;
+    serde_json::from_reader(file_reader(file)).ok()
+}
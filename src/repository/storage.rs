@@ -36,6 +36,10 @@ pub fn hex_decode(hex: &str) -> Result<Vec<u8>, String> {
     if hex.len() % 2 != 0 {
         return Err("hex string must have even length".to_string());
     }
+    // the slices below are byte based, non ascii input would panic
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err("hex string contains invalid characters".to_string());
+    }
 
     (0..hex.len())
         .step_by(2)
@@ -54,6 +58,10 @@ pub(in crate::repository) fn get_target_id_mapping_file(target_path: &Path) -> P
     target_path.join(PathBuf::from(storage_const::FILE_ID_MAPPING))
 }
 
+pub(in crate::repository) fn get_target_chno_mapping_file(target_path: &Path) -> PathBuf {
+    target_path.join(PathBuf::from(storage_const::FILE_CHNO_MAPPING))
+}
+
 pub fn ensure_target_storage_path(cfg: &Config, target_name: &str) -> Result<PathBuf, M3uFilterError> {
     if let Some(path) = get_target_storage_path(cfg, target_name) {
         if std::fs::create_dir_all(&path).is_err() {
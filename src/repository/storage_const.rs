@@ -1,13 +1,21 @@
 pub const FILE_EPG: &str = "epg.xml";
+pub const FILE_PLAYLIST: &str = "playlist.txt";
+pub const FILE_SUFFIX_HTTP_CACHE: &str = "http_cache.json";
 pub(in crate::repository) const FILE_SUFFIX_DB: &str = "db";
 pub(in crate::repository) const FILE_SUFFIX_INDEX: &str = "idx";
 pub(in crate::repository) const FILE_ID_MAPPING: &str = "id_mapping.db";
+pub(in crate::repository) const FILE_CHNO_MAPPING: &str = "chno_mapping.db";
 pub(in crate::repository) const FILE_STRM: &str = "strm";
+pub(in crate::repository) const FILE_STRM_ARTWORK_FAILED: &str = "strm_artwork_failed.json";
 pub(in crate::repository) const FILE_M3U: &str = "m3u";
+pub(in crate::repository) const FILE_JSON_PLAYLIST: &str = "playlist.json";
+pub(in crate::repository) const FILE_PROCESSING_STATS: &str = "processing_stats.json";
+pub(in crate::repository) const FILE_OVERRIDES: &str = "overrides.json";
 
 pub const FILE_SUFFIX_WAL: &str = "wal";
 pub const M3U_STREAM_PATH: &str = "m3u-stream";
 pub const M3U_RESOURCE_PATH: &str = "resource/m3u";
+pub const M3U_CATCHUP_PATH: &str = "m3u-catchup";
 
 pub const COL_CAT_LIVE: &str = "cat_live";
 pub const COL_CAT_SERIES: &str = "cat_series";
@@ -23,7 +23,7 @@ use futures::{stream, Stream, StreamExt};
 use log::error;
 use serde::Serialize;
 use serde_json::{json, Map, Value};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::fs::File;
 use std::io::{BufReader, Error, ErrorKind, Read};
@@ -197,6 +197,14 @@ fn xtream_garbage_collect(config: &Config, target_name: &str) -> std::io::Result
     Ok(())
 }
 
+fn xtream_category(cat_id: u32, name: &str, parent_id: u32) -> Value {
+    json!({
+      xtream_const::XC_TAG_CATEGORY_ID: format!("{cat_id}"),
+      xtream_const::XC_TAG_CATEGORY_NAME: name,
+      xtream_const::XC_TAG_PARENT_ID: parent_id
+    })
+}
+
 pub async fn xtream_write_playlist(
     target: &ConfigTarget,
     cfg: &Config,
@@ -214,28 +222,41 @@ pub async fn xtream_write_playlist(
     // preserve category_ids
     let (max_cat_id, existing_cat_ids) = load_old_category_ids(&path);
     let mut cat_id_counter = max_cat_id;
+    let mut assigned_cat_ids: HashMap<String, u32> = HashMap::new();
+    let mut get_cat_id = |cat_key: String| -> u32 {
+        *assigned_cat_ids.entry(cat_key).or_insert_with_key(|key| {
+            existing_cat_ids.get(key).copied().unwrap_or_else(|| {
+                cat_id_counter += 1;
+                cat_id_counter
+            })
+        })
+    };
+    // parent categories are only written when there is no group with the same name
+    let group_cats: HashSet<String> = playlist.iter().filter(|plg| !plg.channels.is_empty())
+        .map(|plg| format!("{}{}", plg.xtream_cluster, &plg.title)).collect();
+    let mut parent_cats: HashSet<String> = HashSet::new();
     for plg in playlist.iter_mut() {
         if !&plg.channels.is_empty() {
-            let cat_key = format!("{}{}", plg.xtream_cluster, &plg.title);
-            let cat_id = existing_cat_ids.get(&cat_key).unwrap_or_else(|| {
-                cat_id_counter += 1;
-                &cat_id_counter
-            });
-            plg.id = *cat_id;
-
-            match &plg.xtream_cluster {
+            let cat_id = get_cat_id(format!("{}{}", plg.xtream_cluster, &plg.title));
+            plg.id = cat_id;
+            let cat_col = match &plg.xtream_cluster {
                 XtreamCluster::Live => &mut cat_live_col,
                 XtreamCluster::Series => &mut cat_series_col,
                 XtreamCluster::Video => &mut cat_vod_col,
-            }.push(json!({
-              xtream_const::XC_TAG_CATEGORY_ID: format!("{}", &cat_id),
-              xtream_const::XC_TAG_CATEGORY_NAME: plg.title.clone(),
-              xtream_const::XC_TAG_PARENT_ID: 0
-            }));
+            };
+            let parent_id = target.get_group_parent(&plg.title).map_or(0, |parent| {
+                let parent_key = format!("{}{parent}", plg.xtream_cluster);
+                let parent_id = get_cat_id(parent_key.clone());
+                if !group_cats.contains(&parent_key) && parent_cats.insert(parent_key) {
+                    cat_col.push(xtream_category(parent_id, parent, 0));
+                }
+                parent_id
+            });
+            cat_col.push(xtream_category(cat_id, &plg.title, parent_id));
 
             for pli in &mut plg.channels {
                 let header = &mut pli.header;
-                header.category_id = *cat_id;
+                header.category_id = cat_id;
                 let col = match header.xtream_cluster {
                     XtreamCluster::Live => &mut live_col,
                     XtreamCluster::Series => &mut series_col,
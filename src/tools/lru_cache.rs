@@ -7,6 +7,11 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::RwLock;
 
+/// Returns the path of the cached file for the url inside the cache directory, the file may not exist.
+pub fn get_cache_file_path(cache_dir: &Path, url: &str) -> PathBuf {
+    cache_dir.join(hash_string_as_hex(url))
+}
+
 /// `LRUResourceCache`
 ///
 /// A least-recently-used (LRU) file-based resource cache that stores files in a directory on disk,
@@ -104,10 +109,7 @@ impl LRUResourceCache {
     }
 
     pub fn store_path(&self, url: &str) -> PathBuf {
-        let key = hash_string_as_hex(url);
-        let mut path = self.cache_dir.clone();
-        path.push(&key);
-        path
+        get_cache_file_path(&self.cache_dir, url)
     }
 
     ///   - Retrieves a file from the cache if it exists.
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::hash::BuildHasher;
+use crate::utils::constants::CONSTANTS;
+
+// the query parameters of a proxied catchup url are named after the position of the placeholder
+const CATCHUP_PARAM_PREFIX: &str = "p";
+
+/// Catchup source for a xtream live stream, placeholders are written in the common m3u catchup syntax.
+pub fn get_xtream_catchup_source(url: &str, username: &str, password: &str, stream_id: u32) -> String {
+    format!("{url}/timeshift/{username}/{password}/{{duration:60}}/{{Y}}-{{m}}-{{d}}:{{H}}-{{M}}/{stream_id}.ts")
+}
+
+fn get_xc_catchup_template(url: &str) -> Option<String> {
+    CONSTANTS.re_xtream_live_url.captures(url).map(|caps| {
+        let ext = caps.name("ext").map_or("ts", |ext| ext.as_str());
+        format!("{}/timeshift/{}/{}/{{duration:60}}/{{Y}}-{{m}}-{{d}}:{{H}}-{{M}}/{}.{ext}",
+                &caps["base"], &caps["user"], &caps["pass"], &caps["id"])
+    })
+}
+
+/// Returns the absolute catchup url template for the `catchup` mode of a stream.
+/// Modes which are derived from the stream url by the player are resolved against the provider url,
+/// unknown modes return `None`.
+pub fn get_catchup_template(catchup: &str, catchup_source: &str, url: &str) -> Option<String> {
+    match catchup.to_lowercase().as_str() {
+        "" | "default" => Some(catchup_source.to_string()).filter(|source| !source.is_empty()),
+        "append" => Some(format!("{url}{catchup_source}")).filter(|_| !catchup_source.is_empty()),
+        "shift" => Some(format!("{url}{}utc={{utc}}&lutc={{lutc}}", if url.contains('?') { '&' } else { '?' })),
+        "xc" => get_xc_catchup_template(url),
+        _ => None,
+    }
+}
+
+/// Creates the query for a proxied catchup url. Each placeholder of the template becomes a parameter,
+/// the player replaces it with the actual value.
+pub fn get_catchup_query(template: &str) -> String {
+    CONSTANTS.re_catchup_placeholder.find_iter(template).enumerate()
+        .map(|(index, placeholder)| format!("{CATCHUP_PARAM_PREFIX}{index}={}", placeholder.as_str()))
+        .collect::<Vec<String>>().join("&")
+}
+
+// The values come from the client request, everything except unreserved characters and `:` is percent encoded.
+fn encode_catchup_value(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~' | b':') {
+            encoded.push(char::from(byte));
+        } else {
+            let _ = write!(encoded, "%{byte:02X}");
+        }
+    }
+    encoded
+}
+
+/// Replaces the placeholders of the template with the percent encoded values of a proxied catchup request.
+pub fn fill_catchup_template<S: BuildHasher>(template: &str, params: &HashMap<String, String, S>) -> String {
+    let mut index = 0;
+    CONSTANTS.re_catchup_placeholder.replace_all(template, |_: &regex::Captures| {
+        let value = params.get(&format!("{CATCHUP_PARAM_PREFIX}{index}")).map_or_else(String::new, |value| encode_catchup_value(value));
+        index += 1;
+        value
+    }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::utils::catchup_utils::{fill_catchup_template, get_catchup_query, get_catchup_template, get_xtream_catchup_source};
+
+    #[test]
+    fn test_catchup_template() {
+        let url = "http://provider.net/live/user/pass/123.ts";
+        let xc_template = get_catchup_template("xc", "", url).unwrap();
+        assert_eq!(xc_template, get_xtream_catchup_source("http://provider.net", "user", "pass", 123));
+        assert_eq!(get_catchup_template("shift", "", url).unwrap(), format!("{url}?utc={{utc}}&lutc={{lutc}}"));
+        assert_eq!(get_catchup_template("append", "?start=${start}", url).unwrap(), format!("{url}?start=${{start}}"));
+        assert!(get_catchup_template("default", "", url).is_none());
+        assert!(get_catchup_template("flussonic", "", url).is_none());
+
+        let query = get_catchup_query(&xc_template);
+        assert_eq!(query, "p0={duration:60}&p1={Y}&p2={m}&p3={d}&p4={H}&p5={M}");
+        let params: HashMap<String, String> = [("p0", "60"), ("p1", "2025"), ("p2", "03"), ("p3", "01"), ("p4", "20"), ("p5", "15")]
+            .iter().map(|(key, value)| ((*key).to_string(), (*value).to_string())).collect();
+        assert_eq!(fill_catchup_template(&xc_template, &params), "http://provider.net/timeshift/user/pass/60/2025-03-01:20-15/123.ts");
+        let params: HashMap<String, String> = [("p0", "60/../../x"), ("p1", "2025?a=b&c")]
+            .iter().map(|(key, value)| ((*key).to_string(), (*value).to_string())).collect();
+        assert_eq!(fill_catchup_template(&xc_template, &params), "http://provider.net/timeshift/user/pass/60%2F..%2F..%2Fx/2025%3Fa%3Db%26c--:-/123.ts");
+    }
+}
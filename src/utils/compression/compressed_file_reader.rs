@@ -1,7 +1,6 @@
-use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
-use flate2::bufread::{GzDecoder, ZlibDecoder};
-use crate::utils::compression::compression_utils::{is_deflate, is_gzip};
+use crate::utils::compression::compression_utils::get_decompressed_reader;
 use crate::utils::file::file_utils::{file_reader, open_readonly_file};
 
 pub struct CompressedFileReader {
@@ -11,20 +10,7 @@ pub struct CompressedFileReader {
 impl CompressedFileReader {
     pub fn new(path: &Path) -> std::io::Result<Self> {
         let file = open_readonly_file(path)?;
-
-        let mut buffered_file = file_reader(file);
-        let mut header = [0u8; 2];
-        buffered_file.read_exact(&mut header)?;
-        buffered_file.seek(SeekFrom::Start(0))?;
-
-        let reader: Box<dyn Read> = if is_gzip(&header) {
-            Box::new(GzDecoder::new(buffered_file))
-        } else if is_deflate(&header) {
-            Box::new(ZlibDecoder::new(buffered_file))
-        } else {
-            Box::new(buffered_file)
-        };
-
+        let reader = get_decompressed_reader(file_reader(file))?;
         Ok(Self {
             reader: BufReader::new(reader),
         })
@@ -1,7 +1,11 @@
-use std::io::{Read, Write};
+use std::io::{BufRead, Cursor, Read, Seek, SeekFrom, Write};
 use flate2::Compression;
 use flate2::read::GzDecoder;
 use flate2::write::GzEncoder;
+use zip::{CompressionMethod, ZipArchive};
+
+// the longest magic number is the xz header
+const MAGIC_BYTES_LEN: usize = 6;
 
 pub const fn is_gzip(bytes: &[u8]) -> bool {
     // Gzip files start with the bytes 0x1F 0x8B
@@ -9,7 +13,88 @@ pub const fn is_gzip(bytes: &[u8]) -> bool {
 }
 
 pub const fn is_deflate(bytes: &[u8]) -> bool {
-    bytes[0] == 0x78 && (bytes[1] == 0x01 || bytes[1] == 0x9C || bytes[1] == 0xDA)
+    bytes.len() >= 2 && bytes[0] == 0x78 && (bytes[1] == 0x01 || bytes[1] == 0x9C || bytes[1] == 0xDA)
+}
+
+pub const fn is_xz(bytes: &[u8]) -> bool {
+    // Xz files start with the bytes 0xFD '7' 'z' 'X' 'Z' 0x00
+    bytes.len() >= 6 && bytes[0] == 0xFD && bytes[1] == b'7' && bytes[2] == b'z'
+        && bytes[3] == b'X' && bytes[4] == b'Z' && bytes[5] == 0x00
+}
+
+pub const fn is_bzip2(bytes: &[u8]) -> bool {
+    // Bzip2 files start with 'B' 'Z' 'h'
+    bytes.len() >= 3 && bytes[0] == b'B' && bytes[1] == b'Z' && bytes[2] == b'h'
+}
+
+pub const fn is_zip(bytes: &[u8]) -> bool {
+    // Zip files start with a local file header 'P' 'K' 0x03 0x04
+    bytes.len() >= 4 && bytes[0] == b'P' && bytes[1] == b'K' && bytes[2] == 0x03 && bytes[3] == 0x04
+}
+
+pub const fn is_compressed(bytes: &[u8]) -> bool {
+    is_gzip(bytes) || is_deflate(bytes) || is_xz(bytes) || is_bzip2(bytes) || is_zip(bytes)
+}
+
+// Opens the single file of a zip archive. The entry is read straight from the archive,
+// only stored and deflated entries are supported.
+fn open_zip_entry<'a, R: Read + Seek + 'a>(reader: R) -> std::io::Result<Box<dyn Read + 'a>> {
+    let mut archive = ZipArchive::new(reader).map_err(std::io::Error::other)?;
+    let mut entry_info = None;
+    for index in 0..archive.len() {
+        let entry = archive.by_index_raw(index).map_err(std::io::Error::other)?;
+        if entry.is_file() {
+            if entry_info.is_some() {
+                return Err(std::io::Error::other("zip archive contains more than one file"));
+            }
+            entry_info = Some((entry.data_start(), entry.compressed_size(), entry.compression()));
+        }
+    }
+    let Some((Some(data_start), compressed_size, compression)) = entry_info else {
+        return Err(std::io::Error::other("zip archive contains no file"));
+    };
+    let mut reader = archive.into_inner();
+    reader.seek(SeekFrom::Start(data_start))?;
+    let entry_reader = reader.take(compressed_size);
+    if compression == CompressionMethod::Stored {
+        Ok(Box::new(entry_reader))
+    } else if compression == CompressionMethod::DEFLATE {
+        Ok(Box::new(flate2::read::DeflateDecoder::new(entry_reader)))
+    } else {
+        Err(std::io::Error::other(format!("unsupported zip compression {compression}")))
+    }
+}
+
+/// Returns a reader for the decompressed content. The compression (gzip, deflate, xz, bzip2 or zip with a single file)
+/// is detected by the magic bytes, uncompressed content is read as is.
+pub fn get_decompressed_reader<'a, R: BufRead + Seek + 'a>(mut reader: R) -> std::io::Result<Box<dyn Read + 'a>> {
+    let mut magic = [0u8; MAGIC_BYTES_LEN];
+    let header = reader.fill_buf()?;
+    let magic_len = header.len().min(MAGIC_BYTES_LEN);
+    magic[..magic_len].copy_from_slice(&header[..magic_len]);
+    let magic = &magic[..magic_len];
+
+    let decompressed: Box<dyn Read + 'a> = if is_gzip(magic) {
+        Box::new(flate2::bufread::GzDecoder::new(reader))
+    } else if is_deflate(magic) {
+        Box::new(flate2::bufread::ZlibDecoder::new(reader))
+    } else if is_xz(magic) {
+        Box::new(lzma_rust2::XzReader::new(reader, true))
+    } else if is_bzip2(magic) {
+        Box::new(bzip2::bufread::MultiBzDecoder::new(reader))
+    } else if is_zip(magic) {
+        open_zip_entry(reader)?
+    } else {
+        Box::new(reader)
+    };
+    Ok(decompressed)
+}
+
+/// Decompresses the content, the compression is detected by the magic bytes.
+pub fn decompress_to_string(bytes: &[u8]) -> std::io::Result<String> {
+    let mut decompressed = String::new();
+    get_decompressed_reader(Cursor::new(bytes))?.read_to_string(&mut decompressed)?;
+    Ok(decompressed)
 }
 
 pub fn compress_string(input: &str) -> std::io::Result<Vec<u8>> {
@@ -24,3 +109,43 @@ pub fn decompress_string(input: &[u8]) -> std::io::Result<String> {
     decoder.read_to_string(&mut decompressed)?;
     Ok(decompressed)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use crate::utils::compression::compression_utils::{compress_string, decompress_to_string, is_compressed};
+
+    const CONTENT: &str = "#EXTM3U\n";
+
+    // "#EXTM3U\n" compressed with xz
+    const XZ_CONTENT: [u8; 64] = [0xFD, 0x37, 0x7A, 0x58, 0x5A, 0x00, 0x00, 0x04, 0xE6, 0xD6, 0xB4, 0x46, 0x02, 0x00, 0x21, 0x01,
+        0x16, 0x00, 0x00, 0x00, 0x74, 0x2F, 0xE5, 0xA3, 0x01, 0x00, 0x07, 0x23, 0x45, 0x58, 0x54, 0x4D,
+        0x33, 0x55, 0x0A, 0x00, 0x66, 0x0E, 0xC9, 0xA4, 0x2F, 0xEE, 0xDA, 0xAF, 0x00, 0x01, 0x20, 0x08,
+        0xBB, 0x19, 0xD9, 0xBB, 0x1F, 0xB6, 0xF3, 0x7D, 0x01, 0x00, 0x00, 0x00, 0x00, 0x04, 0x59, 0x5A];
+
+    // zip archive with the deflated file playlist.m3u containing "#EXTM3U\n"
+    const ZIP_CONTENT: [u8; 132] = [0x50, 0x4B, 0x03, 0x04, 0x14, 0x00, 0x00, 0x00, 0x08, 0x00, 0x17, 0x96, 0x52, 0x5D, 0xB5, 0x98,
+        0xEC, 0x41, 0x0A, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x0C, 0x00, 0x00, 0x00, 0x70, 0x6C,
+        0x61, 0x79, 0x6C, 0x69, 0x73, 0x74, 0x2E, 0x6D, 0x33, 0x75, 0x53, 0x76, 0x8D, 0x08, 0xF1, 0x35,
+        0x0E, 0xE5, 0x02, 0x00, 0x50, 0x4B, 0x01, 0x02, 0x14, 0x03, 0x14, 0x00, 0x00, 0x00, 0x08, 0x00,
+        0x17, 0x96, 0x52, 0x5D, 0xB5, 0x98, 0xEC, 0x41, 0x0A, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00,
+        0x0C, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x01, 0x00, 0x00,
+        0x00, 0x00, 0x70, 0x6C, 0x61, 0x79, 0x6C, 0x69, 0x73, 0x74, 0x2E, 0x6D, 0x33, 0x75, 0x50, 0x4B,
+        0x05, 0x06, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x01, 0x00, 0x3A, 0x00, 0x00, 0x00, 0x34, 0x00,
+        0x00, 0x00, 0x00, 0x00];
+
+    #[test]
+    fn test_decompress_by_magic_bytes() {
+        let gzip_content = compress_string(CONTENT).unwrap();
+        let mut bzip2_encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        bzip2_encoder.write_all(CONTENT.as_bytes()).unwrap();
+        let bzip2_content = bzip2_encoder.finish().unwrap();
+
+        for compressed in [gzip_content.as_slice(), bzip2_content.as_slice(), XZ_CONTENT.as_slice(), ZIP_CONTENT.as_slice()] {
+            assert!(is_compressed(compressed));
+            assert_eq!(decompress_to_string(compressed).unwrap(), CONTENT);
+        }
+        assert!(!is_compressed(CONTENT.as_bytes()));
+        assert_eq!(decompress_to_string(CONTENT.as_bytes()).unwrap(), CONTENT);
+    }
+}
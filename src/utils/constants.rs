@@ -58,7 +58,10 @@ pub struct Constants {
     pub re_filename: Regex,
     pub re_remove_filename_ending: Regex,
     pub re_whitespace: Regex,
+    pub re_episode_pattern: Regex,
     pub re_hls_uri: Regex,
+    pub re_catchup_placeholder: Regex,
+    pub re_xtream_live_url: Regex,
     pub sanitize: AtomicBool,
     pub kodi_style: KodiStyle,
     pub country_codes: HashSet<&'static str>,
@@ -82,7 +85,10 @@ pub static CONSTANTS: LazyLock<Constants> = LazyLock::new(||
         re_filename: Regex::new(r"[^A-Za-z0-9_.-]").unwrap(),
         re_remove_filename_ending: Regex::new(r"[_.\s-]$").unwrap(),
         re_whitespace: Regex::new(r"\s+").unwrap(),
+        re_episode_pattern: Regex::new(r"(?P<episode>[Ss]\d{1,2}[\s._-]*[Ee]\d{1,3})").unwrap(),
         re_hls_uri: Regex::new(r#"URI="([^"]+)""#).unwrap(),
+        re_catchup_placeholder: Regex::new(r"\$?\{[^{}]+}").unwrap(),
+        re_xtream_live_url: Regex::new(r"^(?P<base>https?://.+?)/(?:live/)?(?P<user>[^/]+)/(?P<pass>[^/]+)/(?P<id>\d+)(?:\.(?P<ext>[a-zA-Z0-9]+))?$").unwrap(),
 
         sanitize: AtomicBool::new(true),
         kodi_style: KodiStyle {
@@ -10,4 +10,16 @@ pub const fn default_resolve_delay_secs() -> u16 { 2 }
 // helping avoid triggering hard max_connection enforcement.
 pub const fn default_grace_period_millis() -> u64 { 2000 }
 pub const fn default_grace_period_timeout_secs() -> u64 { 5 }
-pub const fn default_connect_timeout_secs() -> u32 { 10 }
\ No newline at end of file
+pub const fn default_connect_timeout_secs() -> u32 { 10 }
+
+// Default backoff values for retrying failed input downloads.
+pub const fn default_retry_delay_secs() -> u16 { 5 }
+pub const fn default_retry_max_delay_secs() -> u16 { 300 }
+
+// Default values for polling the provider accounts and warning before they expire.
+pub const fn default_account_monitor_interval_mins() -> u32 { 360 }
+pub const fn default_account_expiry_warning_days() -> u16 { 7 }
+
+// Default values for the token header injected after the login request of an input.
+pub fn default_auth_header() -> String { String::from("Authorization") }
+pub fn default_auth_prefix() -> String { String::from("Bearer ") }
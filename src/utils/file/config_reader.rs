@@ -11,10 +11,12 @@ use crate::utils::sys_utils::exit;
 use chrono::Local;
 use log::{debug, error, info, warn};
 use serde::Serialize;
+use std::collections::HashMap;
 use std::env;
 use std::fs::File;
 use std::io::{self, BufRead, BufReader, Cursor, Error, Read};
 use std::path::PathBuf;
+use std::sync::Arc;
 use url::Url;
 
 enum EitherReader<L, R> {
@@ -189,6 +191,7 @@ const FIELD_URL: &str = "url";
 const FIELD_NAME: &str = "name";
 const FIELD_USERNAME: &str = "username";
 const FIELD_PASSWORD: &str = "password";
+const FIELD_PROXY: &str = "proxy";
 const FIELD_UNKNOWN: &str = "?";
 const DEFAULT_COLUMNS: &[&str] = &[FIELD_URL, FIELD_MAX_CON, FIELD_PRIO, FIELD_NAME, FIELD_USERNAME, FIELD_PASSWORD];
 
@@ -257,15 +260,46 @@ fn csv_assign_config_input_column(config_input: &mut ConfigInputAlias, header: &
             FIELD_PASSWORD => {
                 config_input.password = Some(value.to_string());
             }
+            FIELD_PROXY => {
+                config_input.proxy = Some(value.to_string());
+            }
             _ => {}
         }
     }
     Ok(())
 }
 
+/// Splits a csv line into values, separators inside double quotes are part of the value
+/// and a doubled quote inside quotes is an escaped quote. The values of batch inputs are not trimmed.
+fn csv_split_line(line: &str, separator: char, trim: bool) -> Vec<String> {
+    let finish = |value: String| if trim { value.trim().to_string() } else { value };
+    let mut values = vec![];
+    let mut value = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                value.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if value.trim().is_empty() => {
+                quoted = true;
+                value.clear();
+            }
+            c if c == separator && !quoted => values.push(finish(std::mem::take(&mut value))),
+            c => value.push(c),
+        }
+    }
+    values.push(finish(value));
+    values
+}
+
 pub fn csv_read_inputs_from_reader(batch_input_type: InputType, reader: impl BufRead) -> Result<Vec<ConfigInputAlias>, io::Error> {
     let input_type = match batch_input_type {
-        InputType::M3uBatch | InputType::M3u => InputType::M3uBatch,
+        InputType::M3uBatch | InputType::M3u | InputType::Local | InputType::Stalker | InputType::HdHomeRun
+        | InputType::Json | InputType::Csv => InputType::M3uBatch,
         InputType::XtreamBatch | InputType::Xtream => InputType::XtreamBatch
     };
     let mut result = vec![];
@@ -280,14 +314,15 @@ pub fn csv_read_inputs_from_reader(batch_input_type: InputType, reader: impl Buf
         if line.starts_with(HEADER_PREFIX) {
             if !header_defined {
                 header_defined = true;
-                default_columns = line[1..].split(CSV_SEPARATOR).map(|s| {
-                    match s {
+                default_columns = csv_split_line(&line[1..], CSV_SEPARATOR, true).iter().map(|s| {
+                    match s.as_str() {
                         FIELD_URL => FIELD_URL,
                         FIELD_MAX_CON => FIELD_MAX_CON,
                         FIELD_PRIO => FIELD_PRIO,
                         FIELD_NAME => FIELD_NAME,
                         FIELD_USERNAME => FIELD_USERNAME,
                         FIELD_PASSWORD => FIELD_PASSWORD,
+                        FIELD_PROXY => FIELD_PROXY,
                         _ => {
                             error!("Field {s} is unsupported for csv input");
                             FIELD_UNKNOWN
@@ -306,11 +341,14 @@ pub fn csv_read_inputs_from_reader(batch_input_type: InputType, reader: impl Buf
             password: None,
             priority: 0,
             max_connections: 1,
+            proxy: None,
             t_base_url: String::default(),
+            t_client: None,
+            t_portal_token: Arc::default(),
         };
 
-        let columns: Vec<&str> = line.split(CSV_SEPARATOR).collect();
-        for (&header, &value) in default_columns.iter().zip(columns.iter()) {
+        let columns = csv_split_line(&line, CSV_SEPARATOR, false);
+        for (&header, value) in default_columns.iter().zip(columns.iter()) {
             if let Err(err) = csv_assign_config_input_column(&mut config_input, header, value) {
                 error!("Could not parse input line: {line} err: {err}");
             }
@@ -321,6 +359,30 @@ pub fn csv_read_inputs_from_reader(batch_input_type: InputType, reader: impl Buf
     Ok(result)
 }
 
+/// Reads the rows of a csv file as records keyed by the column names of the first line.
+/// The header line can be prefixed with `#` like the batch input files.
+pub fn csv_read_records(reader: impl BufRead, separator: Option<char>) -> Result<Vec<HashMap<String, String>>, io::Error> {
+    let separator = separator.unwrap_or(CSV_SEPARATOR);
+    let mut columns: Option<Vec<String>> = None;
+    let mut result = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim_start_matches('\u{feff}').trim_end_matches('\r');
+        if line.trim().is_empty() {
+            continue;
+        }
+        match columns.as_ref() {
+            None => {
+                let header = line.strip_prefix(HEADER_PREFIX).unwrap_or(line);
+                columns = Some(csv_split_line(header, separator, true));
+            }
+            Some(header) => {
+                result.push(header.iter().cloned().zip(csv_split_line(line, separator, true)).collect());
+            }
+        }
+    }
+    Ok(result)
+}
 
 pub fn csv_read_inputs(input: &ConfigInput) -> Result<Vec<ConfigInputAlias>, io::Error> {
     let file_uri = input.url.to_string();
@@ -354,7 +416,7 @@ fn get_csv_file_path(file_uri: &String) -> Result<PathBuf, Error> {
 #[cfg(test)]
 mod tests {
     use crate::model::config::InputType;
-    use crate::utils::file::config_reader::{csv_read_inputs_from_reader, resolve_env_var};
+    use crate::utils::file::config_reader::{csv_read_inputs_from_reader, csv_read_records, resolve_env_var};
     use std::io::{BufReader, Cursor};
     const M3U_BATCH: &str = r#"
 #url;name;max_connections;priority
@@ -423,4 +485,34 @@ input_2;desanocra;eyCG8SN523KQ;http://provider_2.tv:8080;1
         let resolved = resolve_env_var("${env:HOME}");
         assert_eq!(resolved, std::env::var("HOME").unwrap());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_read_records() {
+        let content = "#name;url;group\r\n\"Channel 1\";http://localhost/1.ts;News\r\n\r\nChannel 2;http://localhost/2.ts\r\n";
+        let records = csv_read_records(BufReader::new(Cursor::new(content)), None).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].get("name").unwrap(), "Channel 1");
+        assert_eq!(records[0].get("group").unwrap(), "News");
+        assert_eq!(records[1].get("url").unwrap(), "http://localhost/2.ts");
+        assert!(!records[1].contains_key("group"));
+    }
+
+    #[test]
+    fn test_read_quoted_separator() {
+        let content = "#name;url;group\n\"News; Sports\";\"http://localhost/1.ts?a=1;b=2\";\"The \"\"Best\"\"\"\n";
+        let records = csv_read_records(BufReader::new(Cursor::new(content)), None).unwrap();
+        assert_eq!(records[0].get("name").unwrap(), "News; Sports");
+        assert_eq!(records[0].get("url").unwrap(), "http://localhost/1.ts?a=1;b=2");
+        assert_eq!(records[0].get("group").unwrap(), "The \"Best\"");
+
+        let content = "#url;name\n\"http://provider.tv/get.php?username=u1&password=p;1\";input_1\n";
+        let aliases = csv_read_inputs_from_reader(InputType::M3uBatch, BufReader::new(Cursor::new(content))).unwrap();
+        assert_eq!(aliases[0].url, "http://provider.tv/get.php?username=u1&password=p;1");
+        assert_eq!(aliases[0].name, "input_1");
+
+        // the header columns are trimmed like the record values
+        let content = "#url; name\nhttp://provider.tv/get.php?username=u1&password=p1;input_1\n";
+        let aliases = csv_read_inputs_from_reader(InputType::M3uBatch, BufReader::new(Cursor::new(content))).unwrap();
+        assert_eq!(aliases[0].name, "input_1");
+    }
+}
@@ -178,6 +178,14 @@ pub fn get_u32_from_serde_value(value: &Value) -> Option<u32> {
     get_u64_from_serde_value(value).and_then(|val| u32::try_from(val).ok())
 }
 
+/// Resolves a dot separated path like `stream.urls.0` inside a json document.
+pub fn get_json_path<'a>(doc: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').filter(|key| !key.is_empty()).try_fold(doc, |value, key| match value {
+        Value::Array(list) => key.parse::<usize>().ok().and_then(|idx| list.get(idx)),
+        _ => value.get(key),
+    })
+}
+
 pub fn get_string_from_serde_value(value: &Value) -> Option<String> {
     match value {
         Value::Number(num_val) => num_val.as_i64().map(|num| num.to_string()),
@@ -12,6 +12,7 @@ pub mod time_utils;
 pub mod crypto_utils;
 pub mod constants;
 pub mod step_measure;
+pub mod catchup_utils;
 
 #[macro_export]
 macro_rules! debug_if_enabled {
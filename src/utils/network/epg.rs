@@ -2,6 +2,7 @@ use crate::m3u_filter_error::M3uFilterError;
 use crate::model::config::{Config, ConfigInput};
 use crate::model::xmltv::TVGuide;
 use crate::repository::storage::short_hash;
+use crate::repository::storage_const;
 use crate::utils::file::file_utils;
 use crate::utils::file::file_utils::prepare_file_path;
 use crate::utils::network::request;
@@ -16,7 +17,7 @@ async fn download_epg_file(url: &str, client: &Arc<reqwest::Client>, input: &Con
     let persist_file_path = prepare_file_path(input.persist.as_deref(), working_dir, "")
         .map(|path| file_utils::add_prefix_to_filename(&path, format!("{file_prefix}_epg_").as_str(), Some("xml")));
 
-    request::get_input_text_content_as_file(Arc::clone(client), input, working_dir, url, persist_file_path).await
+    request::get_input_text_content_as_file(Arc::clone(client), input, working_dir, url, storage_const::FILE_EPG, persist_file_path).await
 }
 
 pub async fn get_xmltv(client: Arc<reqwest::Client>, _cfg: &Config, input: &ConfigInput, working_dir: &str) -> (Option<TVGuide>, Vec<M3uFilterError>) {
@@ -0,0 +1,38 @@
+use std::sync::Arc;
+use log::warn;
+use serde_json::Value;
+use crate::m3u_filter_error::M3uFilterError;
+use crate::model::config::ConfigInput;
+use crate::model::playlist::PlaylistGroup;
+use crate::processing::parser::hdhomerun;
+use crate::processing::parser::hdhomerun::get_hdhomerun_base_url;
+use crate::utils::network::request;
+
+async fn get_discover(client: Arc<reqwest::Client>, input: &ConfigInput, url: &str) -> Result<Value, M3uFilterError> {
+    let discover_url = format!("{}/discover.json", get_hdhomerun_base_url(url));
+    request::get_input_json_content(client, input, &discover_url, None).await
+}
+
+/// Reads the tuner count from `discover.json`, the url can be the input or an alias url.
+pub async fn get_hdhomerun_tuner_count(client: Arc<reqwest::Client>, input: &ConfigInput, url: &str) -> Option<u16> {
+    match get_discover(client, input, url).await {
+        Ok(discover) => hdhomerun::get_hdhomerun_tuner_count(&discover),
+        Err(err) => {
+            warn!("Failed to read tuner count for input {}: {err}", input.name);
+            None
+        }
+    }
+}
+
+pub async fn get_hdhomerun_playlist(client: Arc<reqwest::Client>, input: &ConfigInput) -> (Vec<PlaylistGroup>, Vec<M3uFilterError>) {
+    let discover = match get_discover(Arc::clone(&client), input, &input.url).await {
+        Ok(discover) => discover,
+        Err(err) => return (vec![], vec![err]),
+    };
+    let lineup_url = discover.get("LineupURL").and_then(Value::as_str)
+        .map_or_else(|| format!("{}/lineup.json", get_hdhomerun_base_url(&input.url)), ToString::to_string);
+    match request::get_input_json_content(client, input, &lineup_url, None).await {
+        Ok(lineup) => (hdhomerun::parse_hdhomerun_lineup(input, &discover, &lineup), vec![]),
+        Err(err) => (vec![], vec![err]),
+    }
+}
@@ -0,0 +1,106 @@
+use std::fs;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use log::{debug, error};
+use reqwest::header::{HeaderMap, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+use crate::repository::storage_const;
+
+/// Validators of a downloaded file, used to send conditional requests for the next download.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HttpCacheInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+impl HttpCacheInfo {
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let get_header = |name| headers.get(name).and_then(|value: &HeaderValue| value.to_str().ok()).map(String::from);
+        let etag = get_header(ETAG);
+        let last_modified = get_header(LAST_MODIFIED);
+        if etag.is_none() && last_modified.is_none() {
+            None
+        } else {
+            Some(Self { etag, last_modified })
+        }
+    }
+
+    pub fn get_conditional_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(value) = self.etag.as_ref().and_then(|v| HeaderValue::from_str(v).ok()) {
+            headers.insert(IF_NONE_MATCH, value);
+        }
+        if let Some(value) = self.last_modified.as_ref().and_then(|v| HeaderValue::from_str(v).ok()) {
+            headers.insert(IF_MODIFIED_SINCE, value);
+        }
+        headers
+    }
+}
+
+pub fn get_http_cache_info_path(file_path: &Path) -> PathBuf {
+    let mut path = file_path.as_os_str().to_os_string();
+    path.push(".");
+    path.push(storage_const::FILE_SUFFIX_HTTP_CACHE);
+    PathBuf::from(path)
+}
+
+/// Returns the stored validators, only if the cached file still exists.
+pub fn read_http_cache_info(file_path: &Path) -> Option<HttpCacheInfo> {
+    if !file_path.is_file() {
+        return None;
+    }
+    let content = fs::read_to_string(get_http_cache_info_path(file_path)).ok()?;
+    serde_json::from_str::<HttpCacheInfo>(&content).ok()
+}
+
+/// Stores the validators for the file, or removes stale ones if the server did not send any.
+pub fn write_http_cache_info(file_path: &Path, cache_info: Option<&HttpCacheInfo>) {
+    let info_path = get_http_cache_info_path(file_path);
+    match cache_info {
+        Some(info) => {
+            let result = File::create(&info_path).map(BufWriter::new)
+                .and_then(|writer| serde_json::to_writer(writer, info).map_err(std::io::Error::from));
+            if let Err(err) = result {
+                error!("Failed to write http cache info {}: {err}", info_path.display());
+            }
+        }
+        None => {
+            if info_path.exists() {
+                if let Err(err) = fs::remove_file(&info_path) {
+                    debug!("Failed to remove http cache info {}: {err}", info_path.display());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{HeaderMap, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+    use crate::utils::network::http_cache::{read_http_cache_info, write_http_cache_info, HttpCacheInfo};
+
+    #[test]
+    fn test_http_cache_info() {
+        let mut response_headers = HeaderMap::new();
+        response_headers.insert(ETAG, HeaderValue::from_static("\"abc\""));
+        response_headers.insert(LAST_MODIFIED, HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"));
+        let info = HttpCacheInfo::from_headers(&response_headers).unwrap();
+        let request_headers = info.get_conditional_headers();
+        assert_eq!(request_headers.get(IF_NONE_MATCH).unwrap(), "\"abc\"");
+        assert_eq!(request_headers.get(IF_MODIFIED_SINCE).unwrap(), "Wed, 21 Oct 2015 07:28:00 GMT");
+        assert!(HttpCacheInfo::from_headers(&HeaderMap::new()).is_none());
+
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("epg.xml");
+        write_http_cache_info(&file_path, Some(&info));
+        // no cached file, no conditional request
+        assert!(read_http_cache_info(&file_path).is_none());
+        std::fs::write(&file_path, "<tv></tv>").unwrap();
+        assert_eq!(read_http_cache_info(&file_path), Some(info));
+        write_http_cache_info(&file_path, None);
+        assert!(read_http_cache_info(&file_path).is_none());
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::io::Error;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use log::debug;
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, COOKIE, SET_COOKIE};
+use serde_json::Value;
+use crate::m3u_filter_error::str_to_io_error;
+use crate::model::config::{ConfigInput, ConfigInputAuth, InputFetchMethod};
+use crate::utils::json_utils::{get_json_path, get_string_from_serde_value, get_u64_from_serde_value};
+use crate::utils::network::request::{get_request_headers, sanitize_sensitive_info};
+
+// The token is refreshed shortly before it expires, so running requests don't fail.
+const TOKEN_REFRESH_MARGIN_SECS: u64 = 30;
+
+/// The token issued by the login request of an input.
+#[derive(Debug, Clone)]
+pub struct InputAuthToken {
+    value: String,
+    expires_at: Option<Instant>,
+}
+
+impl InputAuthToken {
+    fn is_valid(&self) -> bool {
+        self.expires_at.is_none_or(|expires_at| Instant::now() < expires_at)
+    }
+}
+
+fn get_cookie_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers.get_all(SET_COOKIE).iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(|cookie| cookie.split(';').next())
+        .find_map(|pair| pair.split_once('=')
+            .filter(|(key, _)| key.trim() == name)
+            .map(|(_, value)| value.trim().to_string()))
+}
+
+fn get_expires_at(auth: &ConfigInputAuth, content: Option<&Value>) -> Option<Instant> {
+    let lifetime_secs = auth.expires_path.as_ref()
+        .and_then(|path| content.and_then(|doc| get_json_path(doc, path)))
+        .and_then(get_u64_from_serde_value)
+        .or(Some(auth.lifetime_secs).filter(|&secs| secs > 0))?;
+    Some(Instant::now() + Duration::from_secs(lifetime_secs.saturating_sub(TOKEN_REFRESH_MARGIN_SECS)))
+}
+
+fn parse_login_response(auth: &ConfigInputAuth, headers: &HeaderMap, body: &str) -> Result<InputAuthToken, String> {
+    let content = serde_json::from_str::<Value>(body).ok();
+    let value = match (auth.cookie.as_ref(), auth.token_path.as_ref()) {
+        (Some(cookie), _) => get_cookie_value(headers, cookie)
+            .ok_or_else(|| format!("cookie {cookie} not found in login response"))?,
+        (None, Some(token_path)) => content.as_ref()
+            .and_then(|doc| get_json_path(doc, token_path))
+            .and_then(get_string_from_serde_value)
+            .ok_or_else(|| format!("token {token_path} not found in login response"))?,
+        (None, None) => return Err("no token_path or cookie configured".to_string()),
+    };
+    Ok(InputAuthToken { value, expires_at: get_expires_at(auth, content.as_ref()) })
+}
+
+async fn login(client: &reqwest::Client, auth: &ConfigInputAuth) -> Result<InputAuthToken, Error> {
+    let masked_url = sanitize_sensitive_info(&auth.url);
+    let mut request = match auth.method {
+        InputFetchMethod::GET => client.get(&auth.url).query(&auth.form),
+        InputFetchMethod::POST => client.post(&auth.url),
+    }.headers(get_request_headers(Some(&auth.headers), None));
+    if let Some(json) = auth.json.as_ref() {
+        request = request.json(json);
+    } else if auth.method == InputFetchMethod::POST && !auth.form.is_empty() {
+        request = request.form(&auth.form);
+    }
+    let response = request.send().await
+        .map_err(|err| str_to_io_error(&format!("Login request failed {masked_url} {err}")))?;
+    if !response.status().is_success() {
+        return Err(str_to_io_error(&format!("Login request failed with status {} {masked_url}", response.status())));
+    }
+    let headers = response.headers().clone();
+    let body = response.text().await
+        .map_err(|err| str_to_io_error(&format!("Failed to read login response {masked_url} {err}")))?;
+    parse_login_response(auth, &headers, &body)
+        .map_err(|err| str_to_io_error(&format!("Login failed {masked_url}: {err}")))
+}
+
+fn get_token_header(auth: &ConfigInputAuth, token: &InputAuthToken) -> (String, String) {
+    match auth.cookie.as_ref() {
+        Some(cookie) => (COOKIE.to_string(), format!("{cookie}={}", token.value)),
+        None => (auth.header.clone(), format!("{}{}", auth.prefix, token.value)),
+    }
+}
+
+// the lock is held during login, concurrent requests wait for the same token
+async fn get_valid_token(client: &reqwest::Client, auth: &ConfigInputAuth, input_name: &str) -> Result<InputAuthToken, Error> {
+    let mut token_guard = auth.t_token.lock().await;
+    if let Some(token) = token_guard.as_ref().filter(|token| token.is_valid()) {
+        return Ok(token.clone());
+    }
+    let token = login(client, auth).await?;
+    debug!("Logged in for input {input_name}");
+    *token_guard = Some(token.clone());
+    Ok(token)
+}
+
+/// Returns the configured headers of the input, if the input has an `auth` block the token is added.
+/// A login is performed when there is no token yet or the token expired.
+pub async fn get_input_headers(client: &Arc<reqwest::Client>, input: &ConfigInput) -> Result<HashMap<String, String>, Error> {
+    let mut headers = input.headers.clone();
+    if let Some(auth) = input.auth.as_ref() {
+        let http_client = input.get_http_client(client, &auth.url);
+        let token = get_valid_token(&http_client, auth, &input.name).await?;
+        let (name, value) = get_token_header(auth, &token);
+        headers.insert(name, value);
+    }
+    Ok(headers)
+}
+
+/// Only `401` and `403` responses are treated as a rejected token, other errors are not related to the login.
+pub fn is_token_rejected(status: StatusCode) -> bool {
+    status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN
+}
+
+/// Drops the token after the provider rejected it, the next request logs in again.
+pub async fn invalidate_input_token(input: &ConfigInput) {
+    if let Some(auth) = input.auth.as_ref() {
+        debug!("Token of input {} rejected, login required", input.name);
+        *auth.t_token.lock().await = None;
+    }
+}
+
+/// The token header of an input for long running streams.
+/// A reconnect can happen after the token expired, the header is resolved again for each request.
+#[derive(Debug, Clone)]
+pub struct InputTokenHeader {
+    input_name: String,
+    auth: ConfigInputAuth,
+    client: Arc<reqwest::Client>,
+}
+
+impl InputTokenHeader {
+    pub fn new(client: &Arc<reqwest::Client>, input: &ConfigInput) -> Option<Self> {
+        input.auth.as_ref().map(|auth| Self {
+            input_name: input.name.clone(),
+            auth: auth.clone(),
+            client: input.get_http_client(client, &auth.url),
+        })
+    }
+
+    pub async fn get_header(&self) -> Result<(HeaderName, HeaderValue), Error> {
+        let token = get_valid_token(&self.client, &self.auth, &self.input_name).await?;
+        let (name, value) = get_token_header(&self.auth, &token);
+        match (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(&value)) {
+            (Ok(name), Ok(value)) => Ok((name, value)),
+            _ => Err(str_to_io_error(&format!("Invalid token header for input {}", self.input_name))),
+        }
+    }
+
+    pub async fn invalidate(&self) {
+        debug!("Token of input {} rejected, login required", self.input_name);
+        *self.auth.t_token.lock().await = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{HeaderMap, HeaderValue, SET_COOKIE};
+    use crate::model::config::ConfigInputAuth;
+    use reqwest::StatusCode;
+    use crate::utils::network::input_auth::{is_token_rejected, parse_login_response};
+
+    #[test]
+    fn test_parse_login_response() {
+        let auth = ConfigInputAuth {
+            token_path: Some("data.access_token".to_string()),
+            expires_path: Some("data.expires_in".to_string()),
+            ..ConfigInputAuth::default()
+        };
+        let token = parse_login_response(&auth, &HeaderMap::new(), r#"{"data": {"access_token": "abc", "expires_in": 3600}}"#).unwrap();
+        assert_eq!(token.value, "abc");
+        assert!(token.is_valid());
+        assert!(token.expires_at.is_some());
+        assert!(parse_login_response(&auth, &HeaderMap::new(), r#"{"error": "denied"}"#).is_err());
+
+        let cookie_auth = ConfigInputAuth { cookie: Some("session".to_string()), ..ConfigInputAuth::default() };
+        let mut headers = HeaderMap::new();
+        headers.append(SET_COOKIE, HeaderValue::from_static("lang=en; Path=/"));
+        headers.append(SET_COOKIE, HeaderValue::from_static("session=xyz123; Path=/; HttpOnly"));
+        let token = parse_login_response(&cookie_auth, &headers, "").unwrap();
+        assert_eq!(token.value, "xyz123");
+        assert!(token.expires_at.is_none());
+    }
+
+    #[test]
+    fn test_is_token_rejected() {
+        assert!(is_token_rejected(StatusCode::UNAUTHORIZED));
+        assert!(is_token_rejected(StatusCode::FORBIDDEN));
+        assert!(!is_token_rejected(StatusCode::NOT_FOUND));
+        assert!(!is_token_rejected(StatusCode::SERVICE_UNAVAILABLE));
+    }
+}
@@ -2,7 +2,7 @@ use std::sync::Arc;
 use crate::m3u_filter_error::M3uFilterError;
 use crate::model::config::{Config, ConfigInput};
 use crate::model::playlist::PlaylistGroup;
-use crate::processing::parser::m3u;
+use crate::processing::parser::{m3u, pls, xspf};
 use crate::utils::file::file_utils::prepare_file_path;
 use crate::utils::network::request;
 
@@ -11,7 +11,18 @@ pub async fn get_m3u_playlist(client: Arc<reqwest::Client>, cfg: &Config, input:
     let persist_file_path = prepare_file_path(input.persist.as_deref(), working_dir, "");
     match request::get_input_text_content(client, input, working_dir, &url, persist_file_path).await {
         Ok(text) => {
-            (m3u::parse_m3u(cfg, input, text.lines()), vec![])
+            if xspf::is_xspf(&text) {
+                return match xspf::parse_xspf(cfg, input, &text) {
+                    Ok(playlist) => (playlist, vec![]),
+                    Err(err) => (vec![], vec![err]),
+                };
+            }
+            let playlist = if pls::is_pls(&text) {
+                pls::parse_pls(cfg, input, &text)
+            } else {
+                m3u::parse_m3u(cfg, input, text.lines())
+            };
+            (playlist, vec![])
         }
         Err(err) => (vec![], vec![err])
     }
@@ -0,0 +1,24 @@
+use std::sync::Arc;
+use crate::m3u_filter_error::M3uFilterError;
+use crate::model::config::{ConfigInput, InputType};
+use crate::model::playlist::PlaylistGroup;
+use crate::processing::parser::mapped;
+use crate::utils::file::file_utils::prepare_file_path;
+use crate::utils::network::request;
+
+pub async fn get_mapped_playlist(client: Arc<reqwest::Client>, input: &ConfigInput, working_dir: &str) -> (Vec<PlaylistGroup>, Vec<M3uFilterError>) {
+    let url = input.url.clone();
+    let persist_file_path = prepare_file_path(input.persist.as_deref(), working_dir, "");
+    let result = match request::get_input_text_content(client, input, working_dir, &url, persist_file_path).await {
+        Ok(text) => if input.input_type == InputType::Json {
+            mapped::parse_json_playlist(input, &text)
+        } else {
+            mapped::parse_csv_playlist(input, &text)
+        },
+        Err(err) => Err(err),
+    };
+    match result {
+        Ok(groups) => (groups, vec![]),
+        Err(err) => (vec![], vec![err]),
+    }
+}
@@ -1,4 +1,9 @@
 pub mod request;
 pub mod xtream;
 pub mod m3u;
-pub mod epg;
\ No newline at end of file
+pub mod epg;
+pub mod stalker;
+pub mod hdhomerun;
+pub mod mapped;
+pub mod http_cache;
+pub mod input_auth;
@@ -5,13 +5,14 @@ use std::io::{BufWriter, Error, ErrorKind, Read, Write};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use flate2::read::{GzDecoder, ZlibDecoder};
 use futures::StreamExt;
 use log::{debug, error, log_enabled, trace, Level};
 use reqwest::header::CONTENT_ENCODING;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::StatusCode;
 use url::Url;
 
 use crate::m3u_filter_error::create_m3u_filter_error_result;
@@ -20,19 +21,22 @@ use crate::model::config::{ConfigInput, InputFetchMethod};
 use crate::model::stats::format_elapsed_time;
 use crate::repository::storage::{get_input_storage_path, short_hash};
 use crate::repository::storage_const;
-use crate::utils::compression::compression_utils::{is_deflate, is_gzip};
+use crate::utils::compression::compression_utils::{decompress_to_string, is_compressed};
 use crate::utils::constants::{CONSTANTS, DASH_EXT, DASH_EXT_FRAGMENT, DASH_EXT_QUERY, ENCODING_DEFLATE, ENCODING_GZIP, HLS_EXT, HLS_EXT_FRAGMENT, HLS_EXT_QUERY};
 use crate::utils::debug_if_enabled;
 use crate::utils::file::file_utils::{get_file_path, persist_file};
+use crate::utils::network::input_auth;
+use crate::utils::network::http_cache::{read_http_cache_info, write_http_cache_info, HttpCacheInfo};
 
 pub const fn bytes_to_megabytes(bytes: u64) -> u64 {
     bytes / 1_048_576
 }
 
-pub async fn get_input_text_content_as_file(client: Arc<reqwest::Client>, input: &ConfigInput, working_dir: &str, url_str: &str, persist_filepath: Option<PathBuf>) -> Result<PathBuf, M3uFilterError> {
+/// Downloads the content into the `persist_filepath` or into the input storage directory with the given `file_name`.
+pub async fn get_input_text_content_as_file(client: Arc<reqwest::Client>, input: &ConfigInput, working_dir: &str, url_str: &str, file_name: &str, persist_filepath: Option<PathBuf>) -> Result<PathBuf, M3uFilterError> {
     debug_if_enabled!("getting input text content working_dir: {}, url: {}", working_dir, sanitize_sensitive_info(url_str));
     if url_str.parse::<url::Url>().is_ok() {
-        match download_text_content_as_file(client, input, url_str, working_dir, persist_filepath).await {
+        match download_text_content_as_file(client, input, url_str, working_dir, file_name, persist_filepath).await {
             Ok(content) => Ok(content),
             Err(e) => {
                 error!("cant download input url: {}  => {}", sanitize_sensitive_info(url_str), sanitize_sensitive_info(e.to_string().as_str()));
@@ -79,7 +83,9 @@ pub async fn get_input_text_content(client: Arc<reqwest::Client>, input: &Config
     debug_if_enabled!("getting input text content working_dir: {}, url: {}", working_dir, sanitize_sensitive_info(url_str));
 
     if url_str.parse::<url::Url>().is_ok() {
-        match download_text_content(client, input, url_str, persist_filepath).await {
+        let cache_file = get_input_storage_path(&input.name, working_dir).ok()
+            .map(|path| path.join(format!("{}_{}", short_hash(url_str), storage_const::FILE_PLAYLIST)));
+        match download_text_content_with_cache(client, input, url_str, persist_filepath, cache_file, None).await {
             Ok((content, _response_url)) => Ok(content),
             Err(e) => {
                 error!("cant download input url: {}  => {}", sanitize_sensitive_info(url_str), sanitize_sensitive_info(e.to_string().as_str()));
@@ -121,6 +127,18 @@ pub async fn get_input_text_content(client: Arc<reqwest::Client>, input: &Config
     }
 }
 
+/// Creates a http client, if a proxy url is given all requests are sent through it (http, https and socks5).
+pub fn create_http_client(connect_timeout_secs: u32, proxy: Option<&str>) -> Result<reqwest::Client, reqwest::Error> {
+    let mut builder = reqwest::Client::builder().http1_only();
+    if connect_timeout_secs > 0 {
+        builder = builder.connect_timeout(Duration::from_secs(u64::from(connect_timeout_secs)));
+    }
+    if let Some(proxy_url) = proxy {
+        builder = builder.proxy(reqwest::Proxy::all(proxy_url)?);
+    }
+    builder.build()
+}
+
 pub fn get_client_request(client: &Arc<reqwest::Client>,
                           method: InputFetchMethod,
                           headers: Option<&HashMap<String, String>>,
@@ -177,13 +195,9 @@ pub fn get_local_file_content(file_path: &PathBuf) -> Result<String, Error> {
     // Check if the file is accessible
     if file_path.exists() && file_path.is_file() {
         if let Ok(content) = fs::read(file_path) {
-            if content.len() >= 2 && is_gzip(&content[0..2]) {
-                let mut decoder = GzDecoder::new(&content[..]);
-                let mut decode_buffer = String::new();
-                return match decoder.read_to_string(&mut decode_buffer) {
-                    Ok(_) => Ok(decode_buffer),
-                    Err(err) => Err(str_to_io_error(&format!("failed to decode gzip content {err}")))
-                };
+            if is_compressed(&content) {
+                return decompress_to_string(&content)
+                    .map_err(|err| str_to_io_error(&format!("failed to decompress content {err}")));
             }
             return Ok(String::from_utf8_lossy(&content).parse().unwrap());
         }
@@ -193,12 +207,36 @@ pub fn get_local_file_content(file_path: &PathBuf) -> Result<String, Error> {
 }
 
 
+async fn send_input_request(client: &Arc<reqwest::Client>, input: &ConfigInput, url: &Url, extra_headers: Option<HeaderMap>) -> Result<reqwest::Response, Error> {
+    let client = input.get_http_client(client, url.as_str());
+    let send = |headers: HashMap<String, String>| {
+        let mut request = get_client_request(&client, input.method, Some(&headers), url, None);
+        if let Some(extra) = extra_headers.clone() {
+            request = request.headers(extra);
+        }
+        request.send()
+    };
+    let headers = input_auth::get_input_headers(&client, input).await?;
+    let mut result = send(headers).await;
+    if input.auth.is_some() && matches!(&result, Ok(response) if input_auth::is_token_rejected(response.status())) {
+        // the token was rejected before it expired, login again and retry once
+        input_auth::invalidate_input_token(input).await;
+        let headers = input_auth::get_input_headers(&client, input).await?;
+        result = send(headers).await;
+    }
+    result.map_err(|err| str_to_io_error(&format!("Request failed {} {err}", sanitize_sensitive_info(url.as_str()))))
+}
+
 async fn get_remote_content_as_file(client: Arc<reqwest::Client>, input: &ConfigInput, url: &Url, file_path: &Path) -> Result<PathBuf, std::io::Error> {
     let start_time = Instant::now();
-    let request = get_client_request(&client, input.method, Some(&input.headers), url, None);
-    match request.send().await {
+    let conditional_headers = read_http_cache_info(file_path).map(|cache_info| cache_info.get_conditional_headers());
+    match send_input_request(&client, input, url, conditional_headers).await {
         Ok(response) => {
-            if response.status().is_success() {
+            if response.status() == StatusCode::NOT_MODIFIED && file_path.is_file() {
+                debug_if_enabled!("Content not modified, using cached file {file_path:?} for {}", sanitize_sensitive_info(url.as_str()));
+                Ok(file_path.to_path_buf())
+            } else if response.status().is_success() {
+                let cache_info = HttpCacheInfo::from_headers(response.headers());
                 // Open a file in write mode
                 let mut file = BufWriter::with_capacity(8192, File::create(file_path)?);
                 // Stream the response body in chunks
@@ -209,12 +247,17 @@ async fn get_remote_content_as_file(client: Arc<reqwest::Client>, input: &Config
                             file.write_all(&bytes)?;
                         }
                         Err(err) => {
+                            // the partial file is useless
+                            drop(file);
+                            let _ = std::fs::remove_file(file_path);
+                            write_http_cache_info(file_path, None);
                             return Err(str_to_io_error(&format!("Failed to read chunk: {err}")));
                         }
                     }
                 }
 
                 file.flush()?;
+                write_http_cache_info(file_path, cache_info.as_ref());
                 let elapsed = start_time.elapsed().as_secs();
                 debug!("File downloaded successfully to {file_path:?}, took:{}", format_elapsed_time(elapsed));
                 Ok(file_path.to_path_buf())
@@ -222,69 +265,76 @@ async fn get_remote_content_as_file(client: Arc<reqwest::Client>, input: &Config
                 Err(str_to_io_error(&format!("Request failed with status {} {}", response.status(), sanitize_sensitive_info(url.as_str()))))
             }
         }
-        Err(err) => Err(str_to_io_error(&format!("Request failed: {} {err}", sanitize_sensitive_info(url.as_str())))),
+        Err(err) => Err(err),
     }
 }
 
-async fn get_remote_content(client: Arc<reqwest::Client>, input: &ConfigInput, url: &Url) -> Result<(String, String), Error> {
+fn decode_response_content(bytes: &[u8], content_encoding: Option<String>) -> Result<String, Error> {
+    // the compression is detected by the magic bytes, the content encoding header is the fallback
+    if is_compressed(bytes) {
+        return decompress_to_string(bytes).map_err(|err| str_to_io_error(&format!("failed to decompress content {err}")));
+    }
+
+    let mut decode_buffer = String::new();
+    if let Some(encoding_type) = content_encoding {
+        match encoding_type.as_str() {
+            ENCODING_GZIP => {
+                let mut decoder = GzDecoder::new(bytes);
+                match decoder.read_to_string(&mut decode_buffer) {
+                    Ok(_) => {}
+                    Err(err) => return Err(str_to_io_error(&format!("failed to decode gzip content {err}")))
+                }
+            }
+            ENCODING_DEFLATE => {
+                let mut decoder = ZlibDecoder::new(bytes);
+                match decoder.read_to_string(&mut decode_buffer) {
+                    Ok(_) => {}
+                    Err(err) => return Err(str_to_io_error(&format!("failed to decode zlib content {err}")))
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if decode_buffer.is_empty() {
+        String::from_utf8(bytes.to_vec()).map_err(|err| str_to_io_error(&format!("failed to plain text content {err}")))
+    } else {
+        Ok(decode_buffer)
+    }
+}
+
+/// Downloads the content, if a `cache_file` is given a conditional request is sent
+/// and the cached content is reused when the server responds with `304 Not Modified`.
+async fn get_remote_content(client: Arc<reqwest::Client>, input: &ConfigInput, url: &Url, cache_file: Option<&Path>, stream_headers: Option<HeaderMap>) -> Result<(String, String), Error> {
     let start_time = Instant::now();
-    let request = get_client_request(&client, input.method, Some(&input.headers), url, None);
-    match request.send().await {
+    let conditional_headers = cache_file.and_then(read_http_cache_info).map(|cache_info| cache_info.get_conditional_headers());
+    let extra_headers = match (conditional_headers, stream_headers) {
+        (Some(mut headers), Some(stream_headers)) => {
+            headers.extend(stream_headers);
+            Some(headers)
+        }
+        (headers, None) | (None, headers) => headers,
+    };
+    match send_input_request(&client, input, url, extra_headers).await {
         Ok(response) => {
-            let is_success = response.status().is_success();
-            if is_success {
-                let response_url = response.url().to_string();
+            let response_url = response.url().to_string();
+            if let (StatusCode::NOT_MODIFIED, Some(cached_file)) = (response.status(), cache_file) {
+                debug_if_enabled!("Content not modified, using cached file {cached_file:?} for {}", sanitize_sensitive_info(url.as_str()));
+                return get_local_file_content(&cached_file.to_path_buf()).map(|content| (content, response_url));
+            }
+            if response.status().is_success() {
                 let headers = response.headers();
                 debug!("{headers:?}");
-                let header_value = headers.get(CONTENT_ENCODING);
-                let mut encoding = header_value.and_then(|encoding_header| encoding_header.to_str().map_or(None, |value| Some(value.to_string())));
+                let cache_info = HttpCacheInfo::from_headers(headers);
+                let encoding = headers.get(CONTENT_ENCODING).and_then(|encoding_header| encoding_header.to_str().map_or(None, |value| Some(value.to_string())));
                 match response.bytes().await {
                     Ok(bytes) => {
-                        if bytes.len() >= 2 {
-                            if is_gzip(&bytes[0..2]) {
-                                encoding = Some(ENCODING_GZIP.to_string());
-                            } else if is_deflate(&bytes[0..2]) {
-                                encoding = Some(ENCODING_DEFLATE.to_string());
-                            }
-                        }
-
-                        let mut decode_buffer = String::new();
-                        if let Some(encoding_type) = encoding {
-                            match encoding_type.as_str() {
-                                ENCODING_GZIP => {
-                                    let mut decoder = GzDecoder::new(&bytes[..]);
-                                    match decoder.read_to_string(&mut decode_buffer) {
-                                        Ok(_) => {}
-                                        Err(err) => return Err(str_to_io_error(&format!("failed to decode gzip content {err}")))
-                                    }
-                                }
-                                ENCODING_DEFLATE => {
-                                    let mut decoder = ZlibDecoder::new(&bytes[..]);
-                                    match decoder.read_to_string(&mut decode_buffer) {
-                                        Ok(_) => {}
-                                        Err(err) => return Err(str_to_io_error(&format!("failed to decode zlib content {err}")))
-                                    }
-                                }
-                                _ => {}
-                            }
-                        }
-
-                        if decode_buffer.is_empty() {
-                            let content_bytes = bytes.to_vec();
-                            match String::from_utf8(content_bytes) {
-                                Ok(decoded_content) => {
-                                    debug_if_enabled!("Request took:{} {}", format_elapsed_time(start_time.elapsed().as_secs()), sanitize_sensitive_info(url.as_str()));
-                                    Ok((decoded_content, response_url))
-                                }
-                                Err(err) => {
-                                    println!("{err:?}");
-                                    Err(str_to_io_error(&format!("failed to plain text content {err}")))
-                                }
-                            }
-                        } else {
-                            debug_if_enabled!("Request took:{},  {}", format_elapsed_time(start_time.elapsed().as_secs()), sanitize_sensitive_info(url.as_str()));
-                            Ok((decode_buffer, response_url))
+                        let content = decode_response_content(&bytes, encoding)?;
+                        debug_if_enabled!("Request took:{} {}", format_elapsed_time(start_time.elapsed().as_secs()), sanitize_sensitive_info(url.as_str()));
+                        if let Some(cached_file) = cache_file {
+                            update_cached_content(cached_file, cache_info.as_ref(), &content);
                         }
+                        Ok((content, response_url))
                     }
                     Err(err) => Err(str_to_io_error(&format!("failed to read response {} {err}", sanitize_sensitive_info(url.as_str()))))
                 }
@@ -292,11 +342,25 @@ async fn get_remote_content(client: Arc<reqwest::Client>, input: &ConfigInput, u
                 Err(str_to_io_error(&format!("Request failed with status {} {}", response.status(), sanitize_sensitive_info(url.as_str()))))
             }
         }
-        Err(err) => Err(str_to_io_error(&format!("Request failed {} {err}", sanitize_sensitive_info(url.as_str()))))
+        Err(err) => Err(err)
     }
 }
 
-pub async fn download_text_content_as_file(client: Arc<reqwest::Client>, input: &ConfigInput, url_str: &str, working_dir: &str, persist_filepath: Option<PathBuf>) -> Result<PathBuf, Error> {
+fn update_cached_content(cache_file: &Path, cache_info: Option<&HttpCacheInfo>, content: &str) {
+    // Only keep a copy if the server supports conditional requests
+    if cache_info.is_some() {
+        if let Err(err) = fs::write(cache_file, content) {
+            error!("Failed to write cache file {}: {err}", cache_file.display());
+            write_http_cache_info(cache_file, None);
+            return;
+        }
+    } else if cache_file.exists() {
+        let _ = fs::remove_file(cache_file);
+    }
+    write_http_cache_info(cache_file, cache_info);
+}
+
+pub async fn download_text_content_as_file(client: Arc<reqwest::Client>, input: &ConfigInput, url_str: &str, working_dir: &str, file_name: &str, persist_filepath: Option<PathBuf>) -> Result<PathBuf, Error> {
     if let Ok(url) = url_str.parse::<url::Url>() {
         if url.scheme() == "file" {
             url.to_file_path().map_or_else(|()| Err(Error::new(ErrorKind::Unsupported, format!("Unknown file {}", sanitize_sensitive_info(url_str)))), |file_path| if file_path.exists() {
@@ -307,7 +371,7 @@ pub async fn download_text_content_as_file(client: Arc<reqwest::Client>, input:
         } else {
             let file_path = persist_filepath.map_or_else(|| match get_input_storage_path(&input.name, working_dir) {
                 Ok(download_path) => {
-                    Ok(download_path.join(format!("{}_{}", short_hash(url_str), storage_const::FILE_EPG)))
+                    Ok(download_path.join(format!("{}_{file_name}", short_hash(url_str))))
                 }
                 Err(err) => Err(err)
             }, Ok);
@@ -321,15 +385,25 @@ pub async fn download_text_content_as_file(client: Arc<reqwest::Client>, input:
     }
 }
 
-
 pub async fn download_text_content(client: Arc<reqwest::Client>, input: &ConfigInput, url_str: &str, persist_filepath: Option<PathBuf>) -> Result<(String, String), Error> {
+    download_text_content_with_cache(client, input, url_str, persist_filepath, None, None).await
+}
+
+/// Downloads the content with the request headers of a playlist item, like the user agent of the `#EXTVLCOPT` options.
+pub async fn download_text_content_with_headers(client: Arc<reqwest::Client>, input: &ConfigInput, url_str: &str, stream_headers: &HashMap<String, String>) -> Result<(String, String), Error> {
+    let stream_headers = (!stream_headers.is_empty()).then(|| get_request_headers(Some(stream_headers), None));
+    download_text_content_with_cache(client, input, url_str, None, None, stream_headers).await
+}
+
+async fn download_text_content_with_cache(client: Arc<reqwest::Client>, input: &ConfigInput, url_str: &str, persist_filepath: Option<PathBuf>,
+                                          cache_file: Option<PathBuf>, stream_headers: Option<HeaderMap>) -> Result<(String, String), Error> {
     if let Ok(url) = url_str.parse::<url::Url>() {
         let result = if url.scheme() == "file" {
             url.to_file_path().map_or_else(|()| Err(str_to_io_error(&format!("Unknown file {}", sanitize_sensitive_info(url_str)))), |file_path|
                 get_local_file_content(&file_path).map(|c| (c, url.to_string()))
             )
         } else {
-            get_remote_content(client, input, &url).await
+            get_remote_content(client, input, &url, cache_file.as_deref(), stream_headers).await
         };
         match result {
             Ok((content, response_url)) => {
@@ -498,7 +572,68 @@ pub fn get_base_url_from_str(url: &str) -> Option<String> {
 
 #[cfg(test)]
 mod tests {
-    use crate::utils::network::request::{get_base_url_from_str, replace_url_extension, sanitize_sensitive_info};
+    use std::sync::Arc;
+    use crate::model::config::{ConfigInput, ConfigInputAlias};
+    use crate::utils::network::request::{create_http_client, get_base_url_from_str, replace_url_extension, sanitize_sensitive_info};
+
+    #[test]
+    fn test_input_proxy_client() {
+        assert!(create_http_client(10, Some("socks5://127.0.0.1:1080")).is_ok());
+        assert!(create_http_client(10, Some("not a proxy")).is_err());
+
+        let shared_client = Arc::new(reqwest::Client::new());
+        let alias_client = Arc::new(create_http_client(10, Some("http://127.0.0.1:3128")).unwrap());
+        let input = ConfigInput {
+            t_base_url: "http://provider.tv".to_string(),
+            aliases: Some(vec![ConfigInputAlias {
+                t_base_url: "http://alias.tv".to_string(),
+                t_client: Some(Arc::clone(&alias_client)),
+                ..ConfigInputAlias::default()
+            }]),
+            ..ConfigInput::default()
+        };
+        assert!(Arc::ptr_eq(&input.get_http_client(&shared_client, "http://provider.tv/live/1.ts"), &shared_client));
+        assert!(Arc::ptr_eq(&input.get_http_client(&shared_client, "http://alias.tv/live/1.ts"), &alias_client));
+        assert!(Arc::ptr_eq(&input.get_http_client(&shared_client, "http://alias.tv.other.com/live/1.ts"), &shared_client));
+
+        // the alias account is on the same host as the input
+        let input = ConfigInput {
+            t_base_url: "http://provider.tv".to_string(),
+            username: Some("user1".to_string()),
+            password: Some("pass1".to_string()),
+            aliases: Some(vec![ConfigInputAlias {
+                t_base_url: "http://provider.tv".to_string(),
+                username: Some("user2".to_string()),
+                password: Some("pass2".to_string()),
+                t_client: Some(Arc::clone(&alias_client)),
+                ..ConfigInputAlias::default()
+            }]),
+            ..ConfigInput::default()
+        };
+        assert!(Arc::ptr_eq(&input.get_http_client(&shared_client, "http://provider.tv/live/user1/pass1/1.ts"), &shared_client));
+        assert!(Arc::ptr_eq(&input.get_http_client(&shared_client, "http://provider.tv/live/user2/pass2/1.ts"), &alias_client));
+        assert!(Arc::ptr_eq(&input.get_http_client(&shared_client, "http://provider.tv/get.php?username=user2&password=pass2"), &alias_client));
+        assert!(Arc::ptr_eq(&input.get_http_client(&shared_client, "http://provider.tv/live/user2/pass22/1.ts"), &shared_client));
+    }
+
+    #[test]
+    fn test_input_proxy_client_by_name() {
+        let shared_client = Arc::new(reqwest::Client::new());
+        let alias_client = Arc::new(create_http_client(10, Some("http://127.0.0.1:3128")).unwrap());
+        let input = ConfigInput {
+            name: "provider".to_string(),
+            aliases: Some(vec![ConfigInputAlias {
+                name: "alias".to_string(),
+                t_client: Some(Arc::clone(&alias_client)),
+                ..ConfigInputAlias::default()
+            }]),
+            ..ConfigInput::default()
+        };
+        assert!(Arc::ptr_eq(&input.get_http_client_by_name(&shared_client, Some("alias")), &alias_client));
+        assert!(Arc::ptr_eq(&input.get_http_client_by_name(&shared_client, Some("provider")), &shared_client));
+        assert!(Arc::ptr_eq(&input.get_http_client_by_name(&shared_client, Some("unknown")), &shared_client));
+        assert!(Arc::ptr_eq(&input.get_http_client_by_name(&shared_client, None), &shared_client));
+    }
 
     #[test]
     fn test_url_mask() {
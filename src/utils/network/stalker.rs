@@ -0,0 +1,270 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+use log::{debug, warn};
+use reqwest::header::{HeaderName, HeaderValue, AUTHORIZATION, COOKIE, USER_AGENT};
+use serde_json::Value;
+use url::Url;
+use crate::m3u_filter_error::{info_err, M3uFilterError, M3uFilterErrorKind};
+use crate::model::config::ConfigInput;
+use crate::model::playlist::{PlaylistGroup, XtreamCluster};
+use crate::processing::parser::stalker;
+use crate::processing::parser::stalker::{STALKER_TYPE_LIVE, STALKER_TYPE_SERIES, STALKER_TYPE_VOD};
+use crate::utils::json_utils::{get_string_from_serde_value, get_u64_from_serde_value};
+use crate::utils::network::request::{get_request_headers, sanitize_sensitive_info};
+use crate::utils::network::xtream::get_skip_cluster;
+
+const STALKER_USER_AGENT: &str = "Mozilla/5.0 (QtEmbedded; U; Linux; C) AppleWebKit/533.3 (KHTML, like Gecko) MAG200 stbapp ver: 2 rev: 250 Safari/533.3";
+const STALKER_X_USER_AGENT: &str = "Model: MAG250; Link: WiFi";
+const STALKER_MAX_PAGES: u64 = 1_000;
+
+/// Returns the api endpoint of a portal. The input url can be the portal url like `http://host/stalker_portal/c/`
+/// or the full api url like `http://host/portal.php`.
+pub fn get_stalker_api_url(portal_url: &str) -> String {
+    let url = portal_url.trim().trim_end_matches('/');
+    if std::path::Path::new(url).extension().is_some_and(|ext| ext.eq_ignore_ascii_case("php")) {
+        url.to_string()
+    } else {
+        format!("{}/server/load.php", url.strip_suffix("/c").unwrap_or(url))
+    }
+}
+
+struct StalkerSession<'a> {
+    client: Arc<reqwest::Client>,
+    input: &'a ConfigInput,
+    mac: String,
+    token: String,
+}
+
+impl<'a> StalkerSession<'a> {
+    fn get_headers(&self) -> reqwest::header::HeaderMap {
+        let mut headers = get_request_headers(Some(&self.input.headers), None);
+        if !headers.contains_key(USER_AGENT) {
+            headers.insert(USER_AGENT, HeaderValue::from_static(STALKER_USER_AGENT));
+        }
+        headers.insert(HeaderName::from_static("x-user-agent"), HeaderValue::from_static(STALKER_X_USER_AGENT));
+        if let Ok(cookie) = HeaderValue::from_str(&format!("mac={}; stb_lang=en; timezone=UTC", self.mac)) {
+            headers.insert(COOKIE, cookie);
+        }
+        if !self.token.is_empty() {
+            if let Ok(auth) = HeaderValue::from_str(&format!("Bearer {}", self.token)) {
+                headers.insert(AUTHORIZATION, auth);
+            }
+        }
+        headers
+    }
+
+    async fn get_url(&self, url: &str) -> Result<Value, M3uFilterError> {
+        let response = self.client.get(url).headers(self.get_headers()).send().await
+            .map_err(|err| info_err!(format!("Portal request failed {} {err}", sanitize_sensitive_info(url))))?;
+        if !response.status().is_success() {
+            return Err(info_err!(format!("Portal request failed with status {} {}", response.status(), sanitize_sensitive_info(url))));
+        }
+        let mut doc: Value = response.json().await
+            .map_err(|err| info_err!(format!("Portal response is not valid json {} {err}", sanitize_sensitive_info(url))))?;
+        Ok(doc.get_mut("js").map_or(Value::Null, Value::take))
+    }
+
+    async fn request(&self, stalker_type: &str, action: &str, params: &[(&str, &str)]) -> Result<Value, M3uFilterError> {
+        let api_url = get_stalker_api_url(&self.input.url);
+        let mut query = vec![("type", stalker_type), ("action", action)];
+        query.extend_from_slice(params);
+        query.push(("JsHttpRequest", "1-xml"));
+        let url = Url::parse_with_params(&api_url, &query).map_err(|err| info_err!(format!("Invalid portal url {api_url} {err}")))?;
+        self.get_url(url.as_str()).await
+    }
+
+    fn new(client: &Arc<reqwest::Client>, input: &'a ConfigInput) -> Self {
+        let mac = input.username.as_ref().map(|mac| mac.trim().to_uppercase()).unwrap_or_default();
+        let client = input.get_http_client(client, &input.url);
+        Self { client, input, mac, token: String::new() }
+    }
+
+    // The token is stored at the input, stream links are created with it until the portal rejects it.
+    async fn handshake(&mut self) -> Result<(), M3uFilterError> {
+        self.token.clear();
+        let js = self.request("stb", "handshake", &[("token", "")]).await?;
+        self.token = js.get("token").and_then(get_string_from_serde_value)
+            .ok_or_else(|| info_err!(format!("Portal handshake failed for input {}", self.input.name)))?;
+        // the profile request activates the token on most portals
+        if let Err(err) = self.request("stb", "get_profile", &[]).await {
+            debug!("Portal profile request failed for input {}: {err}", self.input.name);
+        }
+        *self.input.t_portal_token.lock().await = Some(self.token.clone());
+        Ok(())
+    }
+
+    async fn create_link(&self, link_url: &str) -> Result<String, M3uFilterError> {
+        let js = self.get_url(link_url).await?;
+        js.get("cmd").and_then(Value::as_str).and_then(stalker::get_stalker_cmd_url)
+            .ok_or_else(|| info_err!(format!("Portal returned no stream link for input {}", self.input.name)))
+    }
+
+    async fn get_ordered_list(&self, stalker_type: &str, category_id: &str) -> Result<Vec<Value>, M3uFilterError> {
+        let mut result = vec![];
+        for page in 1..=STALKER_MAX_PAGES {
+            let page_str = page.to_string();
+            let js = self.request(stalker_type, "get_ordered_list", &[("category", category_id), ("p", &page_str)]).await?;
+            let total_items = js.get("total_items").and_then(get_u64_from_serde_value).unwrap_or(0);
+            match js.get("data").and_then(Value::as_array) {
+                Some(data) if !data.is_empty() => result.extend(data.iter().cloned()),
+                _ => break,
+            }
+            if result.len() as u64 >= total_items {
+                break;
+            }
+        }
+        Ok(result)
+    }
+
+    async fn get_category_items(&self, stalker_type: &str) -> Result<Vec<PlaylistGroup>, M3uFilterError> {
+        let categories = self.request(stalker_type, "get_categories", &[]).await?;
+        let mut items = vec![];
+        let category_ids: Vec<String> = categories.as_array()
+            .map(|list| list.iter().filter_map(|c| c.get("id").and_then(get_string_from_serde_value)).collect()).unwrap_or_default();
+        for category_id in category_ids {
+            // `*` is the virtual category containing everything
+            if category_id == "*" {
+                continue;
+            }
+            let category_items = self.get_ordered_list(stalker_type, &category_id).await?;
+            items.push((category_id, category_items));
+        }
+        Ok(stalker::parse_stalker_category_items(self.input, &get_stalker_api_url(&self.input.url), stalker_type, &categories, &items))
+    }
+
+    async fn get_live(&self) -> Result<Vec<PlaylistGroup>, M3uFilterError> {
+        let genres = self.request(STALKER_TYPE_LIVE, "get_genres", &[]).await?;
+        let channels = self.request(STALKER_TYPE_LIVE, "get_all_channels", &[]).await?;
+        let channel_list = channels.get("data").and_then(Value::as_array).map_or_else(Vec::new, Clone::clone);
+        Ok(stalker::parse_stalker_live(self.input, &get_stalker_api_url(&self.input.url), &genres, &channel_list))
+    }
+}
+
+pub async fn get_stalker_playlist(client: Arc<reqwest::Client>, input: &ConfigInput) -> (Vec<PlaylistGroup>, Vec<M3uFilterError>) {
+    let mut session = StalkerSession::new(&client, input);
+    if let Err(err) = session.handshake().await {
+        warn!("Failed to login portal {}: {err}", input.name);
+        return (vec![], vec![err]);
+    }
+    let skip_cluster = get_skip_cluster(input);
+    let mut playlist_groups = vec![];
+    let mut errors = vec![];
+    for (cluster, stalker_type) in [(XtreamCluster::Live, STALKER_TYPE_LIVE), (XtreamCluster::Video, STALKER_TYPE_VOD), (XtreamCluster::Series, STALKER_TYPE_SERIES)] {
+        if skip_cluster.contains(&cluster) {
+            continue;
+        }
+        let result = if cluster == XtreamCluster::Live { session.get_live().await } else { session.get_category_items(stalker_type).await };
+        match result {
+            Ok(mut groups) => playlist_groups.append(&mut groups),
+            Err(err) => errors.push(err),
+        }
+    }
+    for (idx, group) in playlist_groups.iter_mut().enumerate() {
+        #[allow(clippy::cast_possible_truncation)]
+        let id = idx as u32 + 1;
+        group.id = id;
+    }
+    (playlist_groups, errors)
+}
+
+/// Resolves the stored `create_link` request of a portal item into the current stream url.
+/// The link is created with the account of `provider_name`, which is the input or one of its aliases.
+/// A handshake is only done if there is no token for the account yet or the portal rejects it.
+pub async fn resolve_stalker_stream_url(client: Arc<reqwest::Client>, input: &ConfigInput, provider_name: Option<&str>, link_url: &str) -> Result<String, M3uFilterError> {
+    let alias = provider_name.filter(|name| *name != input.name)
+        .and_then(|name| input.aliases.iter().flatten().find(|alias| alias.name == name));
+    let (account_input, link_url) = match alias {
+        Some(alias) => (Cow::Owned(input.create_alias_input(alias)),
+                        Cow::Owned(link_url.replacen(&get_stalker_api_url(&input.url), &get_stalker_api_url(&alias.url), 1))),
+        None => (Cow::Borrowed(input), Cow::Borrowed(link_url)),
+    };
+    let mut session = StalkerSession::new(&client, &account_input);
+    let cached_token = account_input.t_portal_token.lock().await.clone();
+    if let Some(token) = cached_token {
+        session.token = token;
+        match session.create_link(&link_url).await {
+            Ok(stream_url) => return Ok(stream_url),
+            Err(err) => debug!("Portal token of input {} rejected, handshake required: {err}", account_input.name),
+        }
+    }
+    session.handshake().await?;
+    session.create_link(&link_url).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use axum::extract::Query;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::response::IntoResponse;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use crate::model::config::{ConfigInput, InputType};
+    use crate::model::playlist::XtreamCluster;
+    use super::{get_stalker_api_url, get_stalker_playlist, resolve_stalker_stream_url};
+
+    static HANDSHAKES: AtomicUsize = AtomicUsize::new(0);
+
+    async fn mock_portal(headers: HeaderMap, Query(params): Query<HashMap<String, String>>) -> axum::response::Response {
+        let action = params.get("action").map(String::as_str);
+        if action == Some("create_link") && headers.get("authorization").is_none_or(|auth| auth != "Bearer TOKEN") {
+            return StatusCode::UNAUTHORIZED.into_response();
+        }
+        let js = match (params.get("type").map(String::as_str), action) {
+            (Some("stb"), Some("handshake")) => {
+                HANDSHAKES.fetch_add(1, Ordering::SeqCst);
+                json!({"token": "TOKEN"})
+            }
+            (Some("itv"), Some("get_genres")) => json!([{"id": "1", "title": "News"}]),
+            (Some("itv"), Some("get_all_channels")) => json!({"data": [{"id": 1, "name": "News 1", "cmd": "ffmpeg http://localhost/ch/1_", "tv_genre_id": "1"}]}),
+            (Some("vod" | "series"), Some("get_categories")) => json!([{"id": "*", "title": "All"}, {"id": "2", "title": "Movies"}]),
+            (Some("vod"), Some("get_ordered_list")) => json!({"total_items": 1, "data": [{"id": "5", "name": "Movie", "cmd": "/media/5.mpg"}]}),
+            (Some("series"), Some("get_ordered_list")) => json!({"total_items": 0, "data": []}),
+            (Some(stalker_type), Some("create_link")) => json!({"cmd": format!("ffmpeg http://stream/{stalker_type}/{}", params.get("cmd").map_or("", String::as_str))}),
+            _ => json!({}),
+        };
+        axum::Json(json!({"js": js})).into_response()
+    }
+
+    #[test]
+    fn test_stalker_api_url() {
+        assert_eq!(get_stalker_api_url("http://host/stalker_portal/c/"), "http://host/stalker_portal/server/load.php");
+        assert_eq!(get_stalker_api_url("http://host/portal.php"), "http://host/portal.php");
+    }
+
+    #[tokio::test]
+    async fn test_stalker_mock_portal() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = axum::Router::new().route("/portal.php", axum::routing::get(mock_portal));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap(); });
+
+        let input = ConfigInput {
+            name: String::from("portal"),
+            input_type: InputType::Stalker,
+            url: format!("http://{addr}/portal.php"),
+            username: Some(String::from("00:1a:79:00:00:01")),
+            ..Default::default()
+        };
+        let client = Arc::new(reqwest::Client::new());
+        let (groups, errors) = get_stalker_playlist(Arc::clone(&client), &input).await;
+        assert!(errors.is_empty());
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].xtream_cluster, XtreamCluster::Live);
+        assert_eq!(groups[1].title, "Movies");
+
+        let link_url = &groups[1].channels[0].header.url;
+        let stream_url = resolve_stalker_stream_url(Arc::clone(&client), &input, None, link_url).await.unwrap();
+        assert_eq!(stream_url, "http://stream/vod//media/5.mpg");
+        // the token of the playlist download is reused
+        assert_eq!(HANDSHAKES.load(Ordering::SeqCst), 1);
+
+        // a rejected token requires a new handshake
+        *input.t_portal_token.lock().await = Some(String::from("EXPIRED"));
+        let stream_url = resolve_stalker_stream_url(Arc::clone(&client), &input, None, link_url).await.unwrap();
+        assert_eq!(stream_url, "http://stream/vod//media/5.mpg");
+        assert_eq!(HANDSHAKES.load(Ordering::SeqCst), 2);
+        assert_eq!(input.t_portal_token.lock().await.as_deref(), Some("TOKEN"));
+    }
+}
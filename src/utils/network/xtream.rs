@@ -1,5 +1,5 @@
 use std::sync::Arc;
-use crate::m3u_filter_error::{str_to_io_error, M3uFilterError};
+use crate::m3u_filter_error::{create_m3u_filter_error_result, info_err, str_to_io_error, M3uFilterError, M3uFilterErrorKind};
 use crate::model::config::{Config, ConfigInput, ConfigTarget};
 use crate::model::playlist::{PlaylistEntry, PlaylistGroup, XtreamCluster, XtreamPlaylistItem};
 use crate::processing::parser::xtream;
@@ -8,10 +8,15 @@ use crate::repository::xtream_repository;
 use log::{info, warn};
 use std::cmp::Ordering;
 use std::io::Error;
+use std::path::Path;
+use serde_json::Value;
 use crate::model::api_proxy::{ProxyUserCredentials};
+use crate::model::provider_account::ProviderAccountInfo;
 use crate::model::xtream_const;
+use crate::utils::compression::compressed_file_reader::CompressedFileReader;
 use crate::utils::json_utils::get_string_from_serde_value;
 use crate::utils::network::request;
+use crate::utils::network::http_cache::write_http_cache_info;
 use crate::utils::network::request::extract_extension_from_url;
 
 
@@ -44,6 +49,14 @@ pub fn get_xtream_player_api_info_url(input: &ConfigInput, cluster: XtreamCluste
 }
 
 
+/// Reads the account `user_info` from `player_api.php`, m3u inputs with credentials in the url are queried too.
+pub async fn get_xtream_account_info(client: Arc<reqwest::Client>, input: &ConfigInput) -> Result<ProviderAccountInfo, M3uFilterError> {
+    let user_info = input.get_user_info().ok_or_else(|| info_err!(format!("No credentials for input {}", input.name)))?;
+    let url = get_xtream_stream_url_base(&user_info.base_url, &user_info.username, &user_info.password);
+    let content = request::get_input_json_content(client, input, &url, None).await?;
+    ProviderAccountInfo::from_player_api(&content).ok_or_else(|| info_err!(format!("No account info for input {}", input.name)))
+}
+
 pub async fn get_xtream_stream_info_content(client: Arc<reqwest::Client>, info_url: &str, input: &ConfigInput) -> Result<String, Error> {
     match request::download_text_content(client, input, info_url, None).await {
         Ok((content, _response_url)) => Ok(content),
@@ -106,7 +119,7 @@ where
                                  target.name.replace(' ', "_").as_str(), &cluster, pli.get_virtual_id())))
 }
 
-fn get_skip_cluster(input: &ConfigInput) -> Vec<XtreamCluster> {
+pub(crate) fn get_skip_cluster(input: &ConfigInput) -> Vec<XtreamCluster> {
     let mut skip_cluster = vec![];
     if let Some(input_options) = &input.options {
         if input_options.xtream_skip_live {
@@ -130,6 +143,22 @@ const ACTIONS: [(XtreamCluster, &str, &str); 3] = [
     (XtreamCluster::Video, xtream_const::XC_ACTION_GET_VOD_CATEGORIES, xtream_const::XC_ACTION_GET_VOD_STREAMS),
     (XtreamCluster::Series, xtream_const::XC_ACTION_GET_SERIES_CATEGORIES, xtream_const::XC_ACTION_GET_SERIES)];
 
+// The stream lists can be huge, they are downloaded to a file and parsed entry by entry.
+fn parse_xtream_stream_file(input: &ConfigInput, xtream_cluster: XtreamCluster, categories: &Value, stream_file: &Path) -> Result<Option<Vec<PlaylistGroup>>, M3uFilterError> {
+    match CompressedFileReader::new(stream_file) {
+        Ok(reader) => xtream::parse_xtream(input, xtream_cluster, categories, reader),
+        Err(err) => create_m3u_filter_error_result!(M3uFilterErrorKind::Notify, "Failed to read xtream streams {}: {err}", stream_file.display()),
+    }
+}
+
+// The downloaded stream list is only needed for parsing, a file of the `persist` option is kept.
+fn remove_stream_file(stream_file: &Path) {
+    if let Err(err) = std::fs::remove_file(stream_file) {
+        warn!("Failed to remove xtream streams file {}: {err}", stream_file.display());
+    }
+    write_http_cache_info(stream_file, None);
+}
+
 pub async fn get_xtream_playlist(client: Arc<reqwest::Client>, input: &ConfigInput, working_dir: &str) -> (Vec<PlaylistGroup>, Vec<M3uFilterError>) {
 
     let username = input.username.as_ref().map_or("", |v| v);
@@ -154,15 +183,19 @@ pub async fn get_xtream_playlist(client: Arc<reqwest::Client>, input: &ConfigInp
             let category_file_path = crate::utils::file::file_utils::prepare_file_path(input.persist.as_deref(), working_dir, format!("{category}_").as_str());
             let stream_file_path = crate::utils::file::file_utils::prepare_file_path(input.persist.as_deref(), working_dir, format!("{stream}_").as_str());
 
+            let stream_file_name = format!("{stream}.json");
+            let is_temp_stream_file = stream_file_path.is_none() && !stream_url.starts_with("file:");
+
             match futures::join!(
                 request::get_input_json_content(Arc::clone(&client), input, category_url.as_str(), category_file_path),
-                request::get_input_json_content(Arc::clone(&client), input, stream_url.as_str(), stream_file_path)
+                request::get_input_text_content_as_file(Arc::clone(&client), input, working_dir, stream_url.as_str(), &stream_file_name, stream_file_path)
             ) {
-                (Ok(category_content), Ok(stream_content)) => {
-                    match xtream::parse_xtream(input,
-                                               *xtream_cluster,
-                                               &category_content,
-                                               &stream_content) {
+                (Ok(category_content), Ok(stream_file)) => {
+                    let parse_result = parse_xtream_stream_file(input, *xtream_cluster, &category_content, &stream_file);
+                    if is_temp_stream_file {
+                        remove_stream_file(&stream_file);
+                    }
+                    match parse_result {
                         Ok(sub_playlist_parsed) => {
                             if let Some(mut xtream_sub_playlist) = sub_playlist_parsed {
                                 playlist_groups.append(&mut xtream_sub_playlist);
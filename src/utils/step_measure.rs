@@ -19,33 +19,61 @@ fn format_duration(duration: Duration) -> String {
 
 pub struct StepMeasure {
     enabled: bool,
+    record: bool,
     msg: String,
     start: Instant,
+    steps: Vec<(String, Duration)>,
 }
 
 impl StepMeasure {
     pub fn new(msg: &str) -> Self {
         Self {
             enabled: log_enabled!(Level::Debug),
+            record: false,
             msg: msg.to_string(),
             start: Instant::now(),
+            steps: Vec::new(),
         }
     }
 
-    pub fn tick(&mut self, msg: &str) {
+    /// Like `new`, but keeps the duration of each step regardless of the log level.
+    pub fn recording(msg: &str) -> Self {
+        let mut measure = Self::new(msg);
+        measure.record = true;
+        measure
+    }
+
+    fn finish_step(&mut self) {
+        let elapsed = self.start.elapsed();
         if self.enabled {
-            debug!("{} in {}", self.msg, format_duration(self.start.elapsed()));
+            debug!("{} in {}", self.msg, format_duration(elapsed));
+        }
+        if self.record {
+            self.steps.push((self.msg.clone(), elapsed));
+        }
+    }
+
+    pub fn tick(&mut self, msg: &str) {
+        if self.enabled || self.record {
+            self.finish_step();
             self.msg = msg.to_string();
             self.start = Instant::now();
         }
     }
 
     pub fn stop(&mut self) {
-        if self.enabled && !self.msg.is_empty() {
-            debug!("{} in {}", self.msg, format_duration( self.start.elapsed()));
+        if (self.enabled || self.record) && !self.msg.is_empty() {
+            self.finish_step();
             self.enabled = false;
+            self.record = false;
         }
     }
+
+    /// Returns the recorded steps, the current step is closed first.
+    pub fn take_steps(&mut self) -> Vec<(String, Duration)> {
+        self.stop();
+        std::mem::take(&mut self.steps)
+    }
 }
 
 impl Drop for StepMeasure {
@@ -41,3 +41,63 @@ pub fn get_trimmed_string(value: &Option<String>) -> Option<String> {
     }
     None
 }
+
+/// Compares two strings treating embedded digit runs as numbers,
+/// so that "Channel 9" is ordered before "Channel 10".
+pub fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+    let mut chars_a = a.chars().peekable();
+    let mut chars_b = b.chars().peekable();
+    loop {
+        match (chars_a.peek().copied(), chars_b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(ca), Some(cb)) if ca.is_ascii_digit() && cb.is_ascii_digit() => {
+                let mut digits_a = String::new();
+                while let Some(c) = chars_a.next_if(char::is_ascii_digit) {
+                    digits_a.push(c);
+                }
+                let mut digits_b = String::new();
+                while let Some(c) = chars_b.next_if(char::is_ascii_digit) {
+                    digits_b.push(c);
+                }
+                let trimmed_a = digits_a.trim_start_matches('0');
+                let trimmed_b = digits_b.trim_start_matches('0');
+                let ordering = trimmed_a.len().cmp(&trimmed_b.len())
+                    .then_with(|| trimmed_a.cmp(trimmed_b))
+                    .then_with(|| digits_a.len().cmp(&digits_b.len()));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(ca), Some(cb)) => {
+                let ordering = ca.cmp(&cb);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                chars_a.next();
+                chars_b.next();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+    use crate::utils::string_utils::natural_cmp;
+
+    #[test]
+    fn test_natural_cmp() {
+        assert_eq!(natural_cmp("Channel 9", "Channel 10"), Ordering::Less);
+        assert_eq!(natural_cmp("Channel 10", "Channel 9"), Ordering::Greater);
+        assert_eq!(natural_cmp("Channel 10", "Channel 10"), Ordering::Equal);
+        assert_eq!(natural_cmp("Channel 2 HD", "Channel 2"), Ordering::Greater);
+        assert_eq!(natural_cmp("A 007", "A 7"), Ordering::Greater);
+        assert_eq!(natural_cmp("abc", "abd"), Ordering::Less);
+        let mut names = vec!["Sport 10", "Sport 1", "Sport 2", "News"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(names, vec!["News", "Sport 1", "Sport 2", "Sport 10"]);
+    }
+}